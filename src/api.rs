@@ -0,0 +1,319 @@
+// src/api.rs - Local HTTP/JSON control API for a running trader
+// Enabled with the `control-api` feature; every route requires a bearer token
+
+use crate::config::ApiConfig;
+use crate::{FastMemeTrader, StrategyType, TakeProfitTier, TimeExitRules, TradeConfig};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::watch;
+use anyhow::{anyhow, Result};
+
+#[derive(Clone)]
+struct ApiState {
+    trader: Arc<FastMemeTrader>,
+    token: Arc<str>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn bad_request(error: impl std::fmt::Display) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, error.to_string())
+}
+
+type ApiResult = std::result::Result<Json<Value>, ApiError>;
+
+fn validate_token(token_address: &str) -> std::result::Result<(), ApiError> {
+    Pubkey::from_str(token_address)
+        .map(|_| ())
+        .map_err(|_| bad_request(format!("Invalid token address: {}", token_address)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuyRequest {
+    token_address: String,
+    amount_sol: f64,
+    #[serde(default)]
+    slippage_bps: Option<u16>,
+    // Same syntax as the CLI: conservative-ath, custom:pb=10,min=4, ...
+    #[serde(default)]
+    strategy: Option<String>,
+    // profit%:sell% pairs, e.g. "30:25,60:25"
+    #[serde(default)]
+    ladder: Option<String>,
+    #[serde(default)]
+    time_exits: Option<TimeExitRules>,
+    // Jito tip in lamports; 0 sends through plain RPC, omitted uses the configured default
+    #[serde(default)]
+    tip_lamports: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SellRequest {
+    token_address: String,
+    // Raw token amount; the full balance if omitted
+    #[serde(default)]
+    amount: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SellAllRequest {
+    confirm: bool,
+}
+
+pub fn router(trader: Arc<FastMemeTrader>, token: &str) -> Router {
+    let state = ApiState {
+        trader,
+        token: Arc::from(token),
+    };
+
+    Router::new()
+        .route("/health", get(health))
+        .route("/stats", get(stats))
+        .route("/positions", get(positions))
+        .route("/ath", get(ath_all))
+        .route("/ath/:token_address", get(ath_one))
+        .route("/buy", post(buy))
+        .route("/sell", post(sell))
+        .route("/sell-all", post(sell_all))
+        .layer(middleware::from_fn_with_state(state.clone(), require_bearer_token))
+        .with_state(state)
+}
+
+// Serve until the shutdown receiver flips, e.g. from daemon::spawn_shutdown_listener
+pub async fn serve(trader: Arc<FastMemeTrader>, config: &ApiConfig, mut shutdown: watch::Receiver<bool>) -> Result<()> {
+    let token = config.token.as_deref()
+        .ok_or_else(|| anyhow!("api.token must be set to enable the control API"))?;
+    let addr = SocketAddr::from_str(&config.bind)
+        .map_err(|e| anyhow!("Invalid api.bind '{}': {}", config.bind, e))?;
+    if !addr.ip().is_loopback() {
+        log::warn!("Control API bound to non-loopback address {}; anyone with the token can trade", addr);
+    }
+
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .map_err(|e| anyhow!("Failed to bind control API on {}: {}", addr, e))?;
+    log::info!("Control API listening on http://{}", addr);
+
+    axum::serve(listener, router(trader, token))
+        .with_graceful_shutdown(async move {
+            while !*shutdown.borrow() {
+                if shutdown.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+        .await?;
+
+    log::info!("Control API stopped");
+    Ok(())
+}
+
+async fn require_bearer_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let provided = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), state.token.as_bytes()) => next.run(request).await,
+        _ => {
+            log::warn!("Rejected unauthorized control API request to {}", request.uri().path());
+            ApiError(StatusCode::UNAUTHORIZED, "missing or invalid bearer token".to_string()).into_response()
+        }
+    }
+}
+
+// Compare without short-circuiting so response timing doesn't leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn to_json(value: impl serde::Serialize) -> ApiResult {
+    serde_json::to_value(value)
+        .map(Json)
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn health(State(state): State<ApiState>) -> ApiResult {
+    let report = state.trader.health_report().await
+        .map_err(|e| ApiError(StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    to_json(report)
+}
+
+async fn stats(State(state): State<ApiState>) -> ApiResult {
+    Ok(Json(json!({
+        "stats": state.trader.trade_stats(),
+        "active_positions": state.trader.positions.read().await.len(),
+        "paper_balance_sol": state.trader.paper_account().map(|paper| paper.sol_balance()),
+    })))
+}
+
+async fn positions(State(state): State<ApiState>) -> ApiResult {
+    to_json(state.trader.position_statuses().await)
+}
+
+async fn ath_all(State(state): State<ApiState>) -> ApiResult {
+    let tokens: Vec<String> = state.trader.positions.read().await.keys().cloned().collect();
+    let mut trackers = serde_json::Map::new();
+    for token in tokens {
+        if let Some(tracker) = state.trader.ath_tracker(&token).await {
+            trackers.insert(token, serde_json::to_value(tracker).unwrap_or(Value::Null));
+        }
+    }
+    Ok(Json(Value::Object(trackers)))
+}
+
+async fn ath_one(State(state): State<ApiState>, Path(token_address): Path<String>) -> ApiResult {
+    match state.trader.ath_tracker(&token_address).await {
+        Some(tracker) => Ok(Json(json!({
+            "tracker": tracker,
+            "status": state.trader.get_ath_status(&token_address).await,
+        }))),
+        None => Err(ApiError(StatusCode::NOT_FOUND, format!("No ATH data for {}", token_address))),
+    }
+}
+
+async fn buy(State(state): State<ApiState>, Json(request): Json<BuyRequest>) -> ApiResult {
+    validate_token(&request.token_address)?;
+    let strategy = match request.strategy.as_deref() {
+        Some(strategy) => StrategyType::from_str(strategy).map_err(bad_request)?,
+        None => StrategyType::ConservativeATH,
+    };
+    let take_profit_ladder = match request.ladder.as_deref() {
+        Some(ladder) => TakeProfitTier::parse_ladder(ladder).map_err(bad_request)?,
+        None => Vec::new(),
+    };
+
+    let config = TradeConfig {
+        token_address: request.token_address,
+        amount_sol: request.amount_sol,
+        slippage_bps: request.slippage_bps.unwrap_or(100),
+        strategy,
+        take_profit_ladder,
+        time_exits: request.time_exits.unwrap_or_default(),
+        tip_lamports: request.tip_lamports,
+    };
+
+    log::info!("Control API buy: {} SOL of {}", config.amount_sol, config.token_address);
+    to_json(state.trader.buy_fast(config).await)
+}
+
+async fn sell(State(state): State<ApiState>, Json(request): Json<SellRequest>) -> ApiResult {
+    validate_token(&request.token_address)?;
+    log::info!("Control API sell: {}", request.token_address);
+    let result = match request.amount {
+        Some(amount) => state.trader.sell_amount(&request.token_address, amount).await,
+        None => state.trader.sell_position(&request.token_address).await,
+    };
+    to_json(result.map_err(bad_request)?)
+}
+
+async fn sell_all(State(state): State<ApiState>, Json(request): Json<SellAllRequest>) -> ApiResult {
+    if !request.confirm {
+        return Err(bad_request("set \"confirm\": true to sell every position"));
+    }
+    log::warn!("Control API emergency sell all");
+    to_json(state.trader.emergency_sell_all().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Endpoints, ExecutionMode, TraderOptions};
+    use solana_sdk::signature::Keypair;
+
+    // Paper trader with no reachable services, on a fresh state dir
+    fn offline_trader(name: &str) -> Arc<FastMemeTrader> {
+        let state_dir = std::env::temp_dir().join(format!("fast-meme-trader-api-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        let options = TraderOptions {
+            state_dir,
+            mode: ExecutionMode::Paper { starting_sol: 1.0 },
+            endpoints: Endpoints::all_at("http://127.0.0.1:1"),
+            ..Default::default()
+        };
+        Arc::new(FastMemeTrader::new_with_options(&Keypair::new().to_base58_string(), "test".to_string(), options).unwrap())
+    }
+
+    // Serve the router on an ephemeral loopback port
+    async fn spawn_api(name: &str, token: &str) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(offline_trader(name), token);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        addr
+    }
+
+    // Status of GET /stats with an optional Authorization header
+    async fn get_stats(addr: SocketAddr, authorization: Option<&str>) -> u16 {
+        let url = format!("http://{}/stats", addr);
+        let authorization = authorization.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            let mut request = ureq::get(&url);
+            if let Some(value) = authorization {
+                request = request.set("Authorization", &value);
+            }
+            match request.call() {
+                Ok(response) => response.status(),
+                Err(ureq::Error::Status(status, _)) => status,
+                Err(e) => panic!("request failed: {}", e),
+            }
+        }).await.unwrap()
+    }
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_length() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn routes_require_the_bearer_token() {
+        let addr = spawn_api("auth", "s3cret").await;
+
+        assert_eq!(get_stats(addr, None).await, 401);
+        assert_eq!(get_stats(addr, Some("Bearer wrong")).await, 401);
+        assert_eq!(get_stats(addr, Some("Bearer s3cret2")).await, 401);
+        // The scheme is required, not just the token
+        assert_eq!(get_stats(addr, Some("s3cret")).await, 401);
+        assert_eq!(get_stats(addr, Some("Bearer s3cret")).await, 200);
+    }
+
+    #[test]
+    fn binds_to_localhost_by_default() {
+        let config = ApiConfig::default();
+        assert!(!config.enabled);
+        assert!(SocketAddr::from_str(&config.bind).unwrap().ip().is_loopback());
+    }
+
+    #[tokio::test]
+    async fn refuses_to_serve_without_a_token() {
+        let (_shutdown, receiver) = watch::channel(false);
+
+        let error = serve(offline_trader("no-token"), &ApiConfig::default(), receiver).await.unwrap_err();
+        assert!(error.to_string().contains("api.token"));
+    }
+}
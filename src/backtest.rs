@@ -0,0 +1,513 @@
+// src/backtest.rs - Offline replay of price series through the live exit logic
+// Uses the same ATHTracker, ladder and strategy::evaluate_exit path as monitor_positions
+
+use crate::strategy::{self, ExitDecision, ExitReason, ExitRule, ExitStrategy, PriceTick};
+use crate::trade_journal::{ClosedTrade, TradeStats};
+use crate::{ATHTracker, Position, StrategyType, TakeProfitTier, TimeExitRules};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+
+// Simulated position sizes are priced in nano-units so u64 amounts keep precision
+const UNITS_PER_SOL_VALUE: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PricePoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: Decimal,
+}
+
+// Time-ordered prices per token
+pub type PriceSeries = BTreeMap<String, Vec<PricePoint>>;
+
+// Fills execute at the tick price moved against us by slippage, plus a flat fee per transaction
+#[derive(Debug, Clone, Copy)]
+pub struct FillModel {
+    pub slippage_bps: u16,
+    pub fee_sol_per_trade: f64,
+}
+
+impl Default for FillModel {
+    fn default() -> Self {
+        Self {
+            slippage_bps: 100,
+            // 5000 lamport signature fee plus a typical priority fee
+            fee_sol_per_trade: 0.0001,
+        }
+    }
+}
+
+impl FillModel {
+    fn buy_price(&self, price: Decimal) -> Decimal {
+        price * (Decimal::ONE + Decimal::from(self.slippage_bps) / Decimal::from(10_000))
+    }
+
+    fn sell_price(&self, price: Decimal) -> Decimal {
+        price * (Decimal::ONE - Decimal::from(self.slippage_bps) / Decimal::from(10_000))
+    }
+}
+
+#[derive(Clone)]
+pub struct BacktestConfig {
+    pub amount_sol: f64,
+    pub strategy: StrategyType,
+    pub take_profit_ladder: Vec<TakeProfitTier>,
+    pub time_exits: TimeExitRules,
+    pub fill_model: FillModel,
+    // Implementation for StrategyType::Registered; built-ins resolve themselves
+    pub exit_strategy: Option<Arc<dyn ExitStrategy>>,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            amount_sol: 0.1,
+            strategy: StrategyType::default(),
+            take_profit_ladder: Vec::new(),
+            time_exits: TimeExitRules::default(),
+            fill_model: FillModel::default(),
+            exit_strategy: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestReport {
+    pub strategy: StrategyType,
+    pub trades: Vec<ClosedTrade>,
+    pub stats: TradeStats,
+}
+
+// Replay every token's series: enter at its first tick, exit when the strategy says so
+pub fn run_backtest(series: &PriceSeries, config: &BacktestConfig) -> Result<BacktestReport> {
+    if config.amount_sol <= 0.0 {
+        return Err(anyhow!("Backtest amount must be positive, got {} SOL", config.amount_sol));
+    }
+    TakeProfitTier::validate_ladder(&config.take_profit_ladder)?;
+    config.time_exits.validate()?;
+
+    let exit_strategy = match (&config.exit_strategy, config.strategy.exit_strategy()) {
+        (Some(exit_strategy), _) => exit_strategy.clone(),
+        (None, Some(builtin)) => {
+            config.strategy.params().validate()?;
+            builtin
+        },
+        (None, None) => return Err(anyhow!("Strategy {:?} needs an ExitStrategy implementation", config.strategy)),
+    };
+
+    let mut trades = Vec::new();
+    for (token_address, points) in series {
+        if let Some(trade) = simulate_token(token_address, points, config, exit_strategy.as_ref()) {
+            trades.push(trade);
+        }
+    }
+
+    let stats = TradeStats::from_trades(&trades);
+    Ok(BacktestReport {
+        strategy: config.strategy.clone(),
+        trades,
+        stats,
+    })
+}
+
+fn simulate_token(token_address: &str, points: &[PricePoint], config: &BacktestConfig, exit_strategy: &dyn ExitStrategy) -> Option<ClosedTrade> {
+    let entry = points.iter().find(|point| point.price > Decimal::ZERO)?;
+    let fill = &config.fill_model;
+
+    // Entry is what the fill paid, slippage included, as a live position records it
+    let entry_price = fill.buy_price(entry.price);
+    let amount_tokens = (config.amount_sol / entry_price.to_f64()? * UNITS_PER_SOL_VALUE) as u64;
+    if amount_tokens == 0 {
+        log::warn!("Backtest skipped {}: entry price too high for {} SOL", token_address, config.amount_sol);
+        return None;
+    }
+
+    let mut position = Position {
+        token_address: token_address.to_string(),
+        entry_price: Some(entry_price),
+        amount_tokens,
+        entry_time: entry.timestamp,
+        strategy: config.strategy.clone(),
+        buy_signature: "backtest-buy".to_string(),
+        sol_in: config.amount_sol,
+        realized_sol_out: 0.0,
+        realized_fee_lamports: 0,
+        realized_tip_lamports: 0,
+        initial_amount_tokens: amount_tokens,
+        take_profit_ladder: config.take_profit_ladder.clone(),
+        completed_tiers: Vec::new(),
+        fired_exits: Vec::new(),
+        time_exits: config.time_exits,
+        price_feed: None,
+    };
+    let mut tracker = ATHTracker::new(Some(entry_price), &config.strategy.params());
+    tracker.last_updated = entry.timestamp;
+    let mut fees_sol = fill.fee_sol_per_trade;
+
+    // Simulated sell of raw units at a tick, returns SOL received
+    let sell = |amount: u64, price: Decimal| -> f64 {
+        let fill_price = fill.sell_price(price).to_f64().unwrap_or(0.0);
+        amount as f64 * fill_price / UNITS_PER_SOL_VALUE
+    };
+
+    let mut exit: Option<(ExitReason, DateTime<Utc>)> = None;
+
+    // Non-positive ticks are bad data, skipped as process_price_tick skips them live
+    for point in points.iter().filter(|point| point.timestamp > entry.timestamp && point.price > Decimal::ZERO) {
+        let tick = PriceTick {
+            price: point.price,
+            timestamp: point.timestamp,
+        };
+        tracker.update(&tick);
+
+        // Ladder tiers, exactly as execute_take_profit_tiers fires them
+        for tier_index in strategy::due_take_profit_tiers(&position, tick.price) {
+            let amount = position.tier_sell_amount(tier_index);
+            if amount > 0 {
+                position.realized_sol_out += sell(amount, tick.price);
+                position.amount_tokens -= amount;
+                fees_sol += fill.fee_sol_per_trade;
+            }
+            position.completed_tiers.push(tier_index);
+
+            if position.amount_tokens == 0 {
+                let reason = ExitReason::triggered(ExitRule::TakeProfitTier(tier_index), &position, &tracker, &tick);
+                exit = Some((reason, tick.timestamp));
+                break;
+            }
+        }
+        if exit.is_some() {
+            break;
+        }
+
+        match strategy::evaluate_exit(&position, &tracker, &tick, exit_strategy) {
+            ExitDecision::Hold => {},
+            ExitDecision::SellAll(reason) => {
+                position.realized_sol_out += sell(position.amount_tokens, tick.price);
+                position.amount_tokens = 0;
+                fees_sol += fill.fee_sol_per_trade;
+                exit = Some((reason, tick.timestamp));
+                break;
+            },
+            ExitDecision::SellFraction(fraction, reason) => {
+                let amount = (Decimal::from(position.amount_tokens) * fraction.min(Decimal::ONE))
+                    .floor()
+                    .to_u64()
+                    .unwrap_or(0);
+                if amount > 0 {
+                    position.realized_sol_out += sell(amount, tick.price);
+                    position.amount_tokens -= amount;
+                    fees_sol += fill.fee_sol_per_trade;
+                }
+                position.fired_exits.push(reason.rule.clone());
+                if position.amount_tokens == 0 {
+                    exit = Some((reason, tick.timestamp));
+                    break;
+                }
+            },
+        }
+    }
+
+    // Still holding when the data runs out: mark to the last positive price
+    let (exit_reason, exit_time) = match exit {
+        Some(exit) => exit,
+        None => {
+            let last = points.iter().rev().find(|point| point.price > Decimal::ZERO)?;
+            let tick = PriceTick {
+                price: last.price,
+                timestamp: last.timestamp,
+            };
+            position.realized_sol_out += sell(position.amount_tokens, last.price);
+            fees_sol += fill.fee_sol_per_trade;
+            (ExitReason::triggered(ExitRule::EndOfData, &position, &tracker, &tick), last.timestamp)
+        }
+    };
+
+    Some(ClosedTrade {
+        token_address: token_address.to_string(),
+        buy_signature: position.buy_signature.clone(),
+        sell_signature: "backtest-sell".to_string(),
+        sol_in: config.amount_sol,
+        sol_out: position.realized_sol_out,
+        fees_sol,
+        entry_time: position.entry_time,
+        exit_time,
+        hold_time_secs: (exit_time - position.entry_time).num_seconds(),
+        strategy: config.strategy.clone(),
+        exit_reason,
+    })
+}
+
+#[derive(Deserialize)]
+struct JsonPricePoint {
+    timestamp: Value,
+    #[serde(alias = "token_address", alias = "mint")]
+    token: Option<String>,
+    price: Value,
+}
+
+// Load a price series from .csv (timestamp,token,price) or .json. JSON may be an
+// array of {timestamp, token, price} or an object of token -> [{timestamp, price}].
+// Timestamps are RFC 3339 or unix seconds/milliseconds.
+pub fn load_price_series(path: impl AsRef<Path>) -> Result<PriceSeries> {
+    let path = path.as_ref();
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read price series {}: {}", path.display(), e))?;
+
+    let is_json = path.extension().map(|ext| ext.eq_ignore_ascii_case("json")).unwrap_or(false)
+        || data.trim_start().starts_with(['[', '{']);
+
+    let mut series = if is_json {
+        parse_json_series(&data)?
+    } else {
+        parse_csv_series(&data)?
+    };
+
+    for points in series.values_mut() {
+        points.sort_by_key(|point| point.timestamp);
+    }
+    series.retain(|_, points| !points.is_empty());
+
+    if series.is_empty() {
+        return Err(anyhow!("No price points found in {}", path.display()));
+    }
+    Ok(series)
+}
+
+// A header is the first row if its price column isn't a number, or any row whose price
+// column is literally "price" (files concatenated with their headers). Comments and
+// blank lines may come before it.
+fn parse_csv_series(data: &str) -> Result<PriceSeries> {
+    let mut series = PriceSeries::new();
+    let mut first_row = true;
+
+    for (line_number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(anyhow!("Line {}: expected timestamp,token,price", line_number + 1));
+        }
+
+        let is_header = fields[2].eq_ignore_ascii_case("price")
+            || (first_row && Decimal::from_str(fields[2]).is_err() && Decimal::from_scientific(fields[2]).is_err());
+        first_row = false;
+        if is_header {
+            continue;
+        }
+
+        let timestamp = parse_timestamp(&Value::String(fields[0].to_string()))
+            .map_err(|e| anyhow!("Line {}: {}", line_number + 1, e))?;
+        let price = Decimal::from_str(fields[2])
+            .or_else(|_| Decimal::from_scientific(fields[2]))
+            .map_err(|_| anyhow!("Line {}: invalid price '{}'", line_number + 1, fields[2]))?;
+
+        series.entry(fields[1].to_string()).or_default().push(PricePoint { timestamp, price });
+    }
+
+    Ok(series)
+}
+
+fn parse_json_series(data: &str) -> Result<PriceSeries> {
+    let mut series = PriceSeries::new();
+
+    match serde_json::from_str::<Value>(data)? {
+        Value::Array(_) => {
+            let points: Vec<JsonPricePoint> = serde_json::from_str(data)?;
+            for point in points {
+                let token = point.token.ok_or_else(|| anyhow!("Price point without a token"))?;
+                series.entry(token).or_default().push(PricePoint {
+                    timestamp: parse_timestamp(&point.timestamp)?,
+                    price: parse_price(&point.price)?,
+                });
+            }
+        },
+        Value::Object(_) => {
+            let by_token: HashMap<String, Vec<JsonPricePoint>> = serde_json::from_str(data)?;
+            for (token, points) in by_token {
+                let entry = series.entry(token).or_default();
+                for point in points {
+                    entry.push(PricePoint {
+                        timestamp: parse_timestamp(&point.timestamp)?,
+                        price: parse_price(&point.price)?,
+                    });
+                }
+            }
+        },
+        _ => return Err(anyhow!("Price series JSON must be an array or an object")),
+    }
+
+    Ok(series)
+}
+
+fn parse_price(value: &Value) -> Result<Decimal> {
+    match value {
+        Value::Number(number) => number.as_f64()
+            .and_then(Decimal::from_f64)
+            .ok_or_else(|| anyhow!("Invalid price {}", number)),
+        Value::String(text) => Decimal::from_str(text)
+            .or_else(|_| Decimal::from_scientific(text))
+            .map_err(|_| anyhow!("Invalid price '{}'", text)),
+        other => Err(anyhow!("Invalid price {}", other)),
+    }
+}
+
+fn parse_timestamp(value: &Value) -> Result<DateTime<Utc>> {
+    let raw = match value {
+        Value::Number(number) => number.as_i64().ok_or_else(|| anyhow!("Invalid timestamp {}", number))?,
+        Value::String(text) => {
+            if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+                return Ok(parsed.with_timezone(&Utc));
+            }
+            text.parse::<i64>().map_err(|_| anyhow!("Invalid timestamp '{}'", text))?
+        },
+        other => return Err(anyhow!("Invalid timestamp {}", other)),
+    };
+
+    // Values past the year 2286 in seconds are taken as milliseconds
+    let timestamp = if raw > 9_999_999_999 {
+        Utc.timestamp_millis_opt(raw).single()
+    } else {
+        Utc.timestamp_opt(raw, 0).single()
+    };
+    timestamp.ok_or_else(|| anyhow!("Timestamp out of range: {}", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(secs: i64, price: &str) -> PricePoint {
+        PricePoint {
+            timestamp: Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap(),
+            price: Decimal::from_str(price).unwrap(),
+        }
+    }
+
+    fn series(points: Vec<PricePoint>) -> PriceSeries {
+        PriceSeries::from([("TOKEN".to_string(), points)])
+    }
+
+    fn config(strategy: StrategyType) -> BacktestConfig {
+        BacktestConfig {
+            amount_sol: 1.0,
+            strategy,
+            fill_model: FillModel { slippage_bps: 100, fee_sol_per_trade: 0.0 },
+            ..BacktestConfig::default()
+        }
+    }
+
+    #[test]
+    fn entry_price_includes_buy_slippage() {
+        // +15.5% on the tick price is only +14.4% on the 1% slipped fill: no take profit
+        let points = vec![point(0, "1.0"), point(60, "1.155"), point(120, "1.155")];
+        let report = run_backtest(&series(points), &config(StrategyType::Conservative)).unwrap();
+
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].exit_reason.rule, ExitRule::EndOfData);
+
+        let points = vec![point(0, "1.0"), point(60, "1.17")];
+        let report = run_backtest(&series(points), &config(StrategyType::Conservative)).unwrap();
+        assert_eq!(report.trades[0].exit_reason.rule, ExitRule::TakeProfit);
+    }
+
+    #[test]
+    fn stop_loss_exits_and_books_the_slipped_sell() {
+        let points = vec![point(0, "1.0"), point(60, "0.9"), point(120, "2.0")];
+        let report = run_backtest(&series(points), &config(StrategyType::Conservative)).unwrap();
+        let trade = &report.trades[0];
+
+        assert_eq!(trade.exit_reason.rule, ExitRule::StopLoss);
+        assert_eq!(trade.hold_time_secs, 60);
+        // Bought at 1.01, sold at 0.9 * 0.99
+        let expected = 0.9 * 0.99 / 1.01;
+        assert!((trade.sol_out - expected).abs() < 1e-6, "sol_out {}", trade.sol_out);
+        assert_eq!(report.stats.closed_trades, 1);
+        assert_eq!(report.stats.winning_trades, 0);
+    }
+
+    #[test]
+    fn ath_pullback_exits_after_the_peak() {
+        let points = vec![point(0, "1.0"), point(60, "1.5"), point(120, "1.3"), point(180, "3.0")];
+        let report = run_backtest(&series(points), &config(StrategyType::ConservativeATH)).unwrap();
+        let trade = &report.trades[0];
+
+        assert_eq!(trade.exit_reason.rule, ExitRule::AthPullback);
+        assert_eq!(trade.hold_time_secs, 120);
+        assert_eq!(report.stats.winning_trades, 1);
+    }
+
+    #[test]
+    fn zero_prices_before_the_first_quote_are_skipped() {
+        let points = vec![point(0, "0"), point(60, "1.0"), point(120, "1.0")];
+        let report = run_backtest(&series(points), &config(StrategyType::Conservative)).unwrap();
+        assert_eq!(report.trades[0].entry_time, point(60, "1").timestamp);
+    }
+
+    #[test]
+    fn zero_prices_after_entry_do_not_fire_exits() {
+        let points = vec![point(0, "1.0"), point(60, "0"), point(120, "1.0"), point(180, "0")];
+        let report = run_backtest(&series(points), &config(StrategyType::Conservative)).unwrap();
+        let trade = &report.trades[0];
+
+        assert_eq!(trade.exit_reason.rule, ExitRule::EndOfData);
+        assert_eq!(trade.exit_time, point(120, "1").timestamp);
+        // Marked at 1.0 * 0.99 after buying at 1.01, not at zero
+        let expected = 0.99 / 1.01;
+        assert!((trade.sol_out - expected).abs() < 1e-6, "sol_out {}", trade.sol_out);
+    }
+
+    #[test]
+    fn invalid_amount_is_rejected() {
+        let mut config = config(StrategyType::Conservative);
+        config.amount_sol = 0.0;
+        assert!(run_backtest(&series(vec![point(0, "1.0")]), &config).is_err());
+    }
+
+    #[test]
+    fn csv_header_is_found_after_comments() {
+        let data = "# exported prices\n\ntimestamp,token,price\n1700000000,AAA,0.5\n1700000060,AAA,1e-3\n";
+        let series = parse_csv_series(data).unwrap();
+
+        assert_eq!(series["AAA"].len(), 2);
+        assert_eq!(series["AAA"][1].price, Decimal::new(1, 3));
+    }
+
+    #[test]
+    fn csv_repeated_headers_are_skipped() {
+        let data = "timestamp,token,price\n1700000000,AAA,1\ntimestamp,token,price\n2023-11-14T22:14:20Z,BBB,2\n";
+        let series = parse_csv_series(data).unwrap();
+
+        assert_eq!(series["AAA"].len(), 1);
+        assert_eq!(series["BBB"][0].timestamp, Utc.timestamp_opt(1_700_000_060, 0).unwrap());
+    }
+
+    #[test]
+    fn csv_rejects_bad_rows_after_the_header() {
+        assert!(parse_csv_series("1700000000,AAA,1\n1700000060,AAA,n/a\n").is_err());
+        assert!(parse_csv_series("1700000000,AAA\n").is_err());
+    }
+
+    #[test]
+    fn json_series_accepts_both_shapes() {
+        let array = r#"[{"timestamp": 1700000000000, "mint": "AAA", "price": 0.5}]"#;
+        let series = parse_json_series(array).unwrap();
+        assert_eq!(series["AAA"][0].timestamp, Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+
+        let object = r#"{"AAA": [{"timestamp": "1700000000", "price": "0.25"}]}"#;
+        let series = parse_json_series(object).unwrap();
+        assert_eq!(series["AAA"][0].price, Decimal::new(25, 2));
+
+        assert!(parse_json_series("[{\"timestamp\": 1, \"price\": 1}]").is_err());
+    }
+}
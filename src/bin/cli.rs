@@ -1,33 +1,78 @@
 // src/bin/cli.rs - FIXED Command Line Interface for the trading bot
 
-use fast_meme_trader::{FastMemeTrader, TradeConfig, StrategyType, token_addresses};
-use anyhow::Result;
+use fast_meme_trader::config::TraderConfig;
+use fast_meme_trader::{format_entry_price, short, FastMemeTrader, TradeConfig, TradeResult, StrategyType, StrategyParams, TakeProfitTier, TimeExitRules, token_addresses};
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::time::Duration;
 use solana_sdk::{signature::Signer, pubkey::Pubkey};
 use std::str::FromStr;
 
+const DEFAULT_PAPER_BALANCE_SOL: f64 = 10.0;
+
+// Exit codes for scripted use
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1; // the command ran but the trade or check failed
+const EXIT_USAGE: i32 = 2;  // bad arguments
+const EXIT_SETUP: i32 = 3;  // missing keys, invalid config or trader init failure
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init();
     dotenv::dotenv().ok();
     
-    println!("🚀 Fast Solana Meme Coin Trading Bot v0.3.1 - FIXED");
-    println!("====================================================");
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            eprintln!("💡 Run 'cargo run --bin cli -- help' for usage");
+            std::process::exit(EXIT_USAGE);
+        }
+    };
     
-    // Initialize trader
+    match args.command.as_deref() {
+        None | Some("menu") => run_menu(&args).await,
+        Some("help") => {
+            print_usage();
+            Ok(())
+        },
+        Some(_) => std::process::exit(run_command(&args).await),
+    }
+}
+
+// Build the trader from the layered config; --paper [SOL] switches to simulated fills
+fn init_trader(args: &CliArgs) -> Result<(FastMemeTrader, TraderConfig)> {
     let private_key = std::env::var("WALLET_PRIVATE_KEY")
-        .expect("❌ WALLET_PRIVATE_KEY not set in .env file");
+        .map_err(|_| anyhow!("WALLET_PRIVATE_KEY not set in .env file"))?;
     let helius_api_key = std::env::var("HELIUS_API_KEY")
-        .expect("❌ HELIUS_API_KEY not set in .env file");
+        .map_err(|_| anyhow!("HELIUS_API_KEY not set in .env file"))?;
+    
+    // trader.toml / trader.json / TRADER_CONFIG, then TRADER_* env overrides
+    let mut config = TraderConfig::load()?;
+    if let Some(starting_sol) = args.paper {
+        config.paper_starting_sol = Some(
+            starting_sol.or(config.paper_starting_sol).unwrap_or(DEFAULT_PAPER_BALANCE_SOL)
+        );
+    }
+    
+    let trader = FastMemeTrader::from_config(&private_key, helius_api_key, &config)?;
+    Ok((trader, config))
+}
+
+async fn run_menu(args: &CliArgs) -> Result<()> {
+    println!("🚀 Fast Solana Meme Coin Trading Bot v0.3.1 - FIXED");
+    println!("====================================================");
     
     println!("🔄 Initializing trader...");
-    let trader = FastMemeTrader::new(&private_key, helius_api_key)?;
+    let (trader, config) = init_trader(args)?;
     println!("✅ Trader initialized successfully!");
-    println!("🔑 Wallet: {}...{}", 
-        &trader.keypair.pubkey().to_string()[..8],
-        &trader.keypair.pubkey().to_string()[trader.keypair.pubkey().to_string().len()-8..]
-    );
+    if trader.is_paper() {
+        println!("📝 PAPER TRADING MODE - fills are simulated, no transactions are sent");
+    }
+    println!("🔑 Wallet: {}...", short(&trader.keypair.pubkey().to_string()));
     
     // Show initial health check
     if let Ok(health) = trader.health_check().await {
@@ -62,7 +107,7 @@ async fn main() -> Result<()> {
             "6" => emergency_sell_all(&trader).await?,
             "7" => platform_test(&trader).await?,
             "8" => performance_stats(&trader).await?,
-            "9" => show_settings(),
+            "9" => show_settings(&config),
             "0" => {
                 println!("👋 Goodbye!");
                 break;
@@ -74,6 +119,439 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// Non-interactive subcommands
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct CliArgs {
+    command: Option<String>,
+    json: bool,
+    // --paper with an optional starting balance
+    paper: Option<Option<f64>>,
+    options: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+impl CliArgs {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut args = args.peekable();
+        let mut parsed = CliArgs::default();
+        
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => parsed.json = true,
+                "--help" | "-h" => parsed.command = Some("help".to_string()),
+                "--paper" => {
+                    let starting_sol = args.peek().and_then(|value| value.parse::<f64>().ok());
+                    if starting_sol.is_some() {
+                        args.next();
+                    }
+                    parsed.paper = Some(starting_sol);
+                },
+                "--once" | "--yes" => parsed.switches.push(arg),
+                flag if flag.starts_with("--") => {
+                    let value = args.next().ok_or_else(|| anyhow!("Missing value for {}", flag))?;
+                    parsed.options.insert(flag.to_string(), value);
+                },
+                _ if parsed.command.is_none() => parsed.command = Some(arg),
+                _ => return Err(anyhow!("Unexpected argument '{}'", arg)),
+            }
+        }
+        
+        Ok(parsed)
+    }
+    
+    // Reject flags the command doesn't understand, so typos don't silently fall back to defaults
+    fn allow_only(&self, allowed: &[&str]) -> Result<()> {
+        for flag in self.options.keys().chain(self.switches.iter()) {
+            if !allowed.contains(&flag.as_str()) {
+                return Err(anyhow!("Unknown option {} for this command", flag));
+            }
+        }
+        Ok(())
+    }
+    
+    fn get(&self, flag: &str) -> Option<&str> {
+        self.options.get(flag).map(String::as_str)
+    }
+    
+    fn require(&self, flag: &str) -> Result<&str> {
+        self.get(flag).ok_or_else(|| anyhow!("Missing required option {}", flag))
+    }
+    
+    fn parse_opt<T: FromStr>(&self, flag: &str) -> Result<Option<T>> {
+        match self.get(flag) {
+            Some(value) => value.parse::<T>()
+                .map(Some)
+                .map_err(|_| anyhow!("Invalid value for {}: {}", flag, value)),
+            None => Ok(None),
+        }
+    }
+    
+    fn has(&self, switch: &str) -> bool {
+        self.switches.iter().any(|s| s == switch)
+    }
+}
+
+enum Command {
+    Buy(Box<TradeConfig>),
+    Sell { token_address: String, amount: Option<u64> },
+    Positions,
+    Ath { token_address: Option<String> },
+    Monitor { interval_secs: u64, once: bool },
+    SellAll,
+    Health,
+    Stats,
+}
+
+impl Command {
+    // Validate everything up front so bad arguments never reach the chain
+    fn from_args(args: &CliArgs) -> Result<Self> {
+        let command = args.command.as_deref().unwrap_or_default();
+        match command {
+            "buy" => {
+                args.allow_only(&["--token", "--amount", "--slippage", "--slippage-bps", "--strategy", "--ladder", "--max-hold", "--tip"])?;
+                let amount_sol: f64 = args.parse_opt("--amount")?
+                    .ok_or_else(|| anyhow!("Missing required option --amount"))?;
+                let slippage_bps = match (args.parse_opt::<f64>("--slippage")?, args.parse_opt::<u16>("--slippage-bps")?) {
+                    (Some(_), Some(_)) => return Err(anyhow!("Use either --slippage or --slippage-bps, not both")),
+                    (Some(percent), None) if (0.1..=50.0).contains(&percent) => (percent * 100.0) as u16,
+                    (Some(percent), None) => return Err(anyhow!("--slippage must be between 0.1 and 50.0 percent, got {}", percent)),
+                    (None, Some(bps)) if (10..=5000).contains(&bps) => bps,
+                    (None, Some(bps)) => return Err(anyhow!("--slippage-bps must be between 10 and 5000, got {}", bps)),
+                    (None, None) => 100,
+                };
+                let strategy = match args.get("--strategy") {
+                    Some(strategy) => StrategyType::from_str(strategy)?,
+                    None => StrategyType::ConservativeATH,
+                };
+                let take_profit_ladder = match args.get("--ladder") {
+                    Some(ladder) => TakeProfitTier::parse_ladder(ladder)?,
+                    None => Vec::new(),
+                };
+                let time_exits = TimeExitRules {
+                    max_hold_minutes: args.parse_opt("--max-hold")?,
+                    ..TimeExitRules::default()
+                };
+                time_exits.validate()?;
+                
+                Ok(Command::Buy(Box::new(TradeConfig {
+                    token_address: resolve_token(args.require("--token")?)?,
+                    amount_sol,
+                    slippage_bps,
+                    strategy,
+                    take_profit_ladder,
+                    time_exits,
+                    tip_lamports: args.parse_opt("--tip")?,
+                })))
+            },
+            "sell" => {
+                args.allow_only(&["--token", "--amount"])?;
+                Ok(Command::Sell {
+                    token_address: resolve_token(args.require("--token")?)?,
+                    amount: args.parse_opt("--amount")?,
+                })
+            },
+            "positions" => {
+                args.allow_only(&[])?;
+                Ok(Command::Positions)
+            },
+            "ath" => {
+                args.allow_only(&["--token"])?;
+                Ok(Command::Ath {
+                    token_address: args.get("--token").map(resolve_token).transpose()?,
+                })
+            },
+            "monitor" => {
+                args.allow_only(&["--interval", "--once"])?;
+                let interval_secs = args.parse_opt("--interval")?.unwrap_or(10);
+                if interval_secs == 0 {
+                    return Err(anyhow!("--interval must be at least 1 second"));
+                }
+                Ok(Command::Monitor { interval_secs, once: args.has("--once") })
+            },
+            "sell-all" => {
+                args.allow_only(&["--yes"])?;
+                if !args.has("--yes") {
+                    return Err(anyhow!("sell-all sells every position immediately; pass --yes to confirm"));
+                }
+                Ok(Command::SellAll)
+            },
+            "health" => {
+                args.allow_only(&[])?;
+                Ok(Command::Health)
+            },
+            "stats" => {
+                args.allow_only(&[])?;
+                Ok(Command::Stats)
+            },
+            other => Err(anyhow!("Unknown command '{}'", other)),
+        }
+    }
+}
+
+async fn run_command(args: &CliArgs) -> i32 {
+    let command = match Command::from_args(args) {
+        Ok(command) => command,
+        Err(e) => {
+            report_error(args.json, &e);
+            return EXIT_USAGE;
+        }
+    };
+    
+    let (trader, _) = match init_trader(args) {
+        Ok(initialized) => initialized,
+        Err(e) => {
+            report_error(args.json, &e);
+            return EXIT_SETUP;
+        }
+    };
+    
+    match execute(&trader, command, args.json).await {
+        Ok(code) => code,
+        Err(e) => {
+            report_error(args.json, &e);
+            EXIT_FAILED
+        }
+    }
+}
+
+async fn execute(trader: &FastMemeTrader, command: Command, json: bool) -> Result<i32> {
+    match command {
+        Command::Buy(config) => {
+            let result = trader.buy_fast(*config).await;
+            if json {
+                print_json(&result)?;
+            } else if result.success {
+                println!("✅ Bought {} tokens for {} SOL via {}",
+                    result.tokens_received.unwrap_or(0), result.sol_spent.unwrap_or(0.0), platform_name(&result));
+                println!("📝 Signature: {}", result.signature);
+                if let Some(tip) = result.tip_lamports {
+                    println!("🎁 Jito tip: {} lamports", tip);
+                }
+                print_fill(&result);
+            } else {
+                eprintln!("❌ Buy failed: {}", result.error.as_deref().unwrap_or("Unknown error"));
+            }
+            Ok(if result.success { EXIT_OK } else { EXIT_FAILED })
+        },
+        Command::Sell { token_address, amount } => {
+            let result = match amount {
+                Some(amount) => trader.sell_amount(&token_address, amount).await?,
+                None => trader.sell_position(&token_address).await?,
+            };
+            if json {
+                print_json(&result)?;
+            } else if result.success {
+                println!("✅ Sold {} for {:.6} SOL", short(&token_address), result.sol_received.unwrap_or(0.0));
+                println!("📝 Signature: {}", result.signature);
+                print_fill(&result);
+            } else {
+                eprintln!("❌ Sell failed: {}", result.error.as_deref().unwrap_or("Unknown error"));
+            }
+            Ok(if result.success { EXIT_OK } else { EXIT_FAILED })
+        },
+        Command::Positions => {
+            let statuses = trader.position_statuses().await;
+            if json {
+                print_json(&statuses)?;
+            } else if statuses.is_empty() {
+                println!("📭 No active positions");
+            } else {
+                for status in &statuses {
+                    let position = &status.position;
+                    println!(
+                        "{}: {} tokens | Entry: {} | Current: {} | P&L: {} | Strategy: {:?}",
+                        position.token_address,
+                        status.amount_ui.map(|amount| amount.normalize().to_string())
+                            .unwrap_or_else(|| format!("{} raw", position.amount_tokens)),
+                        format_entry_price(position.entry_price),
+                        status.current_price.map(|p| format!("${:.8}", p)).unwrap_or_else(|| "n/a".to_string()),
+                        status.profit_percent.map(|p| format!("{:.2}%", p)).unwrap_or_else(|| "n/a".to_string()),
+                        position.strategy
+                    );
+                }
+            }
+            Ok(EXIT_OK)
+        },
+        Command::Ath { token_address } => {
+            let tokens = match token_address {
+                Some(token_address) => vec![token_address],
+                None => trader.positions.read().await.keys().cloned().collect(),
+            };
+            
+            let mut trackers = Vec::new();
+            for token in tokens {
+                match trader.ath_tracker(&token).await {
+                    Some(tracker) => trackers.push((token, tracker)),
+                    None => return Err(anyhow!("No ATH data found for {}", token)),
+                }
+            }
+            
+            if json {
+                let trackers: HashMap<_, _> = trackers.into_iter().collect();
+                print_json(&trackers)?;
+            } else if trackers.is_empty() {
+                println!("📭 No active positions to check");
+            } else {
+                for (token, _) in &trackers {
+                    if let Some(status) = trader.get_ath_status(token).await {
+                        println!("{}: {}", token, status);
+                    }
+                }
+            }
+            Ok(EXIT_OK)
+        },
+        Command::Monitor { interval_secs, once } => {
+            let mut failed_sells = 0;
+            loop {
+                for sell in trader.monitor_positions().await {
+                    if !sell.trade.success {
+                        failed_sells += 1;
+                    }
+                    if json {
+                        // One event per line so the stream can be tailed
+                        println!("{}", serde_json::to_string(&sell)?);
+                    } else if sell.trade.success {
+                        println!("💰 EXECUTED SELL: {}", sell);
+                    } else {
+                        eprintln!("❌ {}", sell);
+                    }
+                }
+                
+                if once {
+                    break;
+                }
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => break,
+                    _ = tokio::time::sleep(Duration::from_secs(interval_secs)) => {},
+                }
+            }
+            
+            trader.flush_state().await?;
+            Ok(if once && failed_sells > 0 { EXIT_FAILED } else { EXIT_OK })
+        },
+        Command::SellAll => {
+            let results = trader.emergency_sell_all().await;
+            let all_succeeded = results.iter().all(|result| result.success);
+            if json {
+                print_json(&results)?;
+            } else {
+                for result in &results {
+                    if result.success {
+                        println!("✅ {} ({:.6} SOL)", result.signature, result.sol_received.unwrap_or(0.0));
+                    } else {
+                        eprintln!("❌ {}", result.error.as_deref().unwrap_or("Unknown error"));
+                    }
+                }
+                println!("🧹 {} positions processed", results.len());
+            }
+            Ok(if all_succeeded { EXIT_OK } else { EXIT_FAILED })
+        },
+        Command::Health => {
+            let report = trader.health_report().await?;
+            if json {
+                print_json(&report)?;
+            } else {
+                println!("📊 {}", report);
+            }
+            Ok(if report.is_healthy() { EXIT_OK } else { EXIT_FAILED })
+        },
+        Command::Stats => {
+            if json {
+                print_json(&serde_json::json!({
+                    "stats": trader.trade_stats(),
+                    "active_positions": trader.positions.read().await.len(),
+                    "paper_balance_sol": trader.paper_account().map(|paper| paper.sol_balance()),
+                }))?;
+            } else {
+                println!("📈 {}", trader.get_performance_stats().await);
+            }
+            Ok(EXIT_OK)
+        },
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn platform_name(result: &TradeResult) -> String {
+    match &result.platform_used {
+        Some(platform) => format!("{:?}", platform),
+        None => "n/a".to_string(),
+    }
+}
+
+// Fee and slippage read back from the confirmed transaction, when available
+fn print_fill(result: &TradeResult) {
+    if let Some(fee) = result.fee_lamports {
+        println!("⛽ Network fee: {} lamports", fee);
+    }
+    if let Some(slippage) = result.slippage_percent {
+        println!("📉 Slippage vs quote: {:.2}%", slippage);
+    }
+}
+
+// JSON mode reports errors on stdout so callers only need to parse one stream
+fn report_error(json: bool, error: &anyhow::Error) {
+    if json {
+        println!("{}", serde_json::json!({ "error": error.to_string() }));
+    } else {
+        eprintln!("❌ {}", error);
+    }
+}
+
+// Token shortcuts or a validated mint address
+fn resolve_token(input: &str) -> Result<String> {
+    let token_address = match input.trim().to_lowercase().as_str() {
+        "bonk" => token_addresses::BONK.to_string(),
+        "usdc" => token_addresses::USDC.to_string(),
+        "usdt" => token_addresses::USDT.to_string(),
+        "jup" => token_addresses::JUP.to_string(),
+        _ => input.trim().to_string(),
+    };
+    
+    if token_address.len() != 44 {
+        return Err(anyhow!("Invalid token address length: expected 44 characters, got {}", token_address.len()));
+    }
+    Pubkey::from_str(&token_address)
+        .map_err(|e| anyhow!("Invalid token address format: {}", e))?;
+    
+    Ok(token_address)
+}
+
+fn print_usage() {
+    println!("Fast Solana Meme Trading Bot CLI");
+    println!();
+    println!("USAGE:");
+    println!("    cargo run --bin cli -- [COMMAND] [OPTIONS] [--json] [--paper [SOL]]");
+    println!("    (no command starts the interactive menu)");
+    println!();
+    println!("COMMANDS:");
+    println!("    buy        --token T --amount SOL [--slippage PCT | --slippage-bps N] [--strategy S]");
+    println!("               [--ladder 30:25,60:25] [--max-hold MINUTES] [--tip LAMPORTS]");
+    println!("    sell       --token T [--amount RAW_TOKENS]   (full balance if --amount is omitted)");
+    println!("    positions  List open positions with current price and P&L");
+    println!("    ath        [--token T]   ATH tracker for one or all positions");
+    println!("    monitor    [--interval SECS] [--once]   Run exit strategies until Ctrl+C");
+    println!("    sell-all   --yes   Emergency sell every position");
+    println!("    health     Wallet balance and API/RPC connectivity");
+    println!("    stats      Realized performance from the trade journal");
+    println!("    menu       Interactive menu (default)");
+    println!();
+    println!("Tokens accept shortcuts: bonk, usdc, usdt, jup. Strategies: conservative, aggressive,");
+    println!("conservative-ath (default), aggressive-ath, custom:tp=..,sl=..,pb=..,min=..,dd=..");
+    println!();
+    println!("EXIT CODES:");
+    println!("    {}  success", EXIT_OK);
+    println!("    {}  trade or health check failed", EXIT_FAILED);
+    println!("    {}  invalid arguments", EXIT_USAGE);
+    println!("    {}  missing keys, invalid config or init failure", EXIT_SETUP);
+}
+
 // FIXED: Enhanced quick_buy with comprehensive validation
 async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
     println!("\n🚀 Quick Buy Setup - ENHANCED VALIDATION");
@@ -105,19 +583,20 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
         return Ok(());
     }
     
-    println!("✅ Token address validated: {}", &token_address[..8]);
+    println!("✅ Token address validated: {}", short(&token_address));
     
     // FIXED: Enhanced amount validation with better limits
-    print!("Enter SOL amount (0.000001 - 10.0): ");
+    let limits = trader.limits();
+    print!("Enter SOL amount ({} - {}): ", limits.min_trade_sol, limits.max_trade_sol);
     io::stdout().flush()?;
     let mut amount_input = String::new();
     io::stdin().read_line(&mut amount_input)?;
     let amount_sol: f64 = amount_input.trim().parse()
         .map_err(|_| anyhow::anyhow!("Invalid amount - please enter a number"))?;
     
-    if amount_sol < 0.000001 || amount_sol > 10.0 {
-        println!("❌ Amount must be between 0.000001 and 10.0 SOL");
-        println!("💡 Minimum: 0.000001 SOL (~$0.0002), Maximum: 10.0 SOL");
+    if !(limits.min_trade_sol..=limits.max_trade_sol).contains(&amount_sol) {
+        println!("❌ Amount must be between {} and {} SOL", limits.min_trade_sol, limits.max_trade_sol);
+        println!("💡 Change the range with limits.min_trade_sol / limits.max_trade_sol in trader.toml");
         return Ok(());
     }
     
@@ -127,9 +606,10 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
     println!("\nSelect strategy:");
     println!("1. 💚 Conservative (15% profit, 5% stop loss)");
     println!("2. 🔥 Aggressive (50% profit, 15% stop loss)");
-    println!("3. 🎯 Conservative ATH (8% pullback, 3% min profit) - RECOMMENDED");
-    println!("4. ⚡ Aggressive ATH (12% pullback, 5% min profit)");
-    print!("Choice (1-4): ");
+    println!("3. 🎯 Conservative ATH (8% pullback, 3% min profit, 20% max drawdown) - RECOMMENDED");
+    println!("4. ⚡ Aggressive ATH (12% pullback, 5% min profit, 30% max drawdown)");
+    println!("5. 🛠️  Custom (enter your own thresholds)");
+    print!("Choice (1-5): ");
     io::stdout().flush()?;
     
     let mut strategy_input = String::new();
@@ -139,6 +619,10 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
         "2" => StrategyType::Aggressive,
         "3" => StrategyType::ConservativeATH,
         "4" => StrategyType::AggressiveATH,
+        "5" => match prompt_custom_strategy()? {
+            Some(strategy) => strategy,
+            None => return Ok(()),
+        },
         _ => {
             println!("❌ Invalid choice, using Conservative ATH (recommended)");
             StrategyType::ConservativeATH
@@ -147,6 +631,41 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
     
     println!("✅ Strategy selected: {:?}", strategy);
     
+    // Optional take-profit ladder
+    print!("Take-profit ladder as profit%:sell% pairs (e.g. 30:25,60:25, Enter to skip): ");
+    io::stdout().flush()?;
+    let mut ladder_input = String::new();
+    io::stdin().read_line(&mut ladder_input)?;
+    let take_profit_ladder = match TakeProfitTier::parse_ladder(ladder_input.trim()) {
+        Ok(ladder) => ladder,
+        Err(e) => {
+            println!("❌ Invalid ladder: {}", e);
+            return Ok(());
+        }
+    };
+    
+    if !take_profit_ladder.is_empty() {
+        println!("✅ Ladder set:");
+        for tier in &take_profit_ladder {
+            println!("   • Sell {}% at +{}%", tier.sell_percent, tier.profit_percent);
+        }
+        println!("   • Remainder trailed by {:?}", strategy);
+    }
+    
+    // Optional time-based exits
+    print!("Add time-based exits? (y/N): ");
+    io::stdout().flush()?;
+    let mut time_input = String::new();
+    io::stdin().read_line(&mut time_input)?;
+    let time_exits = if time_input.trim().eq_ignore_ascii_case("y") {
+        match prompt_time_exits()? {
+            Some(rules) => rules,
+            None => return Ok(()),
+        }
+    } else {
+        TimeExitRules::default()
+    };
+    
     // FIXED: Enhanced slippage validation
     print!("Enter slippage % (default 1.0, range 0.1-50.0): ");
     io::stdout().flush()?;
@@ -191,6 +710,9 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
         amount_sol,
         slippage_bps,
         strategy: strategy.clone(),
+        take_profit_ladder,
+        time_exits,
+        tip_lamports: None,
     };
     
     println!("⏳ Processing... (this may take 10-30 seconds)");
@@ -200,7 +722,7 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
         println!("\n🎉 BUY SUCCESSFUL!");
         println!("=====================================");
         println!("📝 Signature: {}", result.signature);
-        println!("⚡ Platform: {:?}", result.platform_used);
+        println!("⚡ Platform: {}", platform_name(&result));
         println!("⏱️  Execution time: {}ms", result.execution_time_ms);
         if let Some(tokens) = result.tokens_received {
             println!("🪙 Tokens received: {}", tokens);
@@ -208,6 +730,10 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
         if let Some(sol_spent) = result.sol_spent {
             println!("💸 SOL spent: {}", sol_spent);
         }
+        if let Some(tip) = result.tip_lamports {
+            println!("🎁 Jito tip: {} lamports", tip);
+        }
+        print_fill(&result);
         println!("=====================================");
         println!("🎯 Position created with {:?} strategy", strategy);
         println!("💡 Use option 4 to start monitoring for auto-exit");
@@ -215,7 +741,7 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
     } else {
         println!("\n❌ BUY FAILED!");
         println!("=====================================");
-        println!("Error: {}", result.error.unwrap_or("Unknown error".to_string()));
+        println!("Error: {}", result.error.as_deref().unwrap_or("Unknown error"));
         println!("⏱️  Execution time: {}ms", result.execution_time_ms);
         println!("=====================================");
         
@@ -245,6 +771,96 @@ async fn quick_buy(trader: &FastMemeTrader) -> Result<()> {
     Ok(())
 }
 
+// Prompt for custom strategy thresholds; None if the values are rejected
+fn prompt_custom_strategy() -> Result<Option<StrategyType>> {
+    println!("\n🛠️  Custom Strategy (press Enter to skip a rule)");
+    
+    let params = StrategyParams {
+        take_profit_percent: prompt_optional_percent("Take profit %")?,
+        stop_loss_percent: prompt_optional_percent("Stop loss % (e.g. 5 = exit at -5%)")?,
+        pullback_percent: prompt_optional_percent("Pullback from ATH %")?,
+        min_profit_percent: prompt_optional_percent("Min profit % before pullback exit")?,
+        max_drawdown_percent: prompt_optional_percent("Max drawdown % from entry (pullback hard stop)")?,
+    };
+    
+    if let Err(e) = params.validate() {
+        println!("❌ Invalid custom strategy: {}", e);
+        return Ok(None);
+    }
+    
+    Ok(Some(StrategyType::Custom(params)))
+}
+
+// Prompt for time-based exit rules; None if the values are rejected
+fn prompt_time_exits() -> Result<Option<TimeExitRules>> {
+    println!("\n⏰ Time-Based Exits (press Enter to skip a rule)");
+    
+    let max_hold_minutes = prompt_optional_minutes("Sell after N minutes")?;
+    let min_gain_window_minutes = prompt_optional_minutes("Min-gain window in minutes")?;
+    let min_gain_percent = if min_gain_window_minutes.is_some() {
+        prompt_optional_percent("Sell if not up this % within the window")?
+    } else {
+        None
+    };
+    let pullback_decay_per_hour = prompt_optional_percent("Tighten pullback by % per hour held")?;
+    let min_pullback_percent = if pullback_decay_per_hour.is_some() {
+        prompt_optional_percent("Tightest pullback % (default 1)")?
+    } else {
+        None
+    };
+    
+    let rules = TimeExitRules {
+        max_hold_minutes,
+        min_gain_window_minutes,
+        min_gain_percent,
+        pullback_decay_per_hour,
+        min_pullback_percent,
+    };
+    
+    if let Err(e) = rules.validate() {
+        println!("❌ Invalid time-based exits: {}", e);
+        return Ok(None);
+    }
+    
+    Ok(Some(rules))
+}
+
+fn prompt_optional_minutes(label: &str) -> Result<Option<i64>> {
+    loop {
+        print!("{}: ", label);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let trimmed = input.trim();
+        
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        match trimmed.parse::<i64>() {
+            Ok(value) => return Ok(Some(value)),
+            Err(_) => println!("⚠️  Invalid number of minutes, try again"),
+        }
+    }
+}
+
+fn prompt_optional_percent(label: &str) -> Result<Option<Decimal>> {
+    loop {
+        print!("{}: ", label);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let trimmed = input.trim().trim_end_matches('%');
+        
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        match Decimal::from_str(trimmed) {
+            Ok(value) => return Ok(Some(value)),
+            Err(_) => println!("⚠️  Invalid number, try again"),
+        }
+    }
+}
+
 async fn quick_sell(trader: &FastMemeTrader) -> Result<()> {
     println!("\n💰 Quick Sell");
     
@@ -284,15 +900,19 @@ async fn quick_sell(trader: &FastMemeTrader) -> Result<()> {
         input.to_string()
     };
     
-    println!("🔄 Executing sell for {}...", &token_address[..8]);
+    println!("🔄 Executing sell for {}...", short(&token_address));
     let result = trader.sell_position(&token_address).await?;
     
     if result.success {
         println!("✅ Sell successful!");
         println!("📝 Signature: {}", result.signature);
+        if let Some(sol_received) = result.sol_received {
+            println!("💰 SOL received: {}", sol_received);
+        }
+        print_fill(&result);
         println!("⏱️  Execution time: {}ms", result.execution_time_ms);
     } else {
-        println!("❌ Sell failed: {}", result.error.unwrap_or("Unknown error".to_string()));
+        println!("❌ Sell failed: {}", result.error.as_deref().unwrap_or("Unknown error"));
     }
     
     Ok(())
@@ -331,7 +951,11 @@ async fn monitor_positions(trader: &FastMemeTrader) -> Result<()> {
         // Check for sells
         let sells = trader.monitor_positions().await;
         for sell in sells {
-            println!("💰 EXECUTED SELL: {}", sell);
+            if sell.trade.success {
+                println!("💰 EXECUTED SELL: {}", sell);
+            } else {
+                println!("❌ {}", sell);
+            }
         }
         
         // Show current positions
@@ -354,7 +978,7 @@ async fn monitor_positions(trader: &FastMemeTrader) -> Result<()> {
         
         for token in position_tokens {
             if let Some(status) = trader.get_ath_status(&token).await {
-                println!("   {}: {}", &token[..8], status);
+                println!("   {}: {}", short(&token), status);
             }
         }
         
@@ -382,7 +1006,7 @@ async fn check_ath_status(trader: &FastMemeTrader) -> Result<()> {
     
     println!("Current positions:");
     for (i, token) in position_tokens.iter().enumerate() {
-        println!("{}. {}", i + 1, &token[..8]);
+        println!("{}. {}", i + 1, short(token));
     }
     
     print!("\nEnter token address (or position number, or 'all' for all): ");
@@ -394,14 +1018,14 @@ async fn check_ath_status(trader: &FastMemeTrader) -> Result<()> {
     if input.to_lowercase() == "all" {
         for token in position_tokens {
             if let Some(status) = trader.get_ath_status(&token).await {
-                println!("{}: {}", &token[..8], status);
+                println!("{}: {}", short(&token), status);
             }
         }
     } else if let Ok(pos_num) = input.parse::<usize>() {
         if pos_num > 0 && pos_num <= position_tokens.len() {
             let token = &position_tokens[pos_num - 1];
             if let Some(status) = trader.get_ath_status(token).await {
-                println!("📊 {}: {}", &token[..8], status);
+                println!("📊 {}: {}", short(token), status);
             }
         } else {
             println!("❌ Invalid position number");
@@ -409,7 +1033,7 @@ async fn check_ath_status(trader: &FastMemeTrader) -> Result<()> {
     } else {
         let token_address = input;
         if let Some(status) = trader.get_ath_status(token_address).await {
-            println!("📊 {}: {}", &token_address[..8], status);
+            println!("📊 {}: {}", short(token_address), status);
         } else {
             println!("❌ No ATH data found for this token");
         }
@@ -435,11 +1059,16 @@ async fn emergency_sell_all(trader: &FastMemeTrader) -> Result<()> {
             if result.success {
                 println!("{}. ✅ Success: {} ({}ms)", i + 1, result.signature, result.execution_time_ms);
             } else {
-                println!("{}. ❌ Failed: {}", i + 1, result.error.as_ref().unwrap_or(&"Unknown error".to_string()));
+                println!("{}. ❌ Failed: {}", i + 1, result.error.as_deref().unwrap_or("Unknown error"));
             }
         }
         
-        println!("🧹 All positions cleared");
+        let failed = results.iter().filter(|result| !result.success).count();
+        if failed == 0 {
+            println!("🧹 All positions cleared");
+        } else {
+            println!("⚠️  {} positions could not be sold and are still tracked", failed);
+        }
     } else {
         println!("❌ Emergency sell cancelled");
     }
@@ -489,7 +1118,7 @@ async fn platform_test(trader: &FastMemeTrader) -> Result<()> {
         
         println!("🔄 Testing platform detection...");
         let platform = trader.detect_best_platform(token_address).await;
-        println!("✅ Best platform for {}: {:?}", &token_address[..8], platform);
+        println!("✅ Best platform for {}: {:?}", short(token_address), platform);
         
         // Test Jupiter quote capability
         println!("🔄 Testing Jupiter quote capability...");
@@ -498,6 +1127,7 @@ async fn platform_test(trader: &FastMemeTrader) -> Result<()> {
             amount_sol: 0.001,
             slippage_bps: 100,
             strategy: StrategyType::Conservative,
+            ..Default::default()
         };
         
         // This is just a test - don't actually execute
@@ -533,18 +1163,18 @@ async fn performance_stats(trader: &FastMemeTrader) -> Result<()> {
 }
 
 // FIXED: Enhanced settings display
-fn show_settings() {
+fn show_settings(config: &TraderConfig) {
     println!("\n🔧 Current Settings - ENHANCED");
     println!("Environment variables from .env file:");
     
     if let Ok(key) = std::env::var("WALLET_PRIVATE_KEY") {
-        println!("🔑 Wallet: {}...{}", &key[..8], &key[key.len()-8..]);
+        println!("🔑 Wallet: {}...", short(&key));
     } else {
         println!("❌ WALLET_PRIVATE_KEY not set");
     }
     
     if let Ok(key) = std::env::var("HELIUS_API_KEY") {
-        println!("🌐 Helius API: {}...{}", &key[..8], &key[key.len()-8..]);
+        println!("🌐 Helius API: {}...", short(&key));
     } else {
         println!("❌ HELIUS_API_KEY not set");
     }
@@ -552,8 +1182,14 @@ fn show_settings() {
     println!("\n📊 Available Strategies:");
     println!("   • Conservative: 15% profit target, 5% stop loss");
     println!("   • Aggressive: 50% profit target, 15% stop loss");
-    println!("   • Conservative ATH: 8% pullback from ATH, 3% minimum profit ⭐ RECOMMENDED");
-    println!("   • Aggressive ATH: 12% pullback from ATH, 5% minimum profit");
+    println!("   • Conservative ATH: 8% pullback from ATH, 3% minimum profit, 20% max drawdown ⭐ RECOMMENDED");
+    println!("   • Aggressive ATH: 12% pullback from ATH, 5% minimum profit, 30% max drawdown");
+    println!("   • Custom: your own take profit, stop loss, pullback and min profit");
+    
+    println!("\n📝 Paper Trading:");
+    println!("   • Start with: cargo run --bin cli -- --paper [starting SOL, default {}]", DEFAULT_PAPER_BALANCE_SOL);
+    println!("   • Fills use live Jupiter quotes against a virtual SOL balance");
+    println!("   • State is kept separately under <state dir>/paper");
     
     println!("\n🎯 Supported Platforms (AUTO-DETECTED):");
     println!("   • PumpFun: For new meme coins on pump.fun");
@@ -566,13 +1202,24 @@ fn show_settings() {
     println!("   • 'usdt' → {} (USDT)", token_addresses::USDT);
     println!("   • 'jup' → {} (JUP)", token_addresses::JUP);
     
-    println!("\n⚙️ Configuration:");
-    println!("   • Max trade size: 10.0 SOL");
-    println!("   • Min trade size: 0.000001 SOL");
-    println!("   • Default slippage: 1.0%");
-    println!("   • Slippage range: 0.1% - 50.0%");
-    println!("   • Priority fee: Dynamic (High priority)");
-    println!("   • Jupiter API: v4 (Fixed)");
+    let source = match TraderConfig::file_path() {
+        Some(path) => path.display().to_string(),
+        None => "built-in defaults".to_string(),
+    };
+    println!("\n⚙️ Effective Configuration (from {} + TRADER_* overrides):", source);
+    println!("   • Mode: {}", match config.paper_starting_sol {
+        Some(sol) => format!("PAPER ({} SOL starting balance)", sol),
+        None => "LIVE".to_string(),
+    });
+    println!("   • State directory: {}", config.effective_state_dir().display());
+    match config.to_toml() {
+        Ok(toml) => {
+            for line in toml.lines() {
+                println!("   {}", line);
+            }
+        },
+        Err(e) => println!("   ❌ Could not render config: {}", e),
+    }
     
     println!("\n🔧 RECENT FIXES:");
     println!("   ✅ Fixed Jupiter API endpoint (v6 → v4)");
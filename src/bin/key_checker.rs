@@ -3,7 +3,6 @@
 use solana_sdk::signature::{Keypair, Signer};
 use anyhow::Result;
 use std::io::{self, Write};
-use bs58;
 
 fn main() -> Result<()> {
     println!("🔑 Solana Private Key Format Checker");
@@ -88,11 +87,7 @@ fn try_parse_key(private_key: &str) -> Result<Keypair> {
     // 3. Try as hex string (without 0x prefix)
     println!("   → Trying hex format...");
     if trimmed_key.len() == 128 || (trimmed_key.len() == 130 && trimmed_key.starts_with("0x")) {
-        let hex_str = if trimmed_key.starts_with("0x") {
-            &trimmed_key[2..]
-        } else {
-            trimmed_key
-        };
+        let hex_str = trimmed_key.strip_prefix("0x").unwrap_or(trimmed_key);
         
         if let Ok(bytes) = hex::decode(hex_str) {
             println!("     Hex decoded to {} bytes", bytes.len());
@@ -0,0 +1,576 @@
+// src/config.rs - Layered trader configuration
+// Defaults, then a TOML or JSON file, then TRADER_* environment overrides
+
+use crate::http::Endpoints;
+use crate::jito;
+use crate::state::StateStore;
+use crate::{ExecutionMode, TraderOptions};
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+
+// Searched in the working directory when TRADER_CONFIG is not set
+const DEFAULT_CONFIG_FILES: [&str; 2] = ["trader.toml", "trader.json"];
+
+const MIN_API_TOKEN_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraderConfig {
+    // Defaults to TRADER_STATE_DIR or .trader_state
+    pub state_dir: Option<PathBuf>,
+    // Set to trade on paper with this virtual SOL balance
+    pub paper_starting_sol: Option<f64>,
+    pub endpoints: Endpoints,
+    pub fees: FeeConfig,
+    pub limits: TradeLimits,
+    pub transactions: TransactionConfig,
+    pub daemon: DaemonConfig,
+    pub api: ApiConfig,
+    pub jito: JitoConfig,
+}
+
+// Priority fees in microlamports per compute unit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeConfig {
+    pub max_priority_fee: u64,
+    // Used when the Helius fee estimate is unavailable
+    pub fallback_priority_fee: u64,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            max_priority_fee: 200_000,
+            fallback_priority_fee: 150_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TradeLimits {
+    pub min_trade_sol: f64,
+    pub max_trade_sol: f64,
+    pub sell_slippage_bps: u16,
+}
+
+impl Default for TradeLimits {
+    fn default() -> Self {
+        Self {
+            min_trade_sol: 0.000001,
+            max_trade_sol: 50.0,
+            sell_slippage_bps: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransactionConfig {
+    pub send_retries: u32,
+    // Backoff doubles from this after each failed send
+    pub retry_base_delay_ms: u64,
+    pub quote_retries: u32,
+    pub commitment: Commitment,
+    // Simulate before sending and size the compute budget from the result
+    pub simulate: bool,
+    // Headroom added to simulated compute units
+    pub compute_unit_margin_percent: u32,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> Self {
+        Self {
+            send_retries: 5,
+            retry_base_delay_ms: 500,
+            quote_retries: 3,
+            commitment: Commitment::Processed,
+            simulate: true,
+            compute_unit_margin_percent: 20,
+        }
+    }
+}
+
+// Background monitoring loop run by `trader daemon`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonConfig {
+    pub monitor_interval_secs: u64,
+    pub heartbeat_interval_secs: u64,
+    // Push bonding curve prices over the RPC websocket between monitor cycles
+    pub stream_prices: bool,
+    pub stream_reconnect_secs: u64,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            monitor_interval_secs: 10,
+            heartbeat_interval_secs: 60,
+            stream_prices: true,
+            stream_reconnect_secs: 5,
+        }
+    }
+}
+
+// Bundle submission through the Jito block engine, with plain RPC as the fallback
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JitoConfig {
+    pub enabled: bool,
+    // Default tip; trades can override it with TradeConfig::tip_lamports
+    pub tip_lamports: u64,
+    // How long to wait for a bundle to land before falling back to RPC
+    pub bundle_timeout_ms: u64,
+    pub status_poll_ms: u64,
+}
+
+impl Default for JitoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tip_lamports: 100_000,
+            bundle_timeout_ms: 10_000,
+            status_poll_ms: 500,
+        }
+    }
+}
+
+// Local control API served alongside the daemon (requires the control-api feature)
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub bind: String,
+    // Bearer token clients must send; prefer TRADER_API_TOKEN over writing it to a file
+    pub token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:8787".to_string(),
+            token: None,
+        }
+    }
+}
+
+// Keep the token out of logs and the settings screen
+impl fmt::Debug for ApiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("enabled", &self.enabled)
+            .field("bind", &self.bind)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn to_commitment_config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+impl FromStr for Commitment {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "processed" => Ok(Commitment::Processed),
+            "confirmed" => Ok(Commitment::Confirmed),
+            "finalized" => Ok(Commitment::Finalized),
+            other => Err(anyhow!("unknown commitment '{}', use processed, confirmed or finalized", other)),
+        }
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Commitment::Processed => write!(f, "processed"),
+            Commitment::Confirmed => write!(f, "confirmed"),
+            Commitment::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+impl TraderConfig {
+    // Load from TRADER_CONFIG, else trader.toml or trader.json if present, then apply env overrides
+    pub fn load() -> Result<Self> {
+        Self::load_layered(Self::file_path().as_deref())
+    }
+
+    // The config file load() reads, if any
+    pub fn file_path() -> Option<PathBuf> {
+        match std::env::var("TRADER_CONFIG") {
+            Ok(path) => Some(PathBuf::from(path)),
+            Err(_) => DEFAULT_CONFIG_FILES.iter().map(PathBuf::from).find(|path| path.exists()),
+        }
+    }
+
+    // Defaults, then the file at path (if any), then env overrides, then validation
+    pub fn load_layered(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env_overrides()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Parse a config file as JSON (.json) or TOML (anything else), without env overrides
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read config {}: {}", path.display(), e))?;
+
+        let is_json = path.extension().map(|ext| ext.eq_ignore_ascii_case("json")).unwrap_or(false);
+        let config = if is_json {
+            serde_json::from_str(&data).map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))?
+        } else {
+            toml::from_str(&data).map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))?
+        };
+
+        log::info!("Loaded config from {}", path.display());
+        Ok(config)
+    }
+
+    pub fn apply_env_overrides(&mut self) -> Result<()> {
+        if let Some(dir) = env_var("TRADER_STATE_DIR") {
+            self.state_dir = Some(PathBuf::from(dir));
+        }
+        if let Some(sol) = env_parse("TRADER_PAPER_SOL")? {
+            self.paper_starting_sol = Some(sol);
+        }
+
+        let endpoints = [
+            ("TRADER_JUPITER_API", &mut self.endpoints.jupiter_api),
+            ("TRADER_JUPITER_PRICE_API", &mut self.endpoints.jupiter_price_api),
+            ("TRADER_PUMP_FRONTEND_API", &mut self.endpoints.pump_frontend_api),
+            ("TRADER_HELIUS_RPC", &mut self.endpoints.helius_rpc),
+            ("TRADER_JITO_BLOCK_ENGINE", &mut self.endpoints.jito_block_engine),
+        ];
+        for (name, field) in endpoints {
+            if let Some(url) = env_var(name) {
+                *field = url;
+            }
+        }
+
+        override_with(&mut self.fees.max_priority_fee, "TRADER_MAX_PRIORITY_FEE")?;
+        override_with(&mut self.fees.fallback_priority_fee, "TRADER_FALLBACK_PRIORITY_FEE")?;
+        override_with(&mut self.limits.min_trade_sol, "TRADER_MIN_TRADE_SOL")?;
+        override_with(&mut self.limits.max_trade_sol, "TRADER_MAX_TRADE_SOL")?;
+        override_with(&mut self.limits.sell_slippage_bps, "TRADER_SELL_SLIPPAGE_BPS")?;
+        override_with(&mut self.transactions.send_retries, "TRADER_SEND_RETRIES")?;
+        override_with(&mut self.transactions.retry_base_delay_ms, "TRADER_RETRY_BASE_DELAY_MS")?;
+        override_with(&mut self.transactions.quote_retries, "TRADER_QUOTE_RETRIES")?;
+        override_with(&mut self.transactions.commitment, "TRADER_COMMITMENT")?;
+        override_with(&mut self.transactions.simulate, "TRADER_SIMULATE")?;
+        override_with(&mut self.daemon.monitor_interval_secs, "TRADER_MONITOR_INTERVAL_SECS")?;
+        override_with(&mut self.daemon.heartbeat_interval_secs, "TRADER_HEARTBEAT_INTERVAL_SECS")?;
+        override_with(&mut self.daemon.stream_prices, "TRADER_STREAM_PRICES")?;
+        override_with(&mut self.daemon.stream_reconnect_secs, "TRADER_STREAM_RECONNECT_SECS")?;
+        override_with(&mut self.jito.enabled, "TRADER_JITO_ENABLED")?;
+        override_with(&mut self.jito.tip_lamports, "TRADER_JITO_TIP_LAMPORTS")?;
+        override_with(&mut self.api.enabled, "TRADER_API_ENABLED")?;
+        if let Some(bind) = env_var("TRADER_API_BIND") {
+            self.api.bind = bind;
+        }
+        if let Some(token) = env_var("TRADER_API_TOKEN") {
+            self.api.token = Some(token);
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let urls = [
+            ("endpoints.jupiter_api", &self.endpoints.jupiter_api),
+            ("endpoints.jupiter_price_api", &self.endpoints.jupiter_price_api),
+            ("endpoints.pump_frontend_api", &self.endpoints.pump_frontend_api),
+            ("endpoints.helius_rpc", &self.endpoints.helius_rpc),
+            ("endpoints.jito_block_engine", &self.endpoints.jito_block_engine),
+        ];
+        for (name, url) in urls {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(anyhow!("{} must be an http(s) URL, got '{}'", name, url));
+            }
+        }
+
+        // NaN fails every comparison, so each SOL amount must be checked for finiteness first
+        if let Some(sol) = self.paper_starting_sol {
+            if !sol.is_finite() || sol <= 0.0 {
+                return Err(anyhow!("paper_starting_sol must be positive, got {}", sol));
+            }
+        }
+
+        if self.fees.max_priority_fee == 0 {
+            return Err(anyhow!("fees.max_priority_fee must be positive"));
+        }
+        if self.fees.fallback_priority_fee > self.fees.max_priority_fee {
+            return Err(anyhow!(
+                "fees.fallback_priority_fee ({}) must not exceed fees.max_priority_fee ({})",
+                self.fees.fallback_priority_fee, self.fees.max_priority_fee
+            ));
+        }
+
+        if !self.limits.min_trade_sol.is_finite() || self.limits.min_trade_sol <= 0.0 {
+            return Err(anyhow!("limits.min_trade_sol must be positive, got {}", self.limits.min_trade_sol));
+        }
+        if !self.limits.max_trade_sol.is_finite() {
+            return Err(anyhow!("limits.max_trade_sol must be a finite number, got {}", self.limits.max_trade_sol));
+        }
+        if self.limits.max_trade_sol < self.limits.min_trade_sol {
+            return Err(anyhow!(
+                "limits.max_trade_sol ({}) must be at least limits.min_trade_sol ({})",
+                self.limits.max_trade_sol, self.limits.min_trade_sol
+            ));
+        }
+        if self.limits.sell_slippage_bps == 0 || self.limits.sell_slippage_bps > 5000 {
+            return Err(anyhow!("limits.sell_slippage_bps must be between 1 and 5000, got {}", self.limits.sell_slippage_bps));
+        }
+
+        if self.transactions.send_retries == 0 || self.transactions.send_retries > 20 {
+            return Err(anyhow!("transactions.send_retries must be between 1 and 20, got {}", self.transactions.send_retries));
+        }
+        if self.transactions.retry_base_delay_ms == 0 || self.transactions.retry_base_delay_ms > 60_000 {
+            return Err(anyhow!(
+                "transactions.retry_base_delay_ms must be between 1 and 60000, got {}",
+                self.transactions.retry_base_delay_ms
+            ));
+        }
+        if self.transactions.quote_retries == 0 || self.transactions.quote_retries > 20 {
+            return Err(anyhow!("transactions.quote_retries must be between 1 and 20, got {}", self.transactions.quote_retries));
+        }
+        if self.transactions.compute_unit_margin_percent > 100 {
+            return Err(anyhow!(
+                "transactions.compute_unit_margin_percent must be at most 100, got {}",
+                self.transactions.compute_unit_margin_percent
+            ));
+        }
+
+        if self.daemon.monitor_interval_secs == 0 {
+            return Err(anyhow!("daemon.monitor_interval_secs must be at least 1"));
+        }
+        if self.daemon.heartbeat_interval_secs == 0 {
+            return Err(anyhow!("daemon.heartbeat_interval_secs must be at least 1"));
+        }
+        if self.daemon.stream_reconnect_secs == 0 {
+            return Err(anyhow!("daemon.stream_reconnect_secs must be at least 1"));
+        }
+
+        if self.jito.tip_lamports < jito::MIN_TIP_LAMPORTS {
+            return Err(anyhow!("jito.tip_lamports must be at least {}, got {}", jito::MIN_TIP_LAMPORTS, self.jito.tip_lamports));
+        }
+        if self.jito.status_poll_ms == 0 || self.jito.bundle_timeout_ms < self.jito.status_poll_ms {
+            return Err(anyhow!("jito.status_poll_ms must be positive and no longer than jito.bundle_timeout_ms"));
+        }
+
+        if self.api.enabled {
+            std::net::SocketAddr::from_str(&self.api.bind)
+                .map_err(|e| anyhow!("api.bind must be an ip:port address, got '{}': {}", self.api.bind, e))?;
+            match &self.api.token {
+                Some(token) if token.len() >= MIN_API_TOKEN_LEN => {},
+                Some(_) => return Err(anyhow!("api.token must be at least {} characters", MIN_API_TOKEN_LEN)),
+                None => return Err(anyhow!("api.token (or TRADER_API_TOKEN) is required when the control API is enabled")),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Live state goes in the state dir; paper state in its paper subdirectory
+    pub fn effective_state_dir(&self) -> PathBuf {
+        let base = self.state_dir.clone().unwrap_or_else(StateStore::default_dir);
+        match self.paper_starting_sol {
+            Some(_) => base.join("paper"),
+            None => base,
+        }
+    }
+
+    pub fn execution_mode(&self) -> ExecutionMode {
+        match self.paper_starting_sol {
+            Some(starting_sol) => ExecutionMode::Paper { starting_sol },
+            None => ExecutionMode::Live,
+        }
+    }
+
+    pub fn to_options(&self) -> TraderOptions {
+        TraderOptions {
+            state_dir: self.effective_state_dir(),
+            mode: self.execution_mode(),
+            endpoints: self.endpoints.clone(),
+            fees: self.fees,
+            limits: self.limits,
+            transactions: self.transactions,
+            jito: self.jito,
+            ..TraderOptions::default()
+        }
+    }
+
+    // The effective configuration as TOML, e.g. for a settings screen. Secrets are masked.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut shown = self.clone();
+        if shown.api.token.is_some() {
+            shown.api.token = Some("<redacted>".to_string());
+        }
+        Ok(toml::to_string_pretty(&shown)?)
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Result<Option<T>>
+where
+    T::Err: fmt::Display,
+{
+    match env_var(name) {
+        Some(value) => value.trim().parse::<T>()
+            .map(Some)
+            .map_err(|e| anyhow!("Invalid {}='{}': {}", name, value, e)),
+        None => Ok(None),
+    }
+}
+
+fn override_with<T: FromStr>(field: &mut T, name: &str) -> Result<()>
+where
+    T::Err: fmt::Display,
+{
+    if let Some(value) = env_parse(name)? {
+        *field = value;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejects(config: &TraderConfig, field: &str) {
+        let error = config.validate().err().unwrap_or_else(|| panic!("{} was accepted", field));
+        assert!(error.to_string().contains(field), "{}", error);
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        TraderConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn non_finite_sol_amounts_are_rejected() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let mut config = TraderConfig::default();
+            config.limits.min_trade_sol = value;
+            rejects(&config, "limits.min_trade_sol");
+
+            let mut config = TraderConfig::default();
+            config.limits.max_trade_sol = value;
+            rejects(&config, "limits.max_trade_sol");
+
+            let config = TraderConfig { paper_starting_sol: Some(value), ..TraderConfig::default() };
+            rejects(&config, "paper_starting_sol");
+        }
+    }
+
+    #[test]
+    fn out_of_range_settings_are_rejected() {
+        let mut config = TraderConfig::default();
+        config.limits.max_trade_sol = config.limits.min_trade_sol / 2.0;
+        rejects(&config, "limits.max_trade_sol");
+
+        let mut config = TraderConfig::default();
+        config.limits.sell_slippage_bps = 5001;
+        rejects(&config, "limits.sell_slippage_bps");
+
+        let mut config = TraderConfig::default();
+        config.fees.fallback_priority_fee = config.fees.max_priority_fee + 1;
+        rejects(&config, "fees.fallback_priority_fee");
+
+        for delay_ms in [0, 60_001, u64::MAX] {
+            let mut config = TraderConfig::default();
+            config.transactions.retry_base_delay_ms = delay_ms;
+            rejects(&config, "transactions.retry_base_delay_ms");
+        }
+
+        let mut config = TraderConfig::default();
+        config.endpoints.jupiter_api = "ftp://example.com".to_string();
+        rejects(&config, "endpoints.jupiter_api");
+
+        let mut config = TraderConfig::default();
+        config.api.enabled = true;
+        rejects(&config, "api.token");
+        config.api.token = Some("short".to_string());
+        rejects(&config, "api.token");
+        config.api.token = Some("a-long-enough-api-token".to_string());
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn file_values_are_layered_over_defaults() {
+        let path = std::env::temp_dir().join(format!("fast-meme-trader-config-{}.toml", std::process::id()));
+        fs::write(&path, "[limits]\nmax_trade_sol = 2.5\n\n[transactions]\ncommitment = \"confirmed\"\n").unwrap();
+
+        let config = TraderConfig::from_file(&path).unwrap();
+        assert_eq!(config.limits.max_trade_sol, 2.5);
+        assert_eq!(config.limits.min_trade_sol, TradeLimits::default().min_trade_sol);
+        assert_eq!(config.transactions.commitment, Commitment::Confirmed);
+
+        fs::write(&path, "[limits]\nmax_trade = 2.5\n").unwrap();
+        assert!(TraderConfig::from_file(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    // The only test that touches these variables, so it can't race another test
+    #[test]
+    fn env_overrides_are_parsed_and_validated() {
+        let vars = ["TRADER_MAX_TRADE_SOL", "TRADER_SEND_RETRIES", "TRADER_COMMITMENT", "TRADER_SIMULATE"];
+        std::env::set_var("TRADER_MAX_TRADE_SOL", " 3.5 ");
+        std::env::set_var("TRADER_SEND_RETRIES", "7");
+        std::env::set_var("TRADER_COMMITMENT", "Finalized");
+        std::env::set_var("TRADER_SIMULATE", "false");
+
+        let mut config = TraderConfig::default();
+        config.apply_env_overrides().unwrap();
+        assert_eq!(config.limits.max_trade_sol, 3.5);
+        assert_eq!(config.transactions.send_retries, 7);
+        assert_eq!(config.transactions.commitment, Commitment::Finalized);
+        assert!(!config.transactions.simulate);
+
+        std::env::set_var("TRADER_SEND_RETRIES", "seven");
+        let error = TraderConfig::default().apply_env_overrides().err().unwrap();
+        assert!(error.to_string().contains("TRADER_SEND_RETRIES"), "{}", error);
+        std::env::set_var("TRADER_SEND_RETRIES", "7");
+
+        // "nan" parses as an f64, so validation has to catch it
+        std::env::set_var("TRADER_MAX_TRADE_SOL", "nan");
+        let mut config = TraderConfig::default();
+        config.apply_env_overrides().unwrap();
+        rejects(&config, "limits.max_trade_sol");
+
+        for var in vars {
+            std::env::remove_var(var);
+        }
+    }
+}
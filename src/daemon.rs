@@ -0,0 +1,157 @@
+// src/daemon.rs - Long-running monitor loop with graceful shutdown
+// SIGINT/SIGTERM stop the loop between cycles, so in-flight sells always finish
+
+use crate::{FastMemeTrader, SellEvent};
+use crate::config::DaemonConfig;
+use crate::price_stream::{self, StreamedPrice};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio::time::MissedTickBehavior;
+use anyhow::Result;
+
+#[derive(Debug, Clone, Default)]
+pub struct DaemonStats {
+    pub cycles: u64,
+    pub streamed_ticks: u64,
+    pub sells: u64,
+    pub failed_sells: u64,
+}
+
+impl DaemonStats {
+    fn record_sells(&mut self, sells: Vec<SellEvent>) {
+        for sell in sells {
+            self.sells += 1;
+            if sell.trade.success {
+                log::info!("{}", sell);
+            } else {
+                self.failed_sells += 1;
+                log::error!("{}", sell);
+            }
+        }
+    }
+}
+
+// Listen for SIGINT/SIGTERM in the background. The receiver flips to true on the
+// first signal; a second signal exits immediately without waiting for the cycle.
+pub fn spawn_shutdown_listener() -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(false);
+
+    tokio::spawn(async move {
+        wait_for_signal().await;
+        log::warn!("Shutdown requested, finishing in-flight sells (signal again to force exit)");
+        let _ = sender.send(true);
+
+        wait_for_signal().await;
+        log::error!("Second shutdown signal, exiting without flushing state");
+        std::process::exit(130);
+    });
+
+    receiver
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {},
+                _ = sigterm.recv() => {},
+            }
+        },
+        Err(e) => {
+            log::warn!("Could not listen for SIGTERM: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+// Wait for the next streamed price, or forever when streaming is off or has stopped
+async fn next_streamed(ticks: &mut Option<mpsc::Receiver<(String, StreamedPrice)>>) -> Option<(String, StreamedPrice)> {
+    match ticks {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+// Run monitor_positions every monitor interval until shutdown, then flush state.
+// With streaming on, curve and pool updates are also evaluated as they arrive;
+// polling keeps covering every position in case the stream drops.
+pub async fn run(trader: &FastMemeTrader, config: &DaemonConfig, mut shutdown: watch::Receiver<bool>) -> Result<DaemonStats> {
+    let started = Instant::now();
+    let mut stats = DaemonStats::default();
+
+    let mut monitor = tokio::time::interval(Duration::from_secs(config.monitor_interval_secs));
+    monitor.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(config.heartbeat_interval_secs));
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut ticks = config.stream_prices.then(|| {
+        let settings = trader.price_stream_settings(Duration::from_secs(config.stream_reconnect_secs));
+        price_stream::spawn(settings, trader.positions.clone(), shutdown.clone())
+    });
+
+    log::info!(
+        "Daemon started{}: monitoring every {}s{}, heartbeat every {}s",
+        if trader.is_paper() { " in PAPER mode" } else { "" },
+        config.monitor_interval_secs,
+        if config.stream_prices { " plus streamed prices" } else { "" },
+        config.heartbeat_interval_secs
+    );
+
+    while !*shutdown.borrow() {
+        tokio::select! {
+            biased;
+            _ = shutdown.changed() => {},
+            _ = heartbeat.tick() => {
+                log::info!(
+                    "Heartbeat: up {}s | cycles {} | streamed ticks {} | open positions {} | sells {} ({} failed)",
+                    started.elapsed().as_secs(), stats.cycles, stats.streamed_ticks,
+                    trader.positions.read().await.len(), stats.sells, stats.failed_sells
+                );
+            },
+            _ = monitor.tick() => {
+                // Signals during the cycle are picked up once it completes
+                let cycle_start = Instant::now();
+                let sells = trader.monitor_positions().await;
+                stats.record_sells(sells);
+                stats.cycles += 1;
+                log::debug!("Monitor cycle {} took {}ms", stats.cycles, cycle_start.elapsed().as_millis());
+            },
+            streamed = next_streamed(&mut ticks) => {
+                let Some((token_address, price)) = streamed else {
+                    log::warn!("Price stream ended, continuing with polling only");
+                    ticks = None;
+                    continue;
+                };
+
+                // Only the newest price per token matters if updates queued up during a sell
+                let mut latest = HashMap::from([(token_address, price)]);
+                if let Some(receiver) = ticks.as_mut() {
+                    while let Ok((token_address, price)) = receiver.try_recv() {
+                        latest.insert(token_address, price);
+                    }
+                }
+                for (token_address, price) in latest {
+                    stats.streamed_ticks += 1;
+                    let sells = trader.apply_streamed_price(&token_address, price).await;
+                    stats.record_sells(sells);
+                }
+            },
+        }
+    }
+
+    trader.flush_state().await?;
+    log::info!(
+        "Daemon stopped after {}s: {} cycles, {} sells ({} failed). State flushed.",
+        started.elapsed().as_secs(), stats.cycles, stats.sells, stats.failed_sells
+    );
+    Ok(stats)
+}
@@ -0,0 +1,280 @@
+// src/fill.rs - What a confirmed swap actually did to the wallet
+// Read from the transaction's pre/post balances, so positions record the fill
+// rather than the quote
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{UiTransactionStatusMeta, UiTransactionTokenBalance};
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    // Change in the wallet's token balance for the traded mint
+    pub token_delta: i128,
+    // Change in the wallet's SOL, excluding the network fee and token account rent
+    pub sol_delta_lamports: i64,
+    pub fee_lamports: u64,
+    // Rent locked into a token account the swap created for us
+    pub rent_lamports: u64,
+    // The mint's decimals, when the transaction touched one of our token accounts
+    pub decimals: Option<u8>,
+}
+
+impl Fill {
+    // The wallet pays the fee, so it is always account 0
+    pub fn from_meta(meta: &UiTransactionStatusMeta, owner: &Pubkey, mint: &Pubkey) -> Result<Self> {
+        if let Some(err) = &meta.err {
+            return Err(anyhow!("Transaction failed on chain: {:?}", err));
+        }
+        let (pre_sol, post_sol) = match (meta.pre_balances.first(), meta.post_balances.first()) {
+            (Some(pre), Some(post)) => (*pre as i64, *post as i64),
+            _ => return Err(anyhow!("Transaction metadata has no balances")),
+        };
+
+        let owner = owner.to_string();
+        let mint = mint.to_string();
+        let pre_tokens = owned_amount(token_balances(&meta.pre_token_balances), &owner, &mint)?;
+        let post_tokens = owned_amount(token_balances(&meta.post_token_balances), &owner, &mint)?;
+
+        // A token account that held no lamports before the swap was created by it
+        let rent_lamports: u64 = token_balances(&meta.post_token_balances)
+            .iter()
+            .filter(|balance| balance.mint == mint && owner_of(balance) == Some(owner.as_str()))
+            .map(|balance| balance.account_index as usize)
+            .filter(|&index| meta.pre_balances.get(index) == Some(&0))
+            .filter_map(|index| meta.post_balances.get(index))
+            .sum();
+
+        let decimals = token_balances(&meta.post_token_balances)
+            .iter()
+            .chain(token_balances(&meta.pre_token_balances))
+            .find(|balance| balance.mint == mint)
+            .map(|balance| balance.ui_token_amount.decimals);
+
+        Ok(Self {
+            token_delta: post_tokens - pre_tokens,
+            sol_delta_lamports: post_sol - pre_sol + meta.fee as i64 + rent_lamports as i64,
+            fee_lamports: meta.fee,
+            rent_lamports,
+            decimals,
+        })
+    }
+
+    pub fn tokens_received(&self) -> u64 {
+        self.token_delta.max(0) as u64
+    }
+
+    pub fn tokens_sent(&self) -> u64 {
+        (-self.token_delta).max(0) as u64
+    }
+
+    pub fn sol_spent_lamports(&self) -> u64 {
+        (-self.sol_delta_lamports).max(0) as u64
+    }
+
+    pub fn sol_received_lamports(&self) -> u64 {
+        self.sol_delta_lamports.max(0) as u64
+    }
+}
+
+// Realized shortfall against the quoted output, in percent. Negative when the
+// fill beat the quote.
+pub fn slippage_percent(quoted: u64, actual: u64) -> Option<Decimal> {
+    if quoted == 0 {
+        return None;
+    }
+    let quoted = Decimal::from(quoted);
+    Some((quoted - Decimal::from(actual)) / quoted * Decimal::from(100))
+}
+
+// SOL paid per whole token, from raw token units and the mint's decimals
+pub fn price_per_token_sol(sol: f64, raw_tokens: u64, decimals: u8) -> Option<Decimal> {
+    let whole_tokens = Decimal::try_from_i128_with_scale(raw_tokens as i128, decimals as u32).ok()?;
+    let sol = Decimal::from_f64(sol)?;
+    if whole_tokens.is_zero() || sol <= Decimal::ZERO {
+        return None;
+    }
+    Some(sol / whole_tokens)
+}
+
+fn token_balances(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>) -> &[UiTransactionTokenBalance] {
+    match balances {
+        OptionSerializer::Some(balances) => balances,
+        _ => &[],
+    }
+}
+
+fn owner_of(balance: &UiTransactionTokenBalance) -> Option<&str> {
+    match &balance.owner {
+        OptionSerializer::Some(owner) => Some(owner),
+        _ => None,
+    }
+}
+
+// Total raw amount of the mint held by the owner across the listed accounts
+fn owned_amount(balances: &[UiTransactionTokenBalance], owner: &str, mint: &str) -> Result<i128> {
+    balances.iter()
+        .filter(|balance| balance.mint == mint && owner_of(balance) == Some(owner))
+        .map(|balance| {
+            balance.ui_token_amount.amount.parse::<i128>()
+                .map_err(|e| anyhow!("Invalid token amount '{}': {}", balance.ui_token_amount.amount, e))
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    const RENT: u64 = 2_039_280;
+    const FEE: u64 = 5_000;
+
+    // Metadata as getTransaction returns it
+    fn meta(err: Value, pre: &[u64], post: &[u64], pre_tokens: Value, post_tokens: Value) -> UiTransactionStatusMeta {
+        serde_json::from_value(json!({
+            "err": err,
+            "status": { "Ok": null },
+            "fee": FEE,
+            "preBalances": pre,
+            "postBalances": post,
+            "preTokenBalances": pre_tokens,
+            "postTokenBalances": post_tokens,
+        }))
+        .unwrap()
+    }
+
+    fn balance(index: u8, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint.to_string(),
+            "owner": owner.to_string(),
+            "uiTokenAmount": { "amount": amount.to_string(), "decimals": 6, "uiAmount": null, "uiAmountString": "" },
+        })
+    }
+
+    #[test]
+    fn buy_into_a_new_token_account_separates_rent_and_fee() {
+        let (owner, mint, pool) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let spent = 1_000_000_000;
+        let meta = meta(
+            Value::Null,
+            &[10_000_000_000, 0, 5_000_000],
+            &[10_000_000_000 - spent - FEE - RENT, RENT, 5_000_000],
+            json!([balance(2, &mint, &pool, 9_000_000)]),
+            json!([balance(1, &mint, &owner, 1_000_000), balance(2, &mint, &pool, 8_000_000)]),
+        );
+
+        let fill = Fill::from_meta(&meta, &owner, &mint).unwrap();
+        assert_eq!(fill, Fill {
+            token_delta: 1_000_000,
+            sol_delta_lamports: -(spent as i64),
+            fee_lamports: FEE,
+            rent_lamports: RENT,
+            decimals: Some(6),
+        });
+        assert_eq!(fill.tokens_received(), 1_000_000);
+        assert_eq!(fill.tokens_sent(), 0);
+        assert_eq!(fill.sol_spent_lamports(), spent);
+        assert_eq!(fill.sol_received_lamports(), 0);
+    }
+
+    #[test]
+    fn sell_from_an_existing_account_reports_sol_received() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let received = 500_000_000;
+        let meta = meta(
+            Value::Null,
+            &[1_000_000_000, RENT],
+            &[1_000_000_000 + received - FEE, RENT],
+            json!([balance(1, &mint, &owner, 1_000_000)]),
+            json!([balance(1, &mint, &owner, 400_000)]),
+        );
+
+        let fill = Fill::from_meta(&meta, &owner, &mint).unwrap();
+        assert_eq!(fill.rent_lamports, 0);
+        assert_eq!(fill.tokens_sent(), 600_000);
+        assert_eq!(fill.sol_received_lamports(), received);
+        assert_eq!(fill.sol_spent_lamports(), 0);
+    }
+
+    #[test]
+    fn other_mints_and_owners_are_ignored() {
+        let (owner, mint, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let meta = meta(
+            Value::Null,
+            &[1_000_000_000, RENT, RENT],
+            &[1_000_000_000 - FEE, RENT, RENT],
+            json!([balance(1, &other, &owner, 10), balance(2, &mint, &other, 10)]),
+            json!([balance(1, &other, &owner, 0), balance(2, &mint, &other, 20)]),
+        );
+
+        let fill = Fill::from_meta(&meta, &owner, &mint).unwrap();
+        assert_eq!(fill.token_delta, 0);
+        assert_eq!(fill.sol_delta_lamports, 0);
+    }
+
+    #[test]
+    fn failed_transactions_have_no_fill() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let meta = meta(
+            json!({ "InstructionError": [0, { "Custom": 1 }] }),
+            &[1_000_000_000],
+            &[1_000_000_000 - FEE],
+            json!([]),
+            json!([]),
+        );
+        assert!(Fill::from_meta(&meta, &owner, &mint).is_err());
+    }
+
+    #[test]
+    fn slippage_is_the_shortfall_against_the_quote() {
+        assert_eq!(slippage_percent(1_000, 990), Some(Decimal::ONE));
+        assert_eq!(slippage_percent(1_000, 1_010), Some(-Decimal::ONE));
+        assert_eq!(slippage_percent(0, 10), None);
+    }
+
+    #[test]
+    fn price_per_token_uses_whole_tokens() {
+        // 0.5 SOL for 2,000,000 raw units of a 6 decimal mint: 0.25 SOL per token
+        assert_eq!(price_per_token_sol(0.5, 2_000_000, 6), Some(Decimal::new(25, 2)));
+        assert_eq!(price_per_token_sol(0.5, 0, 6), None);
+        assert_eq!(price_per_token_sol(0.0, 2_000_000, 6), None);
+    }
+
+    #[test]
+    fn round_trip_pnl_is_net_of_fees_but_not_rent() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let buy = Fill::from_meta(
+            &meta(Value::Null, &[3_000_000_000, 0], &[2_000_000_000 - FEE - RENT, RENT], json!([]),
+                json!([balance(1, &mint, &owner, 1_000_000)])),
+            &owner, &mint,
+        ).unwrap();
+        let sell = Fill::from_meta(
+            &meta(Value::Null, &[2_000_000_000 - FEE - RENT, RENT], &[3_500_000_000 - 2 * FEE - RENT, RENT],
+                json!([balance(1, &mint, &owner, 1_000_000)]), json!([balance(1, &mint, &owner, 0)])),
+            &owner, &mint,
+        ).unwrap();
+
+        let lamports = |amount: u64| amount as f64 / 1_000_000_000.0;
+        let trade = crate::trade_journal::ClosedTrade {
+            token_address: mint.to_string(),
+            buy_signature: "buy".to_string(),
+            sell_signature: "sell".to_string(),
+            sol_in: lamports(buy.sol_spent_lamports()),
+            sol_out: lamports(sell.sol_received_lamports()),
+            fees_sol: lamports(buy.fee_lamports + sell.fee_lamports),
+            entry_time: chrono::Utc::now(),
+            exit_time: chrono::Utc::now(),
+            hold_time_secs: 0,
+            strategy: crate::StrategyType::default(),
+            exit_reason: crate::strategy::ExitReason::manual(),
+        };
+
+        // 1 SOL in, 1.5 SOL out, two 5000 lamport fees; the token account rent is still ours
+        assert!((trade.pnl_sol() - 0.49999).abs() < 1e-9, "pnl {}", trade.pnl_sol());
+        assert!((trade.return_percent() - 49.999).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,115 @@
+// src/http.rs - HTTP transport and API base URLs
+// Lets tests and staging point the trader at local mock servers
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json(&self) -> Result<Value> {
+        serde_json::from_str(&self.body)
+            .map_err(|e| anyhow!("Invalid JSON response ({}): {}", e, self.body))
+    }
+}
+
+// Blocking transport behind every external API call. Non-2xx statuses are
+// returned as responses, only transport failures are errors.
+pub trait HttpClient: Send + Sync {
+    fn get(&self, url: &str, timeout: Duration) -> Result<HttpResponse>;
+    fn post_json(&self, url: &str, body: &str, timeout: Duration) -> Result<HttpResponse>;
+}
+
+// Default transport (ureq doesn't require OpenSSL)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UreqClient;
+
+impl UreqClient {
+    fn into_response(result: std::result::Result<ureq::Response, ureq::Error>) -> Result<HttpResponse> {
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(anyhow!("HTTP request failed: {}", e)),
+        };
+        let status = response.status();
+        let body = response.into_string()?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+impl HttpClient for UreqClient {
+    fn get(&self, url: &str, timeout: Duration) -> Result<HttpResponse> {
+        Self::into_response(ureq::get(url).timeout(timeout).call())
+    }
+
+    fn post_json(&self, url: &str, body: &str, timeout: Duration) -> Result<HttpResponse> {
+        Self::into_response(
+            ureq::post(url)
+                .timeout(timeout)
+                .set("Content-Type", "application/json")
+                .send_string(body),
+        )
+    }
+}
+
+// Base URLs for every external service, without trailing slashes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+    pub jupiter_api: String,
+    pub jupiter_price_api: String,
+    pub pump_frontend_api: String,
+    pub helius_rpc: String,
+    pub jito_block_engine: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            // FIX: Use correct Jupiter v4 API endpoint
+            jupiter_api: "https://quote-api.jup.ag/v4".to_string(),
+            jupiter_price_api: "https://price.jup.ag/v4".to_string(),
+            pump_frontend_api: "https://frontend-api.pump.fun".to_string(),
+            helius_rpc: "https://mainnet.helius-rpc.com".to_string(),
+            jito_block_engine: "https://mainnet.block-engine.jito.wtf".to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    // Every service on one host, e.g. a local mock server on http://127.0.0.1:8899
+    pub fn all_at(base_url: &str) -> Self {
+        let base = base_url.trim_end_matches('/');
+        Self {
+            jupiter_api: format!("{}/jupiter", base),
+            jupiter_price_api: format!("{}/jupiter-price", base),
+            pump_frontend_api: format!("{}/pump", base),
+            helius_rpc: base.to_string(),
+            jito_block_engine: format!("{}/jito", base),
+        }
+    }
+
+    pub fn rpc_url(&self, helius_api_key: &str) -> String {
+        format!("{}/?api-key={}", self.helius_rpc.trim_end_matches('/'), helius_api_key)
+    }
+
+    // Websocket endpoint on the same host as the RPC, for account subscriptions
+    pub fn ws_url(&self, helius_api_key: &str) -> String {
+        let rpc = self.rpc_url(helius_api_key);
+        match rpc.split_once("://") {
+            Some(("https", rest)) => format!("wss://{}", rest),
+            Some(("http", rest)) => format!("ws://{}", rest),
+            _ => rpc,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// src/jito.rs - Jito block-engine bundles
+// A trade is sent as a bundle with a tip transfer after it, so the tip is only
+// paid if the trade lands in the same block
+
+use serde_json::{json, Value};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_system_interface::instruction as system_instruction;
+use anyhow::{anyhow, Result};
+
+// Jito's published tip accounts; spreading tips across them avoids write-lock contention
+pub const TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+// The block engine rejects bundles tipping less than this
+pub const MIN_TIP_LAMPORTS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleStatus {
+    // Not seen yet or still being auctioned
+    Pending,
+    Landed { slot: u64 },
+    Failed,
+    // Unknown to the block engine: not registered yet, or dropped
+    Invalid,
+}
+
+// Pick a tip account from the trade's signature, so concurrent trades spread out
+pub fn tip_account(trade_signature: &Signature) -> Pubkey {
+    TIP_ACCOUNTS[trade_signature.as_ref()[0] as usize % TIP_ACCOUNTS.len()]
+}
+
+pub fn tip_instruction(payer: &Pubkey, trade_signature: &Signature, tip_lamports: u64) -> Instruction {
+    system_instruction::transfer(payer, &tip_account(trade_signature), tip_lamports)
+}
+
+pub fn bundles_url(block_engine: &str) -> String {
+    format!("{}/api/v1/bundles", block_engine.trim_end_matches('/'))
+}
+
+pub fn send_bundle_request(transactions_b64: &[String]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendBundle",
+        "params": [transactions_b64, { "encoding": "base64" }],
+    })
+}
+
+pub fn bundle_status_request(bundle_id: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getInflightBundleStatuses",
+        "params": [[bundle_id]],
+    })
+}
+
+pub fn parse_bundle_id(response: &Value) -> Result<String> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("Block engine rejected bundle: {}", error));
+    }
+    response["result"].as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("No bundle id in block engine response: {}", response))
+}
+
+pub fn parse_bundle_status(response: &Value) -> Result<BundleStatus> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("Bundle status request failed: {}", error));
+    }
+    let status = &response["result"]["value"][0];
+    match status["status"].as_str() {
+        Some("Landed") => Ok(BundleStatus::Landed { slot: status["landed_slot"].as_u64().unwrap_or(0) }),
+        Some("Failed") => Ok(BundleStatus::Failed),
+        Some("Invalid") => Ok(BundleStatus::Invalid),
+        Some("Pending") | None => Ok(BundleStatus::Pending),
+        Some(other) => Err(anyhow!("Unknown bundle status '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tips_go_to_a_tip_account_chosen_by_signature() {
+        let payer = Pubkey::new_unique();
+        let signature = Signature::from([7; 64]);
+
+        let instruction = tip_instruction(&payer, &signature, 25_000);
+        assert_eq!(instruction.program_id, solana_system_interface::program::id());
+        assert_eq!(instruction.accounts[0].pubkey, payer);
+        assert_eq!(instruction.accounts[1].pubkey, TIP_ACCOUNTS[7]);
+        // System transfer: u32 tag 2 then the lamports
+        assert_eq!(instruction.data[..4], 2u32.to_le_bytes());
+        assert_eq!(instruction.data[4..], 25_000u64.to_le_bytes());
+
+        assert_eq!(tip_account(&Signature::from([8; 64])), TIP_ACCOUNTS[0]);
+    }
+
+    #[test]
+    fn bundle_requests_and_ids() {
+        assert_eq!(bundles_url("https://mainnet.block-engine.jito.wtf/"), "https://mainnet.block-engine.jito.wtf/api/v1/bundles");
+
+        let request = send_bundle_request(&["trade".to_string(), "tip".to_string()]);
+        assert_eq!(request["method"], "sendBundle");
+        assert_eq!(request["params"][0], json!(["trade", "tip"]));
+        assert_eq!(request["params"][1]["encoding"], "base64");
+        assert_eq!(bundle_status_request("abc")["params"], json!([["abc"]]));
+
+        assert_eq!(parse_bundle_id(&json!({ "jsonrpc": "2.0", "result": "abc", "id": 1 })).unwrap(), "abc");
+        assert!(parse_bundle_id(&json!({ "error": { "code": -32602, "message": "bundle contains an expired blockhash" } })).is_err());
+        assert!(parse_bundle_id(&json!({ "result": null })).is_err());
+    }
+
+    #[test]
+    fn bundle_statuses() {
+        let status = |value: Value| json!({ "result": { "context": { "slot": 1 }, "value": [value] } });
+
+        assert_eq!(
+            parse_bundle_status(&status(json!({ "bundle_id": "abc", "status": "Landed", "landed_slot": 42 }))).unwrap(),
+            BundleStatus::Landed { slot: 42 }
+        );
+        assert_eq!(parse_bundle_status(&status(json!({ "status": "Pending" }))).unwrap(), BundleStatus::Pending);
+        assert_eq!(parse_bundle_status(&status(json!({ "status": "Failed" }))).unwrap(), BundleStatus::Failed);
+        assert_eq!(parse_bundle_status(&status(json!({ "status": "Invalid" }))).unwrap(), BundleStatus::Invalid);
+        // Not reported yet
+        assert_eq!(parse_bundle_status(&json!({ "result": { "value": [] } })).unwrap(), BundleStatus::Pending);
+        assert!(parse_bundle_status(&status(json!({ "status": "Exploded" }))).is_err());
+        assert!(parse_bundle_status(&json!({ "error": { "message": "rate limited" } })).is_err());
+    }
+}
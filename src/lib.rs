@@ -2,92 +2,772 @@
 // Ultra-fast trading with ATH pullback strategies - ALL ISSUES FIXED
 
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_account_decoder::UiAccountEncoding;
+use solana_transaction_status::UiTransactionEncoding;
 use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
     signature::{Keypair, Signature, Signer},
     pubkey::Pubkey,
     commitment_config::CommitmentConfig,
-    transaction::Transaction,
+    hash::Hash,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
     native_token::LAMPORTS_PER_SOL,
 };
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_system_interface::instruction as system_instruction;
 use base64::Engine;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 use serde_json::{json, Value};
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use chrono::{DateTime, Utc};
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "control-api")]
+pub mod api;
+pub mod backtest;
+pub mod config;
+pub mod daemon;
+pub mod fill;
+pub mod http;
+pub mod jito;
+pub mod mint;
+pub mod paper;
+pub mod price_stream;
+pub mod pumpfun;
+pub mod raydium;
+pub mod simulation;
+pub mod state;
+pub mod strategy;
+pub mod trade_journal;
+
+use fill::Fill;
+use config::{FeeConfig, JitoConfig, TradeLimits, TraderConfig, TransactionConfig};
+use http::{Endpoints, HttpClient, HttpResponse, UreqClient};
+use jito::BundleStatus;
+use mint::MintInfo;
+use paper::PaperAccount;
+use price_stream::{PriceFeed, StreamSettings, StreamedPrice, VaultBalances};
+use pumpfun::{BondingCurve, CurveQuote, Global};
+use raydium::{AmmV4Pool, CpmmPool, MarketAccounts, RaydiumPool, SwapQuote};
+use simulation::SimulationError;
+use state::{JournalEntry, PersistedState, StateStore};
+use trade_journal::{ClosedTrade, TradeJournal, TradeStats};
+use strategy::{
+    AthPullback, ExitDecision, ExitReason, ExitRule, ExitStrategy, ParameterizedExit, PriceTick, TakeProfitStopLoss,
+};
 
 // Main trading bot structure
 pub struct FastMemeTrader {
     pub rpc_client: RpcClient,
     pub keypair: Keypair,
     pub helius_api_key: String,
-    jupiter_endpoint: String,
-    max_priority_fee: u64,
+    endpoints: Endpoints,
+    http: Arc<dyn HttpClient>,
+    fees: FeeConfig,
+    limits: TradeLimits,
+    transactions: TransactionConfig,
+    jito: JitoConfig,
     
     // Strategy tracking
     pub positions: Arc<RwLock<HashMap<String, Position>>>,
     ath_tracker: Arc<RwLock<HashMap<String, ATHTracker>>>,
+    
+    // Durable copy of positions and trackers
+    state_store: StateStore,
+    trade_journal: TradeJournal,
+    
+    // User-registered exit strategies by name
+    exit_strategies: Arc<RwLock<HashMap<String, Arc<dyn ExitStrategy>>>>,
+    
+    // Set in paper mode: fills are simulated against this virtual wallet
+    paper: Option<PaperAccount>,
+    
+    // SOL/USD rate for converting streamed SOL-denominated prices
+    sol_usd_cache: RwLock<Option<(Decimal, Instant)>>,
+    
+    // Deepest SOL pool found per token; pool accounts never move
+    raydium_pools: RwLock<HashMap<String, RaydiumPool>>,
+    
+    // Decoded mints by address; the owning program and decimals are fixed at creation
+    mints: RwLock<HashMap<String, MintInfo>>,
+    
+    // One lock per token with a sell in flight or waiting
+    sell_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+// How long a fetched SOL/USD rate is reused for streamed ticks
+const SOL_USD_CACHE_TTL: Duration = Duration::from_secs(30);
+
+// Reading a fill right after confirmation; getTransaction needs confirmed commitment
+const FILL_FETCH_ATTEMPTS: u32 = 5;
+const FILL_FETCH_DELAY: Duration = Duration::from_millis(400);
+
+// Compute unit limit for a PumpFun trade, including ATA creation. Replaced by the
+// simulated usage when pre-flight simulation is on.
+const PUMPFUN_COMPUTE_UNITS: u32 = 150_000;
+
+// Compute unit limit for a Raydium swap, including SOL wrapping and ATA creation.
+// Also replaced by the simulated usage.
+const RAYDIUM_COMPUTE_UNITS: u32 = 250_000;
+
+// How buys and sells are executed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecutionMode {
+    Live,
+    // Simulated fills from real quotes, starting from this virtual SOL balance
+    Paper { starting_sol: f64 },
+}
+
+// Everything besides the wallet: where state lives, how trades execute and which hosts are called
+#[derive(Clone)]
+pub struct TraderOptions {
+    pub state_dir: PathBuf,
+    pub mode: ExecutionMode,
+    pub endpoints: Endpoints,
+    pub http_client: Arc<dyn HttpClient>,
+    pub fees: FeeConfig,
+    pub limits: TradeLimits,
+    pub transactions: TransactionConfig,
+    pub jito: JitoConfig,
+}
+
+impl Default for TraderOptions {
+    fn default() -> Self {
+        Self {
+            state_dir: StateStore::default_dir(),
+            mode: ExecutionMode::Live,
+            endpoints: Endpoints::default(),
+            http_client: Arc::new(UreqClient),
+            fees: FeeConfig::default(),
+            limits: TradeLimits::default(),
+            transactions: TransactionConfig::default(),
+            jito: JitoConfig::default(),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub token_address: String,
-    pub entry_price: Decimal,
+    // USD per whole token paid on entry; None when the fill could not be priced
+    #[serde(deserialize_with = "deserialize_entry_price")]
+    pub entry_price: Option<Decimal>,
     pub amount_tokens: u64,
     pub entry_time: DateTime<Utc>,
     pub strategy: StrategyType,
     pub buy_signature: String,
+    #[serde(default)]
+    pub sol_in: f64,
+    // SOL and network fees from partial exits so far
+    #[serde(default)]
+    pub realized_sol_out: f64,
+    #[serde(default)]
+    pub realized_fee_lamports: u64,
+    // Jito tips paid by the buy and any partial exits; they are separate transfers, so
+    // the fills never include them
+    #[serde(default)]
+    pub realized_tip_lamports: u64,
+    // Laddered take-profits: amount_tokens is what remains after completed tiers
+    #[serde(default)]
+    pub initial_amount_tokens: u64,
+    #[serde(default)]
+    pub take_profit_ladder: Vec<TakeProfitTier>,
+    #[serde(default)]
+    pub completed_tiers: Vec<usize>,
+    // Rules whose partial exit (ExitDecision::SellFraction) has already fired
+    #[serde(default)]
+    pub fired_exits: Vec<ExitRule>,
+    #[serde(default)]
+    pub time_exits: TimeExitRules,
+    // On-chain account the price is streamed from; None is polled only
+    #[serde(default)]
+    pub price_feed: Option<PriceFeed>,
+}
+
+// Time-based exits, measured from Position::entry_time. Unset rules are not evaluated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeExitRules {
+    // Sell unconditionally after holding this long
+    pub max_hold_minutes: Option<i64>,
+    // Sell if the position has not been up min_gain_percent by the end of the window
+    pub min_gain_window_minutes: Option<i64>,
+    pub min_gain_percent: Option<Decimal>,
+    // Tighten the ATH pullback trigger by this many points per hour held, down to the floor
+    pub pullback_decay_per_hour: Option<Decimal>,
+    pub min_pullback_percent: Option<Decimal>,
+}
+
+impl TimeExitRules {
+    pub fn is_empty(&self) -> bool {
+        *self == TimeExitRules::default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(minutes) = self.max_hold_minutes {
+            if minutes <= 0 {
+                return Err(anyhow!("max hold must be positive, got {} minutes", minutes));
+            }
+        }
+        
+        match (self.min_gain_window_minutes, self.min_gain_percent) {
+            (Some(minutes), Some(_)) if minutes <= 0 => {
+                return Err(anyhow!("min gain window must be positive, got {} minutes", minutes));
+            },
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(anyhow!("min gain window and min gain percent must be set together"));
+            },
+            _ => {}
+        }
+        
+        if let Some(decay) = self.pullback_decay_per_hour {
+            if decay <= Decimal::ZERO {
+                return Err(anyhow!("pullback decay must be positive, got {}% per hour", decay));
+            }
+        }
+        if let Some(floor) = self.min_pullback_percent {
+            if floor <= Decimal::ZERO || floor >= Decimal::from(100) {
+                return Err(anyhow!("min pullback must be between 0% and 100%, got {}%", floor));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+// Sell sell_percent of the initial position once profit reaches profit_percent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TakeProfitTier {
+    pub profit_percent: Decimal,
+    pub sell_percent: Decimal,
+}
+
+impl TakeProfitTier {
+    // Parse "30:25,60:25" as +30% sell 25%, +60% sell 25%
+    pub fn parse_ladder(input: &str) -> Result<Vec<TakeProfitTier>> {
+        let mut ladder = Vec::new();
+        
+        for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (profit, sell) = part.split_once(':')
+                .ok_or_else(|| anyhow!("Invalid tier '{}', expected profit%:sell%", part))?;
+            ladder.push(TakeProfitTier {
+                profit_percent: Decimal::from_str(profit.trim().trim_end_matches('%'))
+                    .map_err(|_| anyhow!("Invalid profit percent in tier '{}'", part))?,
+                sell_percent: Decimal::from_str(sell.trim().trim_end_matches('%'))
+                    .map_err(|_| anyhow!("Invalid sell percent in tier '{}'", part))?,
+            });
+        }
+        
+        Self::validate_ladder(&ladder)?;
+        Ok(ladder)
+    }
+
+    pub fn validate_ladder(ladder: &[TakeProfitTier]) -> Result<()> {
+        let mut total_sell = Decimal::ZERO;
+        for tier in ladder {
+            if tier.profit_percent <= Decimal::ZERO {
+                return Err(anyhow!("Tier profit must be positive, got {}%", tier.profit_percent));
+            }
+            if tier.sell_percent <= Decimal::ZERO || tier.sell_percent > Decimal::from(100) {
+                return Err(anyhow!("Tier sell size must be between 0% and 100%, got {}%", tier.sell_percent));
+            }
+            total_sell += tier.sell_percent;
+        }
+        if total_sell > Decimal::from(100) {
+            return Err(anyhow!("Ladder sells {}% of the position, more than 100%", total_sell));
+        }
+        Ok(())
+    }
+}
+
+impl Position {
+    // Positions restored from before ladders existed have no initial amount recorded
+    pub fn initial_amount(&self) -> u64 {
+        if self.initial_amount_tokens > 0 {
+            self.initial_amount_tokens
+        } else {
+            self.amount_tokens
+        }
+    }
+
+    // Tokens to sell for a ladder tier, capped at what is left
+    pub fn tier_sell_amount(&self, tier_index: usize) -> u64 {
+        let tier = match self.take_profit_ladder.get(tier_index) {
+            Some(tier) => tier,
+            None => return 0,
+        };
+        let amount = (Decimal::from(self.initial_amount()) * tier.sell_percent / Decimal::from(100))
+            .floor()
+            .to_u64()
+            .unwrap_or(0);
+        amount.min(self.amount_tokens)
+    }
+
+    // P&L at the given price, None while the position is unpriced
+    pub fn profit_percent(&self, price: Decimal) -> Option<Decimal> {
+        self.entry_price.map(|entry_price| strategy::profit_percent(entry_price, price))
+    }
+}
+
+// State written before unpriced positions existed stored a missing entry price as 0
+fn deserialize_entry_price<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+    Ok(Option::<Decimal>::deserialize(deserializer)?.filter(|price| !price.is_zero()))
+}
+
+// First eight characters of an address or key for logs and prompts; never panics on
+// short or non-ASCII input
+pub fn short(s: &str) -> &str {
+    s.char_indices().nth(8).map_or(s, |(end, _)| &s[..end])
+}
+
+// "$0.00001234", or "unpriced" when the entry could not be determined
+pub fn format_entry_price(entry_price: Option<Decimal>) -> String {
+    match entry_price {
+        Some(price) => format!("${:.8}", price),
+        None => "unpriced".to_string(),
+    }
+}
+
+// Add or remove an item, returning false when the list already had it that way
+fn set_membership<T: PartialEq>(items: &mut Vec<T>, item: T, present: bool) -> bool {
+    if items.contains(&item) == present {
+        return false;
+    }
+    if present {
+        items.push(item);
+    } else {
+        items.retain(|existing| *existing != item);
+    }
+    true
+}
+
+fn format_profit_percent(profit_percent: Option<Decimal>) -> String {
+    match profit_percent {
+        Some(percent) => format!("{:.2}%", percent),
+        None => "n/a".to_string(),
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ATHTracker {
-    pub entry_price: Decimal,
+    #[serde(deserialize_with = "deserialize_entry_price")]
+    pub entry_price: Option<Decimal>,
     pub ath_price: Decimal,
     pub last_price: Decimal,
     pub pullback_percent: Decimal,
     pub min_profit_percent: Decimal,
     pub last_updated: DateTime<Utc>,
+    // Hard stop: exit once price is this far below entry, whether or not it was ever green
+    #[serde(default)]
+    pub max_drawdown_percent: Option<Decimal>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum StrategyType {
     Conservative,           // 15% profit, 5% stop loss
     Aggressive,            // 50% profit, 15% stop loss  
-    ConservativeATH,       // 8% pullback, 3% min profit
-    AggressiveATH,         // 12% pullback, 5% min profit
+    #[default]
+    ConservativeATH,       // 8% pullback, 3% min profit, 20% max drawdown
+    AggressiveATH,         // 12% pullback, 5% min profit, 30% max drawdown
+    Custom(StrategyParams), // User-defined thresholds
+    Registered(String),     // ExitStrategy registered on the trader by name
+}
+
+// Exit thresholds in percent. Unset rules are not evaluated.
+// stop_loss_percent is a positive number: 5 means exit at -5%.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategyParams {
+    pub take_profit_percent: Option<Decimal>,
+    pub stop_loss_percent: Option<Decimal>,
+    pub pullback_percent: Option<Decimal>,
+    pub min_profit_percent: Option<Decimal>,
+    // Max drawdown from entry before the pullback rule force-exits
+    #[serde(default)]
+    pub max_drawdown_percent: Option<Decimal>,
+}
+
+impl StrategyType {
+    // Thresholds behind each strategy; presets map onto the same parameters as Custom
+    pub fn params(&self) -> StrategyParams {
+        match self {
+            StrategyType::Conservative => StrategyParams {
+                take_profit_percent: Some(Decimal::from(15)),
+                stop_loss_percent: Some(Decimal::from(5)),
+                ..StrategyParams::default()
+            },
+            StrategyType::Aggressive => StrategyParams {
+                take_profit_percent: Some(Decimal::from(50)),
+                stop_loss_percent: Some(Decimal::from(15)),
+                ..StrategyParams::default()
+            },
+            StrategyType::ConservativeATH => StrategyParams {
+                pullback_percent: Some(Decimal::from(8)),
+                min_profit_percent: Some(Decimal::from(3)),
+                max_drawdown_percent: Some(Decimal::from(20)),
+                ..StrategyParams::default()
+            },
+            StrategyType::AggressiveATH => StrategyParams {
+                pullback_percent: Some(Decimal::from(12)),
+                min_profit_percent: Some(Decimal::from(5)),
+                max_drawdown_percent: Some(Decimal::from(30)),
+                ..StrategyParams::default()
+            },
+            StrategyType::Custom(params) => *params,
+            StrategyType::Registered(_) => StrategyParams::default(),
+        }
+    }
+
+    // Built-in ExitStrategy for this type; Registered strategies live on the trader
+    pub fn exit_strategy(&self) -> Option<Arc<dyn ExitStrategy>> {
+        // Thresholds come from params() so the two can't drift apart
+        let take_profit_stop_loss = |name: &'static str| -> Option<Arc<dyn ExitStrategy>> {
+            let params = self.params();
+            Some(Arc::new(TakeProfitStopLoss::new(name, params.take_profit_percent?, params.stop_loss_percent?)))
+        };
+        match self {
+            StrategyType::Conservative => take_profit_stop_loss("Conservative"),
+            StrategyType::Aggressive => take_profit_stop_loss("Aggressive"),
+            StrategyType::ConservativeATH => Some(Arc::new(AthPullback::new("ConservativeATH"))),
+            StrategyType::AggressiveATH => Some(Arc::new(AthPullback::new("AggressiveATH"))),
+            StrategyType::Custom(params) => Some(Arc::new(ParameterizedExit { params: *params })),
+            StrategyType::Registered(_) => None,
+        }
+    }
+}
+
+impl ATHTracker {
+    // Fresh tracker at entry; strategies without a pullback rule still track ATH for status display.
+    // An unpriced tracker starts from zero and takes its ATH from the first tick.
+    pub fn new(entry_price: Option<Decimal>, params: &StrategyParams) -> Self {
+        let start_price = entry_price.unwrap_or(Decimal::ZERO);
+        Self {
+            entry_price,
+            ath_price: start_price,
+            last_price: start_price,
+            pullback_percent: params.pullback_percent.unwrap_or(Decimal::from(10)),
+            min_profit_percent: params.min_profit_percent.unwrap_or(Decimal::ZERO),
+            last_updated: Utc::now(),
+            max_drawdown_percent: params.max_drawdown_percent,
+        }
+    }
+
+    // Record a price tick, returns true on a new ATH
+    pub fn update(&mut self, tick: &PriceTick) -> bool {
+        let new_ath = tick.price > self.ath_price;
+        if new_ath {
+            self.ath_price = tick.price;
+        }
+        self.last_price = tick.price;
+        self.last_updated = tick.timestamp;
+        new_ath
+    }
+}
+
+// Parse "conservative", "aggressive-ath", "custom:tp=30,sl=10,pb=8,min=3,dd=20"
+// or "registered:<name>"
+impl FromStr for StrategyType {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let normalized = input.trim().to_lowercase().replace('_', "-");
+        
+        match normalized.as_str() {
+            "conservative" => return Ok(StrategyType::Conservative),
+            "aggressive" => return Ok(StrategyType::Aggressive),
+            "conservative-ath" | "conservativeath" => return Ok(StrategyType::ConservativeATH),
+            "aggressive-ath" | "aggressiveath" => return Ok(StrategyType::AggressiveATH),
+            _ => {}
+        }
+        
+        if let Some(name) = input.trim().strip_prefix("registered:") {
+            return Ok(StrategyType::Registered(name.to_string()));
+        }
+        
+        let rules = normalized.strip_prefix("custom:")
+            .ok_or_else(|| anyhow!(
+                "Unknown strategy '{}'. Use conservative, aggressive, conservative-ath, aggressive-ath, custom:tp=..,sl=..,pb=..,min=..,dd=.. or registered:<name>",
+                input
+            ))?;
+        
+        let mut params = StrategyParams::default();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let (key, value) = rule.split_once('=')
+                .ok_or_else(|| anyhow!("Invalid custom strategy rule '{}', expected key=value", rule))?;
+            let value = Decimal::from_str(value.trim().trim_end_matches('%'))
+                .map_err(|_| anyhow!("Invalid number in custom strategy rule '{}'", rule))?;
+            let field = match key.trim() {
+                "tp" => &mut params.take_profit_percent,
+                "sl" => &mut params.stop_loss_percent,
+                "pb" => &mut params.pullback_percent,
+                "min" => &mut params.min_profit_percent,
+                "dd" => &mut params.max_drawdown_percent,
+                other => return Err(anyhow!("Unknown custom strategy rule '{}' (use tp, sl, pb, min, dd)", other)),
+            };
+            if field.replace(value).is_some() {
+                return Err(anyhow!("Custom strategy rule '{}' is set more than once", key.trim()));
+            }
+        }
+        
+        params.validate()?;
+        Ok(StrategyType::Custom(params))
+    }
+}
+
+impl StrategyParams {
+    pub fn validate(&self) -> Result<()> {
+        let checks = [
+            ("take profit", self.take_profit_percent),
+            ("stop loss", self.stop_loss_percent),
+            ("pullback", self.pullback_percent),
+            ("min profit", self.min_profit_percent),
+            ("max drawdown", self.max_drawdown_percent),
+        ];
+        for (name, value) in checks {
+            if let Some(value) = value {
+                if value < Decimal::ZERO {
+                    return Err(anyhow!("{} must not be negative, got {}%", name, value));
+                }
+            }
+        }
+        
+        if let Some(stop_loss) = self.stop_loss_percent {
+            if stop_loss >= Decimal::from(100) {
+                return Err(anyhow!("stop loss must be below 100%, got {}%", stop_loss));
+            }
+        }
+        if let Some(pullback) = self.pullback_percent {
+            if pullback == Decimal::ZERO || pullback >= Decimal::from(100) {
+                return Err(anyhow!("pullback must be between 0% and 100%, got {}%", pullback));
+            }
+        }
+        if let Some(max_drawdown) = self.max_drawdown_percent {
+            if max_drawdown == Decimal::ZERO || max_drawdown >= Decimal::from(100) {
+                return Err(anyhow!("max drawdown must be between 0% and 100%, got {}%", max_drawdown));
+            }
+            if self.pullback_percent.is_none() {
+                return Err(anyhow!("max drawdown applies to pullback strategies, set a pullback too"));
+            }
+        }
+        
+        if self.take_profit_percent.is_none() && self.stop_loss_percent.is_none() && self.pullback_percent.is_none() {
+            return Err(anyhow!("strategy needs at least one of take profit, stop loss or pullback"));
+        }
+        
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TradeConfig {
     pub token_address: String,
     pub amount_sol: f64,
     pub slippage_bps: u16,
     pub strategy: StrategyType,
+    // Optional partial take-profits, fired before the strategy trails the rest
+    pub take_profit_ladder: Vec<TakeProfitTier>,
+    pub time_exits: TimeExitRules,
+    // Jito tip for this trade: None uses the configured default, Some(0) sends through plain RPC
+    pub tip_lamports: Option<u64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TradeResult {
     pub signature: String,
     pub success: bool,
     pub error: Option<String>,
     pub execution_time_ms: u64,
-    pub platform_used: Platform,
+    // Venue the trade went through or was attempted on; None if it failed before reaching one
+    pub platform_used: Option<Platform>,
     pub tokens_received: Option<u64>,
     pub sol_spent: Option<f64>,
+    pub sol_received: Option<f64>,
+    // Tip paid when the trade landed through a Jito bundle
+    pub tip_lamports: Option<u64>,
+    // Network fee read from the confirmed transaction
+    pub fee_lamports: Option<u64>,
+    // Realized shortfall of the fill against the quote; negative when the fill beat it
+    pub slippage_percent: Option<Decimal>,
+}
+
+impl TradeResult {
+    // A trade that did not happen, timed from when it started
+    pub fn failure(platform: Option<Platform>, error: impl Into<String>, started: Instant) -> Self {
+        Self {
+            signature: String::new(),
+            success: false,
+            error: Some(error.into()),
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            platform_used: platform,
+            tokens_received: None,
+            sol_spent: None,
+            sol_received: None,
+            tip_lamports: None,
+            fee_lamports: None,
+            slippage_percent: None,
+        }
+    }
+}
+
+// A sell that did not execute, with the venue it was attempted on if it got that far
+struct SellFailure {
+    platform: Option<Platform>,
+    error: anyhow::Error,
+}
+
+impl SellFailure {
+    fn on(platform: Platform) -> impl FnOnce(anyhow::Error) -> Self {
+        move |error| Self { platform: Some(platform), error }
+    }
+}
+
+// A swap as executed: its quoted output and how it was sent
+struct Execution {
+    signature: String,
+    // Quoted tokens out for a buy, lamports out for a sell
+    amount_out: u64,
+    platform: Platform,
+    tip_lamports: Option<u64>,
+}
+
+// A landed transaction and the Jito tip paid for it, if it went through a bundle
+struct Submission {
+    signature: Signature,
+    tip_lamports: Option<u64>,
+}
+
+// Next step for a sent transaction that has not been confirmed yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SendAction {
+    Landed,
+    FailedOnChain(TransactionError),
+    // Send the same signed transaction again; it can only ever execute once
+    Resend,
+    // Sign again with a fresh blockhash, which creates a second executable transaction
+    Resign,
+}
+
+// Re-signing is only safe once the original can no longer land: its blockhash has
+// expired and its signature was not processed. A failed status or blockhash lookup
+// (None) is not proof of either, so the original is resent instead.
+fn next_send_action(status: Option<Option<Result<(), TransactionError>>>, blockhash_valid: Option<bool>) -> SendAction {
+    match (status, blockhash_valid) {
+        (Some(Some(Ok(()))), _) => SendAction::Landed,
+        (Some(Some(Err(e))), _) => SendAction::FailedOnChain(e),
+        (Some(None), Some(false)) => SendAction::Resign,
+        _ => SendAction::Resend,
+    }
+}
+
+// Outcome of an automated or emergency sell, with the rule that triggered it
+#[derive(Debug, Clone, Serialize)]
+pub struct SellEvent {
+    pub token_address: String,
+    pub strategy: StrategyType,
+    pub reason: ExitReason,
+    pub trade: TradeResult,
+}
+
+impl SellEvent {
+    // started is when the sell began, so a sell that errors out is still timed
+    fn new(position: &Position, reason: ExitReason, sell_result: Result<TradeResult>, started: Instant) -> Self {
+        let trade = sell_result.unwrap_or_else(|e| TradeResult::failure(None, e.to_string(), started));
+        
+        if !trade.success {
+            log::error!(
+                "Exit sell failed for {} ({}): {}",
+                short(&position.token_address), reason, trade.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+        
+        Self {
+            token_address: position.token_address.clone(),
+            strategy: position.strategy.clone(),
+            reason,
+            trade,
+        }
+    }
+}
+
+impl std::fmt::Display for SellEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.trade.success {
+            write!(
+                f,
+                "Sold {} - Reason: {} - Signature: {} - Strategy: {:?} - Time: {}ms",
+                short(&self.token_address), self.reason, self.trade.signature,
+                self.strategy, self.trade.execution_time_ms
+            )
+        } else {
+            write!(
+                f,
+                "Sell FAILED {} - Reason: {} - Error: {}",
+                short(&self.token_address), self.reason,
+                self.trade.error.as_deref().unwrap_or("unknown error")
+            )
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Platform {
     PumpFun,
     Raydium,
     Jupiter,
 }
 
+// Open position with its latest price, for status displays and --json output
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionStatus {
+    pub position: Position,
+    // amount_tokens in whole tokens, None if the mint could not be read
+    pub amount_ui: Option<Decimal>,
+    pub current_price: Option<Decimal>,
+    pub profit_percent: Option<Decimal>,
+    pub tracker: Option<ATHTracker>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub paper: bool,
+    pub sol_balance: f64,
+    pub positions: usize,
+    pub jupiter_ok: bool,
+    pub rpc_ok: bool,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.jupiter_ok && self.rpc_ok
+    }
+}
+
+impl std::fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Health: {}SOL Balance: {:.6} | Positions: {} | Jupiter: {} | RPC: {}",
+            if self.paper { "[PAPER] " } else { "" },
+            self.sol_balance,
+            self.positions,
+            if self.jupiter_ok { "✅" } else { "❌" },
+            if self.rpc_ok { "✅" } else { "❌" }
+        )
+    }
+}
+
 // Known token addresses for common pairs
 pub mod token_addresses {
     pub const SOL: &str = "So11111111111111111111111111111111111111112";
@@ -100,33 +780,121 @@ pub mod token_addresses {
 impl FastMemeTrader {
     // Ultra-fast initialization with better key parsing
     pub fn new(private_key: &str, helius_api_key: String) -> Result<Self> {
-        log::info!("Initializing FastMemeTrader...");
+        Self::new_with_state_dir(private_key, helius_api_key, StateStore::default_dir())
+    }
+
+    // Same as new, but positions are persisted to and restored from state_dir
+    pub fn new_with_state_dir(private_key: &str, helius_api_key: String, state_dir: impl AsRef<Path>) -> Result<Self> {
+        Self::new_with_options(private_key, helius_api_key, TraderOptions {
+            state_dir: state_dir.as_ref().to_path_buf(),
+            ..TraderOptions::default()
+        })
+    }
+
+    // Paper trading: real prices and quotes, simulated fills. Positions, trades and the
+    // virtual wallet live under <state dir>/paper so they never mix with live state.
+    pub fn new_paper(private_key: &str, helius_api_key: String, starting_sol: f64) -> Result<Self> {
+        Self::new_with_options(private_key, helius_api_key, TraderOptions {
+            state_dir: StateStore::default_dir().join("paper"),
+            mode: ExecutionMode::Paper { starting_sol },
+            ..TraderOptions::default()
+        })
+    }
+
+    // Build from a validated TraderConfig, e.g. TraderConfig::load()
+    pub fn from_config(private_key: &str, helius_api_key: String, config: &TraderConfig) -> Result<Self> {
+        config.validate()?;
+        Self::new_with_options(private_key, helius_api_key, config.to_options())
+    }
+
+    pub fn new_with_options(private_key: &str, helius_api_key: String, options: TraderOptions) -> Result<Self> {
+        let TraderOptions { state_dir, mode, endpoints, http_client, fees, limits, transactions, jito } = options;
+        log::info!("Initializing FastMemeTrader ({:?})...", mode);
         
         // Better keypair parsing with multiple format support
         let keypair = Self::parse_private_key(private_key)?;
         log::info!("Wallet: {}", keypair.pubkey());
         
-        let rpc_url = format!("https://mainnet.helius-rpc.com/?api-key={}", helius_api_key);
+        if endpoints != Endpoints::default() {
+            log::info!("Using custom endpoints: {:?}", endpoints);
+        }
         let rpc_client = RpcClient::new_with_commitment(
-            rpc_url,
-            CommitmentConfig::processed(),
+            endpoints.rpc_url(&helius_api_key),
+            transactions.commitment.to_commitment_config(),
         );
+        
+        // Restore positions and ATH trackers from the last run
+        let (state_store, persisted) = StateStore::open(state_dir)?;
+        let trade_journal = TradeJournal::open(state_store.dir())?;
+        if !persisted.positions.is_empty() {
+            log::info!("Resuming {} open positions", persisted.positions.len());
+        }
+        
+        let paper = match mode {
+            ExecutionMode::Live => None,
+            ExecutionMode::Paper { starting_sol } => {
+                if starting_sol <= 0.0 {
+                    return Err(anyhow!("Paper starting balance must be positive, got {} SOL", starting_sol));
+                }
+                log::warn!("PAPER TRADING: no transactions will be signed or sent");
+                Some(PaperAccount::open(state_store.dir(), starting_sol)?)
+            },
+        };
 
         let trader = Self {
             rpc_client,
             keypair,
             helius_api_key,
-            // FIX: Use correct Jupiter v4 API endpoint
-            jupiter_endpoint: "https://quote-api.jup.ag/v4".to_string(),
-            max_priority_fee: 200_000,
-            positions: Arc::new(RwLock::new(HashMap::new())),
-            ath_tracker: Arc::new(RwLock::new(HashMap::new())),
+            endpoints,
+            http: http_client,
+            fees,
+            limits,
+            transactions,
+            jito,
+            positions: Arc::new(RwLock::new(persisted.positions)),
+            ath_tracker: Arc::new(RwLock::new(persisted.trackers)),
+            state_store,
+            trade_journal,
+            exit_strategies: Arc::new(RwLock::new(HashMap::new())),
+            paper,
+            sol_usd_cache: RwLock::new(None),
+            raydium_pools: RwLock::new(HashMap::new()),
+            mints: RwLock::new(HashMap::new()),
+            sell_locks: Mutex::new(HashMap::new()),
         };
         
         log::info!("FastMemeTrader initialized successfully");
         Ok(trader)
     }
 
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    pub fn limits(&self) -> TradeLimits {
+        self.limits
+    }
+
+    // Transport calls run on the blocking pool
+    async fn http_get(&self, url: String, timeout: Duration) -> Result<HttpResponse> {
+        let http = self.http.clone();
+        tokio::task::spawn_blocking(move || http.get(&url, timeout)).await?
+    }
+
+    async fn http_post_json(&self, url: String, body: String, timeout: Duration) -> Result<HttpResponse> {
+        let http = self.http.clone();
+        tokio::task::spawn_blocking(move || http.post_json(&url, &body, timeout)).await?
+    }
+
+    pub fn is_paper(&self) -> bool {
+        self.paper.is_some()
+    }
+
+    // Virtual wallet backing paper mode, None when trading live
+    pub fn paper_account(&self) -> Option<&PaperAccount> {
+        self.paper.as_ref()
+    }
+
     // Improved private key parsing with multiple format support
     fn parse_private_key(private_key: &str) -> Result<Keypair> {
         let trimmed_key = private_key.trim();
@@ -155,11 +923,7 @@ impl FastMemeTrader {
         
         // 3. Try as hex string (without 0x prefix)
         if trimmed_key.len() == 128 || (trimmed_key.len() == 130 && trimmed_key.starts_with("0x")) {
-            let hex_str = if trimmed_key.starts_with("0x") {
-                &trimmed_key[2..]
-            } else {
-                trimmed_key
-            };
+            let hex_str = trimmed_key.strip_prefix("0x").unwrap_or(trimmed_key);
             
             if let Ok(bytes) = hex::decode(hex_str) {
                 if bytes.len() == 64 {
@@ -232,12 +996,16 @@ impl FastMemeTrader {
         
         let platform = if is_pumpfun {
             Platform::PumpFun
+        } else if self.get_jupiter_price(token_address).await.is_err() && self.find_raydium_pool(token_address).await.is_ok() {
+            // Freshly migrated tokens trade on Raydium before Jupiter indexes them
+            log::info!("Jupiter has no price for {}, trading its Raydium pool directly", short(token_address));
+            Platform::Raydium
         } else {
             // Default to Jupiter for all other tokens
             Platform::Jupiter
         };
         
-        log::info!("Selected platform: {:?} for token {}", platform, &token_address[..8]);
+        log::info!("Selected platform: {:?} for token {}", platform, short(token_address));
         platform
     }
 
@@ -245,79 +1013,98 @@ impl FastMemeTrader {
     pub async fn buy_fast(&self, config: TradeConfig) -> TradeResult {
         let start_time = Instant::now();
         
-        log::info!("Starting fast buy: {} SOL for {}", config.amount_sol, &config.token_address[..8]);
+        log::info!("Starting fast buy: {} SOL for {}", config.amount_sol, short(&config.token_address));
         
         // Enhanced validation
-        if config.amount_sol < 0.000001 || config.amount_sol > 50.0 {
-            return TradeResult {
-                signature: String::new(),
-                success: false,
-                error: Some("Amount must be between 0.000001 and 50.0 SOL".to_string()),
-                execution_time_ms: start_time.elapsed().as_millis() as u64,
-                platform_used: Platform::Jupiter,
-                tokens_received: None,
-                sol_spent: None,
-            };
+        if !(self.limits.min_trade_sol..=self.limits.max_trade_sol).contains(&config.amount_sol) {
+            let error = format!(
+                "Amount must be between {} and {} SOL", self.limits.min_trade_sol, self.limits.max_trade_sol
+            );
+            return TradeResult::failure(None, error, start_time);
+        }
+        
+        // Validate strategy thresholds
+        let strategy_check = match &config.strategy {
+            StrategyType::Registered(name) => {
+                if self.exit_strategies.read().await.contains_key(name) {
+                    Ok(())
+                } else {
+                    Err(anyhow!("no exit strategy registered as '{}'", name))
+                }
+            },
+            builtin => builtin.params().validate(),
+        }
+        .and_then(|_| TakeProfitTier::validate_ladder(&config.take_profit_ladder))
+        .and_then(|_| config.time_exits.validate())
+        .and_then(|_| match config.tip_lamports {
+            Some(tip) if tip > 0 && tip < jito::MIN_TIP_LAMPORTS => {
+                Err(anyhow!("Jito tip must be 0 or at least {} lamports, got {}", jito::MIN_TIP_LAMPORTS, tip))
+            },
+            _ => Ok(()),
+        });
+        if let Err(e) = strategy_check {
+            return TradeResult::failure(None, format!("Invalid strategy: {}", e), start_time);
         }
         
         // Validate token address
         if let Err(e) = Self::validate_token_address(&config.token_address) {
-            return TradeResult {
-                signature: String::new(),
-                success: false,
-                error: Some(format!("Invalid token address: {}", e)),
-                execution_time_ms: start_time.elapsed().as_millis() as u64,
-                platform_used: Platform::Jupiter,
-                tokens_received: None,
-                sol_spent: None,
-            };
+            return TradeResult::failure(None, format!("Invalid token address: {}", e), start_time);
         }
         
         let platform = self.detect_best_platform(&config.token_address).await;
         
-        let result = match platform {
-            Platform::PumpFun => self.buy_pumpfun(&config).await,
-            Platform::Raydium => self.buy_jupiter(&config).await, // Fall back to Jupiter
-            Platform::Jupiter => self.buy_jupiter(&config).await,
+        let result = match (&self.paper, platform.clone()) {
+            (Some(paper), platform) => self.buy_paper(&config, paper, platform).await,
+            (None, Platform::PumpFun) => self.buy_pumpfun(&config).await,
+            (None, Platform::Raydium) => self.buy_raydium(&config).await,
+            (None, Platform::Jupiter) => self.buy_jupiter(&config).await,
         };
         
         let execution_time = start_time.elapsed().as_millis() as u64;
         
         match result {
-            Ok((signature, tokens_received)) => {
-                log::info!("Buy successful: {} tokens received in {}ms", tokens_received, execution_time);
+            Ok(execution) => {
+                // Record what actually landed; the quote is only a fallback
+                let fill = self.read_fill(&execution, &config.token_address).await;
+                let tokens_received = fill.map(|fill| fill.tokens_received()).unwrap_or(execution.amount_out);
+                let sol_spent = fill
+                    .map(|fill| fill.sol_spent_lamports() as f64 / LAMPORTS_PER_SOL as f64)
+                    .unwrap_or(config.amount_sol);
+                let slippage = fill.and_then(|fill| fill::slippage_percent(execution.amount_out, fill.tokens_received()));
+                log::info!(
+                    "Buy successful: {} tokens (quoted {}) for {} SOL in {}ms",
+                    tokens_received, execution.amount_out, sol_spent, execution_time
+                );
                 
                 // Initialize position and ATH tracking
-                self.initialize_position(&config, &signature, tokens_received).await;
+                let decimals = fill.and_then(|fill| fill.decimals);
+                let price_feed = self.price_feed(&config.token_address, &platform, decimals).await;
+                self.initialize_position(&config, &execution, tokens_received, sol_spent, decimals, price_feed).await;
                 
                 TradeResult {
-                    signature,
+                    signature: execution.signature,
                     success: true,
                     error: None,
                     execution_time_ms: execution_time,
-                    platform_used: platform,
+                    platform_used: Some(execution.platform),
                     tokens_received: Some(tokens_received),
-                    sol_spent: Some(config.amount_sol),
+                    sol_spent: Some(sol_spent),
+                    sol_received: None,
+                    tip_lamports: execution.tip_lamports,
+                    fee_lamports: fill.map(|fill| fill.fee_lamports),
+                    slippage_percent: slippage,
                 }
             },
             Err(e) => {
                 log::error!("Buy failed after {}ms: {}", execution_time, e);
-                TradeResult {
-                    signature: String::new(),
-                    success: false,
-                    error: Some(e.to_string()),
-                    execution_time_ms: execution_time,
-                    platform_used: platform,
-                    tokens_received: None,
-                    sol_spent: None,
-                }
+                TradeResult::failure(Some(platform), e.to_string(), start_time)
             },
         }
     }
 
     // FIXED: Complete Jupiter implementation with proper error handling
-    async fn buy_jupiter(&self, config: &TradeConfig) -> Result<(String, u64)> {
-        log::info!("Executing Jupiter buy for {}", &config.token_address[..8]);
+    async fn buy_jupiter(&self, config: &TradeConfig) -> Result<Execution> {
+        log::info!("Executing Jupiter buy for {}", short(&config.token_address));
         
         // Validate token first
         Self::validate_token_address(&config.token_address)?;
@@ -332,23 +1119,10 @@ impl FastMemeTrader {
         log::info!("Getting Jupiter quote for {} lamports...", amount_lamports);
         let quote = tokio::time::timeout(
             Duration::from_secs(15),
-            self.get_jupiter_quote_with_retry(config, amount_lamports, 3)
+            self.get_jupiter_quote_with_retry(config, amount_lamports, self.transactions.quote_retries)
         ).await??;
         
-        // FIX: Handle both v4 and v6 response formats
-        let tokens_expected = if let Some(out_amount) = quote.get("outAmount") {
-            out_amount.as_str()
-                .ok_or_else(|| anyhow!("outAmount is not a string"))?
-                .parse::<u64>()?
-        } else if let Some(data) = quote.get("data") {
-            // Handle alternative response format
-            data.get("outAmount")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("No outAmount in data"))?
-                .parse::<u64>()?
-        } else {
-            return Err(anyhow!("No outAmount found in quote response"));
-        };
+        let tokens_expected = Self::quote_out_amount(&quote)?;
         
         log::info!("Jupiter quote: {} lamports -> {} tokens", amount_lamports, tokens_expected);
         
@@ -363,32 +1137,86 @@ impl FastMemeTrader {
         });
         
         let swap_data_str = serde_json::to_string(&swap_data)?;
-        let url = format!("{}/swap", self.jupiter_endpoint);
+        let url = format!("{}/swap", self.endpoints.jupiter_api);
         
         log::debug!("Sending swap request to: {}", url);
         
-        let response = tokio::task::spawn_blocking(move || {
-            ureq::post(&url)
-                .timeout(Duration::from_secs(30))
-                .set("Content-Type", "application/json")
-                .send_string(&swap_data_str)
-        }).await??;
+        let response = self.http_post_json(url, swap_data_str, Duration::from_secs(30)).await?;
         
-        if response.status() != 200 {
-            let error_text = response.into_string().unwrap_or_else(|_| "Failed to read error response".to_string());
-            log::error!("Jupiter swap API error: Status {}, Body: {}", response.status(), error_text);
-            return Err(anyhow!("Jupiter swap API error ({}): {}", response.status(), error_text));
+        if !response.is_success() {
+            log::error!("Jupiter swap API error: Status {}, Body: {}", response.status, response.body);
+            return Err(anyhow!("Jupiter swap API error ({}): {}", response.status, response.body));
         }
         
-        let swap_result: Value = response.into_json()?;
+        let swap_result = response.json()?;
         let transaction_b64 = swap_result["swapTransaction"].as_str()
             .ok_or_else(|| anyhow!("No transaction returned from Jupiter swap"))?;
         
         // 3. Execute transaction
         log::info!("Executing swap transaction...");
-        let signature = self.execute_transaction_b64(transaction_b64).await?;
+        let submission = self.execute_transaction_b64(transaction_b64, self.resolve_tip(config.tip_lamports)).await?;
+        
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: tokens_expected,
+            platform: Platform::Jupiter,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    // FIX: Handle both v4 and v6 response formats
+    fn quote_out_amount(quote: &Value) -> Result<u64> {
+        if let Some(out_amount) = quote.get("outAmount") {
+            Ok(out_amount.as_str()
+                .ok_or_else(|| anyhow!("outAmount is not a string"))?
+                .parse::<u64>()?)
+        } else if let Some(data) = quote.get("data") {
+            // Handle alternative response format
+            Ok(data.get("outAmount")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow!("No outAmount in data"))?
+                .parse::<u64>()?)
+        } else {
+            Err(anyhow!("No outAmount found in quote response"))
+        }
+    }
+
+    // Paper buy: fill at the quote of the platform a live buy would use, without signing anything
+    async fn buy_paper(&self, config: &TradeConfig, paper: &PaperAccount, platform: Platform) -> Result<Execution> {
+        let amount_lamports = (config.amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+        
+        let tokens_out = match platform {
+            Platform::PumpFun => {
+                let global = self.fetch_pump_global().await?;
+                self.fetch_bonding_curve(&config.token_address).await?.buy_quote(&global, amount_lamports)?.tokens
+            },
+            Platform::Raydium => {
+                let pool = self.find_raydium_pool(&config.token_address).await?;
+                let mint = self.fetch_mint(&config.token_address).await?;
+                let quote = self.raydium_quote(&pool, &spl_token::native_mint::id(), amount_lamports).await?;
+                self.amount_after_transfer_fee(&mint, quote.amount_out)?
+            },
+            Platform::Jupiter => {
+                let quote = tokio::time::timeout(
+                    Duration::from_secs(15),
+                    self.get_jupiter_quote_with_retry(config, amount_lamports, self.transactions.quote_retries)
+                ).await??;
+                Self::quote_out_amount(&quote)?
+            },
+        };
         
-        Ok((signature, tokens_expected))
+        let signature = paper.fill_buy(&config.token_address, amount_lamports, tokens_out)?;
+        log::info!(
+            "Paper buy filled on {:?}: {} lamports -> {} tokens of {} | Paper balance: {:.6} SOL",
+            platform, amount_lamports, tokens_out, short(&config.token_address), paper.sol_balance()
+        );
+        
+        Ok(Execution {
+            signature,
+            amount_out: tokens_out,
+            platform,
+            tip_lamports: None,
+        })
     }
 
     // FIXED: Jupiter quote with proper validation and retry logic
@@ -412,7 +1240,7 @@ impl FastMemeTrader {
     }
 
     // FIXED: Jupiter quote with proper URL and validation
-    async fn get_jupiter_quote(&self, config: &TradeConfig, amount_lamports: u64) -> Result<Value> {
+    pub async fn get_jupiter_quote(&self, config: &TradeConfig, amount_lamports: u64) -> Result<Value> {
         // Validate inputs first
         Self::validate_token_address(&config.token_address)?;
         
@@ -432,7 +1260,7 @@ impl FastMemeTrader {
         // FIX: Simplified Jupiter v4 quote endpoint without problematic parameters
         let url = format!(
             "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-            self.jupiter_endpoint,
+            self.endpoints.jupiter_api,
             token_addresses::SOL,
             config.token_address,
             amount_lamports,
@@ -441,19 +1269,14 @@ impl FastMemeTrader {
         
         log::debug!("Jupiter quote URL: {}", url);
         
-        let response = tokio::task::spawn_blocking(move || {
-            ureq::get(&url)
-                .timeout(Duration::from_secs(15))
-                .call()
-        }).await??;
+        let response = self.http_get(url, Duration::from_secs(15)).await?;
         
-        if response.status() != 200 {
-            let error_text = response.into_string().unwrap_or_else(|_| "Failed to read error response".to_string());
-            log::error!("Jupiter quote API error: Status {}, Body: {}", response.status(), error_text);
-            return Err(anyhow!("Jupiter quote API error ({}): {}", response.status(), error_text));
+        if !response.is_success() {
+            log::error!("Jupiter quote API error: Status {}, Body: {}", response.status, response.body);
+            return Err(anyhow!("Jupiter quote API error ({}): {}", response.status, response.body));
         }
         
-        let quote: Value = response.into_json()?;
+        let quote = response.json()?;
         
         // FIX: Validate quote response
         if quote.get("data").is_none() && quote.get("outAmount").is_none() {
@@ -465,79 +1288,518 @@ impl FastMemeTrader {
     }
 
     // FIXED: Complete PumpFun implementation
-    async fn buy_pumpfun(&self, config: &TradeConfig) -> Result<(String, u64)> {
-        log::info!("Executing PumpFun buy for {}", &config.token_address[..8]);
+    async fn buy_pumpfun(&self, config: &TradeConfig) -> Result<Execution> {
+        log::info!("Executing PumpFun buy for {}", short(&config.token_address));
         
         let amount_lamports = (config.amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+        let mint = self.fetch_mint(&config.token_address).await?;
+        let user = self.keypair.pubkey();
+        
+        // Quote against the curve before trading: the fill moves it
+        let curve = self.fetch_bonding_curve(&config.token_address).await?;
+        let global = self.fetch_pump_global().await?;
+        let quote = curve.buy_quote(&global, amount_lamports)?;
+        let max_sol_cost = pumpfun::max_sol_cost(&quote, config.slippage_bps);
+        log::info!(
+            "Bonding curve quote: {} tokens for {} lamports ({} fee), price impact {:.2}%, max cost {} lamports",
+            quote.tokens, quote.sol_lamports, quote.fee_lamports, quote.price_impact_percent, max_sol_cost
+        );
         
-        // Use PumpPortal API for transaction generation
-        let pumpfun_data = json!({
-            "publicKey": self.keypair.pubkey().to_string(),
-            "action": "buy",
-            "mint": config.token_address,
-            "denominatedInSol": "true",
-            "amount": amount_lamports,
-            "slippage": config.slippage_bps,
-            "priorityFee": self.calculate_priority_fee().await,
-            "pool": "pump"
-        });
-        
-        let pumpfun_data_str = serde_json::to_string(&pumpfun_data)?;
+        // The user's token account may not exist yet; creation is a no-op if it does
+        let instructions = vec![
+            create_associated_token_account_idempotent(&user, &user, &mint.address, &mint.token_program),
+            curve.buy_instruction(&global, &mint.address, &mint.token_program, &user, quote.tokens, max_sol_cost)?,
+        ];
+        let submission = self.send_instructions(instructions, PUMPFUN_COMPUTE_UNITS, self.resolve_tip(config.tip_lamports)).await?;
+        
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: quote.tokens,
+            platform: Platform::PumpFun,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    // Sell into the bonding curve, returns the signature and quoted lamports out
+    async fn sell_pumpfun(&self, token_address: &str, curve: &BondingCurve, amount: u64) -> Result<Execution> {
+        log::info!("Executing PumpFun sell of {} tokens for {}", amount, short(token_address));
+        
+        let mint = self.fetch_mint(token_address).await?;
+        let global = self.fetch_pump_global().await?;
+        let quote = curve.sell_quote(&global, self.amount_after_transfer_fee(&mint, amount)?)?;
+        let min_sol_output = pumpfun::min_sol_output(&quote, self.limits.sell_slippage_bps);
+        log::info!(
+            "Bonding curve quote: {} lamports ({} fee), price impact {:.2}%, min out {} lamports",
+            quote.sol_lamports, quote.fee_lamports, quote.price_impact_percent, min_sol_output
+        );
         
-        let response = tokio::task::spawn_blocking(move || {
-            ureq::post("https://pumpportal.fun/api/trade-local")
-                .timeout(Duration::from_secs(20))
-                .set("Content-Type", "application/json")
-                .send_string(&pumpfun_data_str)
-        }).await??;
+        let instruction = curve.sell_instruction(&global, &mint.address, &mint.token_program, &self.keypair.pubkey(), amount, min_sol_output)?;
+        let submission = self.send_instructions(vec![instruction], PUMPFUN_COMPUTE_UNITS, self.resolve_tip(None)).await?;
         
-        if response.status() != 200 {
-            let error_text = response.into_string()?;
-            return Err(anyhow!("PumpFun API error: {}", error_text));
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: quote.sol_lamports,
+            platform: Platform::PumpFun,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    // Prefix compute budget instructions, sign with a fresh blockhash and send
+    async fn send_instructions(&self, instructions: Vec<Instruction>, compute_units: u32, tip_lamports: Option<u64>) -> Result<Submission> {
+        let payer = self.keypair.pubkey();
+        let mut all_instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(compute_units),
+            ComputeBudgetInstruction::set_compute_unit_price(self.calculate_priority_fee().await),
+        ];
+        all_instructions.extend(instructions);
+        
+        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(&all_instructions, Some(&payer), &[&self.keypair], recent_blockhash);
+        self.submit(VersionedTransaction::from(transaction), tip_lamports).await
+    }
+
+    // Swap SOL for the token through its Raydium pool, wrapping SOL on the way in
+    async fn buy_raydium(&self, config: &TradeConfig) -> Result<Execution> {
+        let pool = self.find_raydium_pool(&config.token_address).await?;
+        log::info!("Executing Raydium {} buy for {} via pool {}", pool.kind(), short(&config.token_address), pool.address());
+        
+        let amount_lamports = (config.amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+        let wsol = spl_token::native_mint::id();
+        let user = self.keypair.pubkey();
+        
+        let token_mint = self.fetch_mint(&config.token_address).await?;
+        let mut quote = self.raydium_quote(&pool, &wsol, amount_lamports).await?;
+        // The pool checks the minimum against what arrives after any transfer fee
+        quote.amount_out = self.amount_after_transfer_fee(&token_mint, quote.amount_out)?;
+        let min_out = raydium::min_amount_out(&quote, config.slippage_bps);
+        log::info!(
+            "Raydium quote: {} tokens for {} lamports, price impact {:.2}%, min out {}",
+            quote.amount_out, amount_lamports, quote.price_impact_percent, min_out
+        );
+        
+        let (_, output) = pool.swap_sides(&wsol)?;
+        let wsol_account = get_associated_token_address(&user, &wsol);
+        let token_account = get_associated_token_address_with_program_id(&user, &output.mint, &output.token_program);
+        let instructions = vec![
+            create_associated_token_account_idempotent(&user, &user, &wsol, &spl_token::id()),
+            system_instruction::transfer(&user, &wsol_account, amount_lamports),
+            spl_token::instruction::sync_native(&spl_token::id(), &wsol_account)?,
+            create_associated_token_account_idempotent(&user, &user, &output.mint, &output.token_program),
+            pool.swap_instruction(&user, &wsol, &wsol_account, &token_account, amount_lamports, min_out)?,
+            // Unwrap whatever is left back to SOL
+            spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &user, &user, &[])?,
+        ];
+        let submission = self.send_instructions(instructions, RAYDIUM_COMPUTE_UNITS, self.resolve_tip(config.tip_lamports)).await?;
+        
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: quote.amount_out,
+            platform: Platform::Raydium,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    // Swap the token for SOL through a Raydium pool, returns the signature and quoted lamports out
+    async fn sell_raydium(&self, token_address: &str, pool: &RaydiumPool, amount: u64) -> Result<Execution> {
+        log::info!("Executing Raydium {} sell of {} tokens for {}", pool.kind(), amount, short(token_address));
+        
+        let token_mint = self.fetch_mint(token_address).await?;
+        let mint = token_mint.address;
+        let wsol = spl_token::native_mint::id();
+        let user = self.keypair.pubkey();
+        
+        // The pool only swaps what is left after any transfer fee
+        let quote = self.raydium_quote(pool, &mint, self.amount_after_transfer_fee(&token_mint, amount)?).await?;
+        let min_out = raydium::min_amount_out(&quote, self.limits.sell_slippage_bps);
+        log::info!(
+            "Raydium quote: {} lamports, price impact {:.2}%, min out {}",
+            quote.amount_out, quote.price_impact_percent, min_out
+        );
+        
+        let (input, _) = pool.swap_sides(&mint)?;
+        let token_account = get_associated_token_address_with_program_id(&user, &mint, &input.token_program);
+        let wsol_account = get_associated_token_address(&user, &wsol);
+        let instructions = vec![
+            create_associated_token_account_idempotent(&user, &user, &wsol, &spl_token::id()),
+            pool.swap_instruction(&user, &mint, &token_account, &wsol_account, amount, min_out)?,
+            spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &user, &user, &[])?,
+        ];
+        let submission = self.send_instructions(instructions, RAYDIUM_COMPUTE_UNITS, self.resolve_tip(None)).await?;
+        
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: quote.amount_out,
+            platform: Platform::Raydium,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    async fn raydium_quote(&self, pool: &RaydiumPool, input_mint: &Pubkey, amount_in: u64) -> Result<SwapQuote> {
+        let (first, second) = pool.sides();
+        let vaults = self.rpc_client.get_multiple_accounts(&[first.vault, second.vault])?;
+        let amount = |account: &Option<Account>| match account {
+            Some(account) => raydium::token_account_amount(&account.data),
+            None => Err(anyhow!("Pool vault not found")),
+        };
+        pool.quote(input_mint, amount_in, (amount(&vaults[0])?, amount(&vaults[1])?))
+    }
+
+    // Find the token's deepest SOL pool across AMM v4 and CPMM, straight from chain
+    pub async fn find_raydium_pool(&self, token_address: &str) -> Result<RaydiumPool> {
+        if let Some(pool) = self.raydium_pools.read().await.get(token_address) {
+            return Ok(pool.clone());
         }
         
-        let transaction_b64 = response.into_string()?;
-        let signature = self.execute_transaction_b64(&transaction_b64).await?;
+        let mint = Pubkey::from_str(token_address)
+            .map_err(|_| anyhow!("Invalid token address format: {}", token_address))?;
+        let wsol = spl_token::native_mint::id();
+        
+        // Candidate pools with the vault holding their SOL side
+        let mut amm_v4 = Vec::new();
+        let mut cpmm = Vec::new();
+        for (mint_offset, sol_offset) in [
+            (raydium::AMM_V4_BASE_MINT_OFFSET, raydium::AMM_V4_QUOTE_MINT_OFFSET),
+            (raydium::AMM_V4_QUOTE_MINT_OFFSET, raydium::AMM_V4_BASE_MINT_OFFSET),
+        ] {
+            let filters = [(mint_offset, mint), (sol_offset, wsol)];
+            for (address, account) in self.program_accounts(&raydium::AMM_V4_PROGRAM_ID, Some(raydium::AMM_V4_POOL_LEN), &filters)? {
+                if let Ok(pool) = AmmV4Pool::decode(address, &account.data) {
+                    let sol_vault = if pool.quote_mint == wsol { pool.quote_vault } else { pool.base_vault };
+                    amm_v4.push((sol_vault, pool));
+                }
+            }
+        }
+        for (mint_offset, sol_offset) in [
+            (raydium::CPMM_TOKEN_0_MINT_OFFSET, raydium::CPMM_TOKEN_1_MINT_OFFSET),
+            (raydium::CPMM_TOKEN_1_MINT_OFFSET, raydium::CPMM_TOKEN_0_MINT_OFFSET),
+        ] {
+            let filters = [(mint_offset, mint), (sol_offset, wsol)];
+            for (address, account) in self.program_accounts(&raydium::CPMM_PROGRAM_ID, None, &filters)? {
+                if let Ok(pool) = CpmmPool::decode(address, &account.data) {
+                    let sol_vault = if pool.token_1_mint == wsol { pool.token_1_vault } else { pool.token_0_vault };
+                    cpmm.push((sol_vault, pool));
+                }
+            }
+        }
+        if amm_v4.is_empty() && cpmm.is_empty() {
+            return Err(anyhow!("No Raydium SOL pool found for {}", token_address));
+        }
         
-        // Better token estimation based on bonding curve
-        let tokens_estimated = self.estimate_pumpfun_tokens(amount_lamports).await
-            .unwrap_or(amount_lamports * 1_000_000);
+        // Rank by SOL liquidity
+        let sol_vaults: Vec<Pubkey> = amm_v4.iter().map(|(vault, _)| *vault)
+            .chain(cpmm.iter().map(|(vault, _)| *vault))
+            .collect();
+        let liquidity: Vec<u64> = self.rpc_client.get_multiple_accounts(&sol_vaults)?
+            .iter()
+            .map(|account| account.as_ref().and_then(|account| raydium::token_account_amount(&account.data).ok()).unwrap_or(0))
+            .collect();
+        let deepest = (0..liquidity.len()).max_by_key(|&index| liquidity[index]).unwrap_or(0);
+        
+        let pool = if deepest < amm_v4.len() {
+            let pool = amm_v4.swap_remove(deepest).1;
+            let market_data = self.rpc_client.get_account_data(&pool.market)
+                .map_err(|e| anyhow!("Failed to fetch market {} for pool {}: {}", pool.market, pool.address, e))?;
+            let market = MarketAccounts::decode(&pool.market, &pool.market_program, &market_data)?;
+            RaydiumPool::AmmV4 { pool, market }
+        } else {
+            let pool = cpmm.swap_remove(deepest - amm_v4.len()).1;
+            let config_data = self.rpc_client.get_account_data(&pool.amm_config)
+                .map_err(|e| anyhow!("Failed to fetch CPMM config {}: {}", pool.amm_config, e))?;
+            let trade_fee_rate = raydium::decode_cpmm_trade_fee_rate(&config_data)?;
+            RaydiumPool::Cpmm { pool, trade_fee_rate }
+        };
         
-        Ok((signature, tokens_estimated))
+        log::info!(
+            "Raydium {} pool {} for {} ({} lamports SOL liquidity)",
+            pool.kind(), pool.address(), short(token_address), liquidity[deepest]
+        );
+        self.raydium_pools.write().await.insert(token_address.to_string(), pool.clone());
+        Ok(pool)
     }
 
-    // Estimate PumpFun tokens
-    async fn estimate_pumpfun_tokens(&self, amount_lamports: u64) -> Result<u64> {
-        // Simple linear approximation - could be improved with actual curve calculation
-        Ok(amount_lamports * 1_000_000)
+    // getProgramAccounts with byte-exact pubkey matches at the given offsets
+    fn program_accounts(&self, program_id: &Pubkey, data_size: Option<u64>, matches: &[(usize, Pubkey)]) -> Result<Vec<(Pubkey, Account)>> {
+        let mut filters: Vec<RpcFilterType> = matches.iter()
+            .map(|(offset, key)| RpcFilterType::Memcmp(Memcmp::new_raw_bytes(*offset, key.to_bytes().to_vec())))
+            .collect();
+        if let Some(size) = data_size {
+            filters.push(RpcFilterType::DataSize(size));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        self.rpc_client.get_program_accounts_with_config(program_id, config)
+            .map_err(|e| anyhow!("getProgramAccounts failed for {}: {}", program_id, e))
+    }
+
+    // Expected output of a PumpFun buy from the token's current bonding curve
+    pub async fn estimate_pumpfun_tokens(&self, token_address: &str, amount_lamports: u64) -> Result<CurveQuote> {
+        let global = self.fetch_pump_global().await?;
+        self.fetch_bonding_curve(token_address).await?.buy_quote(&global, amount_lamports)
+    }
+
+    // Current protocol and creator fee rates, which the quotes depend on
+    pub async fn fetch_pump_global(&self) -> Result<Global> {
+        let data = self.rpc_client.get_account_data(&pumpfun::GLOBAL)
+            .map_err(|e| anyhow!("Failed to fetch PumpFun global account: {}", e))?;
+        Global::decode(&data)
+    }
+
+    pub async fn fetch_bonding_curve(&self, token_address: &str) -> Result<BondingCurve> {
+        let mint = Pubkey::from_str(token_address)
+            .map_err(|_| anyhow!("Invalid token address format: {}", token_address))?;
+        let curve_address = pumpfun::bonding_curve_address(&mint);
+        let data = self.rpc_client.get_account_data(&curve_address)
+            .map_err(|e| anyhow!("Failed to fetch bonding curve {}: {}", curve_address, e))?;
+        BondingCurve::decode(&data)
+    }
+
+    // USD price from the bonding curve, for pump tokens that have not migrated
+    async fn get_bonding_curve_price(&self, token_address: &str) -> Result<Decimal> {
+        let curve = self.fetch_bonding_curve(token_address).await?;
+        if curve.complete {
+            return Err(anyhow!("Bonding curve for {} has migrated", short(token_address)));
+        }
+        let price_sol = curve.price_sol().ok_or_else(|| anyhow!("Bonding curve has no token reserves"))?;
+        Ok(price_sol * self.sol_usd_price().await?)
     }
 
-    // Execute base64 encoded transaction with better error handling
-    async fn execute_transaction_b64(&self, transaction_b64: &str) -> Result<String> {
+    // A Raydium position's price from its pool vaults, the polled twin of its streamed feed
+    async fn get_raydium_vault_price(&self, feed: &PriceFeed) -> Result<Decimal> {
+        let PriceFeed::RaydiumVaults { pool, token_vault, sol_vault, token_decimals } = feed else {
+            return Err(anyhow!("Not a Raydium price feed"));
+        };
+        let mut accounts = vec![Pubkey::from_str(token_vault)?, Pubkey::from_str(sol_vault)?];
+        if let Some(pool) = pool {
+            accounts.push(Pubkey::from_str(pool)?);
+        }
+        // Vaults and pool come from one snapshot, so they share its slot
+        let response = self.rpc_client
+            .get_multiple_accounts_with_commitment(&accounts, self.transactions.commitment.to_commitment_config())?;
+        let slot = response.context.slot;
+        let account = |index: usize| response.value.get(index).and_then(Option::as_ref);
+        let amount = |account: Option<&Account>| {
+            account.and_then(|account| raydium::token_account_amount(&account.data).ok()).map(|amount| (amount, slot))
+        };
+        let balances = VaultBalances {
+            token: amount(account(0)),
+            sol: amount(account(1)),
+            owed: account(2).and_then(|pool| price_stream::owed_amounts(feed, &pool.data)).unwrap_or_default(),
+        };
+        let price_sol = price_stream::vault_price(balances, *token_decimals)
+            .ok_or_else(|| anyhow!("Pool vaults for {} are missing or empty", short(token_vault)))?;
+        Ok(price_sol * self.sol_usd_price().await?)
+    }
+
+    // Execute a base64 encoded legacy or v0 transaction with better error handling
+    async fn execute_transaction_b64(&self, transaction_b64: &str, tip_lamports: Option<u64>) -> Result<Submission> {
         log::debug!("Executing transaction from base64");
         
         let transaction_bytes = base64::engine::general_purpose::STANDARD.decode(transaction_b64)?;
-        let mut transaction: Transaction = bincode::deserialize(&transaction_bytes)?;
+        // The versioned wire format is a superset of the legacy one
+        let transaction: VersionedTransaction = bincode::deserialize(&transaction_bytes)
+            .map_err(|e| anyhow!("Failed to decode transaction: {}", e))?;
+        log::debug!("Decoded {:?} transaction", transaction.version());
         
         // Get fresh blockhash and re-sign
         let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        transaction.sign(&[&self.keypair], recent_blockhash);
+        let transaction = self.sign_with_blockhash(transaction, recent_blockhash)?;
+        
+        self.submit(transaction, tip_lamports).await
+    }
+
+    // Tip to use for a trade: an explicit per-trade tip, else the configured default when Jito is on
+    fn resolve_tip(&self, requested: Option<u64>) -> Option<u64> {
+        match requested {
+            Some(0) => None,
+            Some(tip) => Some(tip),
+            None if self.jito.enabled => Some(self.jito.tip_lamports),
+            None => None,
+        }
+    }
+
+    // Send a signed transaction as a tipped Jito bundle when a tip is given, falling back
+    // to standard RPC if the bundle is rejected or does not land in time
+    async fn submit(&self, transaction: VersionedTransaction, tip_lamports: Option<u64>) -> Result<Submission> {
+        let transaction = if self.transactions.simulate {
+            self.preflight(transaction)?
+        } else {
+            transaction
+        };
+        
+        if let Some(tip) = tip_lamports {
+            let tip_transaction = self.tip_transaction(&transaction, tip);
+            match self.send_bundle(&transaction, &tip_transaction, tip).await {
+                Ok(signature) => return Ok(Submission { signature, tip_lamports: Some(tip) }),
+                Err(e) => log::warn!("Jito bundle did not land ({}), falling back to RPC", e),
+            }
+            
+            // A bundle can land just after we stop polling; the fallback resends the same
+            // signed transaction so the trade can't execute twice
+            let signature = transaction.signatures[0];
+            match self.send_action(&transaction) {
+                SendAction::Landed => {
+                    log::info!("Bundled transaction {} landed after the status timeout", signature);
+                    return Ok(Submission { signature, tip_lamports: Some(tip) });
+                },
+                SendAction::FailedOnChain(e) => {
+                    return Err(anyhow!("Bundled transaction {} failed on chain: {}", signature, e));
+                },
+                SendAction::Resend | SendAction::Resign => {},
+            }
+            
+            // The bundle can still land while the fallback is in flight. Its tip transfer
+            // only ever executes inside the bundle, so its status says if the tip was paid.
+            let signature = self.send_with_retry(transaction).await?;
+            let tip_paid = self.landed(&tip_transaction.signatures[0]);
+            if tip_paid {
+                log::info!("Jito bundle for {} landed during the RPC fallback", signature);
+            }
+            return Ok(Submission { signature, tip_lamports: tip_paid.then_some(tip) });
+        }
         
         let signature = self.send_with_retry(transaction).await?;
-        Ok(signature.to_string())
+        Ok(Submission { signature, tip_lamports: None })
+    }
+
+    fn landed(&self, signature: &Signature) -> bool {
+        matches!(self.rpc_client.get_signature_status(signature), Ok(Some(Ok(()))))
     }
 
-    // Robust transaction sending with exponential backoff
-    async fn send_with_retry(&self, mut transaction: Transaction) -> Result<Signature> {
+    // Simulate the signed transaction and return it with its compute unit limit
+    // fitted to the simulated usage. A failing simulation aborts the trade before
+    // any fee is paid.
+    fn preflight(&self, transaction: VersionedTransaction) -> Result<VersionedTransaction> {
+        // Simulate at the maximum limit so a low estimate can't fail the simulation itself
+        let mut simulated = transaction.clone();
+        let has_limit = simulation::set_compute_unit_limit(&mut simulated.message, simulation::MAX_COMPUTE_UNITS);
+        
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.transactions.commitment.to_commitment_config()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self.rpc_client.simulate_transaction_with_config(&simulated, config)
+            .map_err(|e| anyhow!("Failed to simulate transaction: {}", e))?
+            .value;
+        
+        let logs = result.logs.unwrap_or_default();
+        if let Some(error) = result.err {
+            let error = SimulationError::decode(&error, &logs);
+            log::warn!("{}", error);
+            for line in &logs {
+                log::debug!("  {}", line);
+            }
+            return Err(error.into());
+        }
+        
+        let (Some(units_consumed), true) = (result.units_consumed, has_limit) else {
+            return Ok(transaction);
+        };
+        let limit = simulation::compute_unit_limit(units_consumed, self.transactions.compute_unit_margin_percent);
+        log::debug!("Simulation consumed {} compute units, requesting {}", units_consumed, limit);
+        
+        let mut message = transaction.message;
+        simulation::set_compute_unit_limit(&mut message, limit);
+        VersionedTransaction::try_new(message, &[&self.keypair])
+            .map_err(|e| anyhow!("Failed to sign transaction: {}", e))
+    }
+
+    // Tip transfer for a trade's bundle, on the same blockhash so both expire together
+    fn tip_transaction(&self, transaction: &VersionedTransaction, tip_lamports: u64) -> Transaction {
+        let payer = self.keypair.pubkey();
+        let tip_instruction = jito::tip_instruction(&payer, &transaction.signatures[0], tip_lamports);
+        Transaction::new_signed_with_payer(
+            &[tip_instruction], Some(&payer), &[&self.keypair], *transaction.message.recent_blockhash(),
+        )
+    }
+
+    // Submit [trade, tip transfer] as one bundle and wait for it to land
+    async fn send_bundle(&self, transaction: &VersionedTransaction, tip_transaction: &Transaction, tip_lamports: u64) -> Result<Signature> {
+        let signature = transaction.signatures[0];
+        
+        let encode = |bytes: Vec<u8>| base64::engine::general_purpose::STANDARD.encode(bytes);
+        let bundle = [
+            encode(bincode::serialize(transaction)?),
+            encode(bincode::serialize(tip_transaction)?),
+        ];
+        
+        let url = jito::bundles_url(&self.endpoints.jito_block_engine);
+        let request = jito::send_bundle_request(&bundle).to_string();
+        let response = self.http_post_json(url.clone(), request, Duration::from_secs(5)).await?;
+        let bundle_id = jito::parse_bundle_id(&response.json()?)?;
+        log::info!("Submitted Jito bundle {} for {} with {} lamport tip", bundle_id, signature, tip_lamports);
+        
+        let deadline = Instant::now() + Duration::from_millis(self.jito.bundle_timeout_ms);
+        let poll_interval = Duration::from_millis(self.jito.status_poll_ms);
+        while Instant::now() < deadline {
+            tokio::time::sleep(poll_interval).await;
+            
+            let request = jito::bundle_status_request(&bundle_id).to_string();
+            let status = match self.http_post_json(url.clone(), request, Duration::from_secs(5)).await
+                .and_then(|response| response.json())
+                .and_then(|response| jito::parse_bundle_status(&response))
+            {
+                Ok(status) => status,
+                Err(e) => {
+                    log::debug!("Bundle status check failed: {}", e);
+                    continue;
+                }
+            };
+            
+            match status {
+                BundleStatus::Landed { slot } => {
+                    log::info!("Jito bundle {} landed in slot {}", bundle_id, slot);
+                    return Ok(signature);
+                },
+                BundleStatus::Failed => return Err(anyhow!("bundle {} failed", bundle_id)),
+                BundleStatus::Pending | BundleStatus::Invalid => {},
+            }
+        }
+        
+        Err(anyhow!("bundle {} not landed after {}ms", bundle_id, self.jito.bundle_timeout_ms))
+    }
+
+    // Point the message at a new blockhash and sign it with our wallet. Address
+    // lookup tables in v0 messages are left untouched.
+    fn sign_with_blockhash(&self, transaction: VersionedTransaction, recent_blockhash: Hash) -> Result<VersionedTransaction> {
+        let mut message = transaction.message;
+        message.set_recent_blockhash(recent_blockhash);
+        VersionedTransaction::try_new(message, &[&self.keypair])
+            .map_err(|e| anyhow!("Failed to sign transaction: {}", e))
+    }
+
+    // Robust transaction sending with exponential backoff. Retries resend the same
+    // signed transaction while it could still land and only re-sign once it can't.
+    async fn send_with_retry(&self, mut transaction: VersionedTransaction) -> Result<Signature> {
         let mut last_error = None;
         
-        for attempt in 1..=5 {
-            log::debug!("Sending transaction attempt {}/5", attempt);
+        let max_attempts = self.transactions.send_retries;
+        for attempt in 1..=max_attempts {
+            log::debug!("Sending transaction attempt {}/{}", attempt, max_attempts);
             
-            // Get fresh blockhash for each attempt after the first
             if attempt > 1 {
-                if let Ok(new_blockhash) = self.rpc_client.get_latest_blockhash() {
-                    transaction.sign(&[&self.keypair], new_blockhash);
+                let signature = transaction.signatures[0];
+                match self.send_action(&transaction) {
+                    SendAction::Landed => {
+                        log::info!("Transaction {} landed after a failed confirmation", signature);
+                        return Ok(signature);
+                    },
+                    SendAction::FailedOnChain(e) => {
+                        return Err(anyhow!("Transaction {} failed on chain: {}", signature, e));
+                    },
+                    SendAction::Resend => log::debug!("Resending {}, its blockhash is still valid", signature),
+                    SendAction::Resign => {
+                        let new_blockhash = self.rpc_client.get_latest_blockhash()?;
+                        transaction = self.sign_with_blockhash(transaction, new_blockhash)?;
+                        log::debug!("Blockhash expired without {} landing, re-signed", signature);
+                    },
                 }
             }
             
@@ -550,75 +1812,228 @@ impl FastMemeTrader {
                     log::warn!("Transaction attempt {} failed: {}", attempt, e);
                     last_error = Some(e);
                     
-                    if attempt < 5 {
-                        // Exponential backoff: 500ms, 1s, 2s, 4s
-                        let delay = Duration::from_millis(500 * (1u64 << (attempt - 1)));
+                    if attempt < max_attempts {
+                        // Exponential backoff from the base delay: 500ms, 1s, 2s, 4s by default
+                        let delay = Duration::from_millis(
+                            self.transactions.retry_base_delay_ms.saturating_mul(1u64 << (attempt - 1).min(10))
+                        );
                         tokio::time::sleep(delay).await;
                     }
                 }
             }
         }
         
-        Err(anyhow!("Transaction failed after 5 attempts: {:?}", last_error))
+        // The last attempt may have landed after its confirmation timed out
+        if self.send_action(&transaction) == SendAction::Landed {
+            let signature = transaction.signatures[0];
+            log::info!("Transaction {} landed after a failed confirmation", signature);
+            return Ok(signature);
+        }
+        
+        Err(anyhow!("Transaction failed after {} attempts: {:?}", max_attempts, last_error))
+    }
+
+    fn send_action(&self, transaction: &VersionedTransaction) -> SendAction {
+        let status = self.rpc_client.get_signature_status(&transaction.signatures[0]).ok();
+        let blockhash_valid = self.rpc_client
+            .is_blockhash_valid(transaction.message.recent_blockhash(), self.transactions.commitment.to_commitment_config())
+            .ok();
+        next_send_action(status, blockhash_valid)
+    }
+
+    // The account a new position's price can be streamed from. Only tokens whose
+    // liquidity lives in one known account qualify; Jupiter-routed ones are polled.
+    async fn price_feed(&self, token_address: &str, platform: &Platform, decimals: Option<u8>) -> Option<PriceFeed> {
+        let mint = Pubkey::from_str(token_address).ok()?;
+        match platform {
+            Platform::PumpFun => Some(PriceFeed::BondingCurve {
+                curve: pumpfun::bonding_curve_address(&mint).to_string(),
+            }),
+            Platform::Raydium => {
+                let pool = self.raydium_pools.read().await.get(token_address).cloned()?;
+                let (token_side, sol_side) = pool.swap_sides(&mint).ok()?;
+                let token_decimals = match decimals {
+                    Some(decimals) => decimals,
+                    None => self.fetch_mint(token_address).await.ok()?.decimals,
+                };
+                Some(PriceFeed::RaydiumVaults {
+                    pool: Some(pool.address().to_string()),
+                    token_vault: token_side.vault.to_string(),
+                    sol_vault: sol_side.vault.to_string(),
+                    token_decimals,
+                })
+            },
+            Platform::Jupiter => None,
+        }
     }
 
     // Initialize position with strategy tracking
-    async fn initialize_position(&self, config: &TradeConfig, signature: &str, tokens_received: u64) {
-        let current_price = self.get_current_price(&config.token_address).await
-            .unwrap_or(Decimal::from(0));
-        
-        log::info!("Initializing position at price ${:.8}", current_price);
+    async fn initialize_position(
+        &self, config: &TradeConfig, execution: &Execution, tokens_received: u64, sol_spent: f64, decimals: Option<u8>,
+        price_feed: Option<PriceFeed>,
+    ) {
+        let entry_price = self.fill_entry_price(&config.token_address, sol_spent, tokens_received, decimals).await;
+        match entry_price {
+            Some(price) => log::info!("Initializing position at price ${:.8}", price),
+            None => log::warn!(
+                "Initializing unpriced position for {}: price-based exits are off until it is priced",
+                short(&config.token_address)
+            ),
+        }
         
         let position = Position {
             token_address: config.token_address.clone(),
-            entry_price: current_price,
+            entry_price,
             amount_tokens: tokens_received,
             entry_time: Utc::now(),
             strategy: config.strategy.clone(),
-            buy_signature: signature.to_string(),
+            buy_signature: execution.signature.clone(),
+            sol_in: sol_spent,
+            realized_sol_out: 0.0,
+            realized_fee_lamports: 0,
+            realized_tip_lamports: execution.tip_lamports.unwrap_or(0),
+            initial_amount_tokens: tokens_received,
+            take_profit_ladder: config.take_profit_ladder.clone(),
+            completed_tiers: Vec::new(),
+            fired_exits: Vec::new(),
+            time_exits: config.time_exits,
+            price_feed,
         };
         
-        let ath_tracker = match config.strategy {
-            StrategyType::ConservativeATH => ATHTracker {
-                entry_price: current_price,
-                ath_price: current_price,
-                last_price: current_price,
-                pullback_percent: Decimal::from(8),
-                min_profit_percent: Decimal::from(3),
-                last_updated: Utc::now(),
-            },
-            StrategyType::AggressiveATH => ATHTracker {
-                entry_price: current_price,
-                ath_price: current_price,
-                last_price: current_price,
-                pullback_percent: Decimal::from(12),
-                min_profit_percent: Decimal::from(5),
-                last_updated: Utc::now(),
-            },
-            _ => ATHTracker {
-                entry_price: current_price,
-                ath_price: current_price,
-                last_price: current_price,
-                pullback_percent: Decimal::from(10),
-                min_profit_percent: Decimal::from(2),
-                last_updated: Utc::now(),
-            },
-        };
+        let ath_tracker = ATHTracker::new(entry_price, &config.strategy.params());
         
         {
             let mut positions = self.positions.write().await;
+            self.persist(&JournalEntry::UpsertPosition { position: Box::new(position.clone()) });
             positions.insert(config.token_address.clone(), position);
         }
         {
             let mut trackers = self.ath_tracker.write().await;
+            self.persist(&JournalEntry::UpsertTracker {
+                token_address: config.token_address.clone(),
+                tracker: ath_tracker.clone(),
+            });
             trackers.insert(config.token_address.clone(), ath_tracker);
         }
+        self.compact_state_if_needed().await;
         
         log::info!("Position and ATH tracker initialized for strategy: {:?}", config.strategy);
     }
 
+    // USD entry price from what the buy actually paid: SOL spent per whole token, at the
+    // current SOL/USD rate so it compares with the USD price ticks. None if any input is missing.
+    async fn fill_entry_price(&self, token_address: &str, sol_spent: f64, tokens_received: u64, decimals: Option<u8>) -> Option<Decimal> {
+        let decimals = match decimals {
+            Some(decimals) => decimals,
+            None => match self.mint_decimals(token_address).await {
+                Ok(decimals) => decimals,
+                Err(e) => {
+                    log::warn!("Could not read decimals for {}: {}", short(token_address), e);
+                    return None;
+                }
+            },
+        };
+        let price_sol = fill::price_per_token_sol(sol_spent, tokens_received, decimals)?;
+        
+        match self.sol_usd_price().await {
+            Ok(sol_usd) => Some(price_sol * sol_usd),
+            Err(e) => {
+                log::warn!("No SOL/USD rate to price the entry for {}: {}", short(token_address), e);
+                None
+            }
+        }
+    }
+
+    async fn mint_decimals(&self, token_address: &str) -> Result<u8> {
+        Ok(self.fetch_mint(token_address).await?.decimals)
+    }
+
+    // The token's mint and owning program. Mints with a transfer fee are refetched each
+    // time since the fee schedule can change; everything else is cached.
+    pub async fn fetch_mint(&self, token_address: &str) -> Result<MintInfo> {
+        if let Some(mint) = self.mints.read().await.get(token_address) {
+            return Ok(mint.clone());
+        }
+        
+        let address = Pubkey::from_str(token_address)
+            .map_err(|_| anyhow!("Invalid token address format: {}", token_address))?;
+        let account = self.rpc_client.get_account(&address)
+            .map_err(|e| anyhow!("Failed to fetch mint {}: {}", address, e))?;
+        let mint = MintInfo::decode(address, &account)?;
+        if mint.is_token_2022() {
+            log::debug!("{} is a Token-2022 mint (transfer fee: {})", short(token_address), mint.transfer_fee.is_some());
+        }
+        
+        if mint.transfer_fee.is_none() {
+            self.mints.write().await.insert(token_address.to_string(), mint.clone());
+        }
+        Ok(mint)
+    }
+
+    // Tokens that reach the counterparty when `amount` is sent, net of any Token-2022 transfer fee
+    fn amount_after_transfer_fee(&self, mint: &MintInfo, amount: u64) -> Result<u64> {
+        if mint.transfer_fee.is_none() {
+            return Ok(amount);
+        }
+        let epoch = self.rpc_client.get_epoch_info()?.epoch;
+        let net = mint.amount_after_transfer_fee(amount, epoch);
+        if net < amount {
+            log::info!("Transfer fee withholds {} of {} tokens", amount - net, amount);
+        }
+        Ok(net)
+    }
+
+    // Journal a state change. This only queues it for the state writer, so callers
+    // can hold the matching map's write lock, which keeps the journal in the same
+    // order as memory and stops compaction racing a mutation. Failures are logged,
+    // not fatal: the in-memory state stays authoritative for this run.
+    fn persist(&self, entry: &JournalEntry) {
+        if let Err(e) = self.state_store.record(entry) {
+            log::error!("Failed to persist state change: {}", e);
+        }
+    }
+
+    // Drop a position and its tracker, both in memory and on disk
+    async fn remove_position(&self, token_address: &str) {
+        {
+            let mut positions_guard = self.positions.write().await;
+            positions_guard.remove(token_address);
+            self.persist(&JournalEntry::RemovePosition { token_address: token_address.to_string() });
+        }
+        {
+            let mut trackers_guard = self.ath_tracker.write().await;
+            trackers_guard.remove(token_address);
+            self.persist(&JournalEntry::RemoveTracker { token_address: token_address.to_string() });
+        }
+        self.compact_state_if_needed().await;
+    }
+
+    // The writer logs a failed compaction, so the monitor loop doesn't wait for it
+    async fn compact_state_if_needed(&self) {
+        if self.state_store.needs_compaction() {
+            self.queue_compaction().await;
+        }
+    }
+
+    // Write a full snapshot of positions and trackers and reset the journal
+    pub async fn flush_state(&self) -> Result<()> {
+        self.queue_compaction().await
+            .await
+            .map_err(|_| anyhow!("State writer stopped before compacting"))?
+    }
+
+    async fn queue_compaction(&self) -> tokio::sync::oneshot::Receiver<Result<()>> {
+        let positions = self.positions.read().await;
+        let trackers = self.ath_tracker.read().await;
+        let state = PersistedState {
+            positions: positions.clone(),
+            trackers: trackers.clone(),
+        };
+        self.state_store.compact(state)
+    }
+
     // Monitor positions and execute strategies with batched price updates
-    pub async fn monitor_positions(&self) -> Vec<String> {
+    pub async fn monitor_positions(&self) -> Vec<SellEvent> {
         let mut executed_sells = Vec::new();
         let positions: Vec<_> = {
             let positions_guard = self.positions.read().await;
@@ -638,151 +2053,572 @@ impl FastMemeTrader {
         }
         
         for position in positions {
-            if let Some(&current_price) = price_updates.get(&position.token_address) {
-                let should_sell = self.evaluate_exit_strategy(&position, current_price).await;
-                
-                if should_sell {
-                    log::info!("Exit strategy triggered for {}", &position.token_address[..8]);
-                    
-                    if let Ok(sell_result) = self.sell_position(&position.token_address).await {
-                        let message = format!(
-                            "Sold {} - Signature: {} - Strategy: {:?} - Time: {}ms",
-                            &position.token_address[..8], 
-                            sell_result.signature, 
-                            position.strategy,
-                            sell_result.execution_time_ms
-                        );
-                        executed_sells.push(message);
-                        
-                        // Clean up position and tracker
-                        {
-                            let mut positions_guard = self.positions.write().await;
-                            positions_guard.remove(&position.token_address);
-                        }
-                        {
-                            let mut trackers_guard = self.ath_tracker.write().await;
-                            trackers_guard.remove(&position.token_address);
-                        }
-                    }
-                }
+            match price_updates.get(&position.token_address) {
+                Some(&current_price) => {
+                    let tick = PriceTick {
+                        price: current_price,
+                        timestamp: Utc::now(),
+                    };
+                    self.process_price_tick(&position, &tick, &mut executed_sells).await;
+                },
+                // Max hold still applies when no price is available this cycle
+                None if strategy::max_hold_expired(&position, Utc::now()) => {
+                    log::info!("Max hold reached for {} without a price, selling", short(&position.token_address));
+                    let reason = ExitReason::without_metrics(ExitRule::MaxHold);
+                    let started = Instant::now();
+                    let sell_result = self.sell_and_close(&position.token_address, reason.clone()).await;
+                    executed_sells.push(SellEvent::new(&position, reason, sell_result, started));
+                },
+                None => {},
             }
         }
         
         executed_sells
     }
 
-    // Strategy evaluation with ATH logic
-    async fn evaluate_exit_strategy(&self, position: &Position, current_price: Decimal) -> bool {
-        // Update ATH tracker
-        {
-            let mut trackers = self.ath_tracker.write().await;
-            if let Some(tracker) = trackers.get_mut(&position.token_address) {
-                if current_price > tracker.ath_price {
-                    tracker.ath_price = current_price;
-                    log::debug!("New ATH for {}: ${:.8}", &position.token_address[..8], current_price);
-                }
-                tracker.last_price = current_price;
-                tracker.last_updated = Utc::now();
+    // Settings for price_stream::spawn against this trader's RPC host
+    pub fn price_stream_settings(&self, reconnect_delay: Duration) -> StreamSettings {
+        StreamSettings {
+            ws_url: self.endpoints.ws_url(&self.helius_api_key),
+            commitment: self.transactions.commitment.to_commitment_config(),
+            reconnect_delay,
+        }
+    }
+
+    // Feed a streamed on-chain price through the same path as a polled one.
+    // Entry prices and trackers are in USD, so the SOL price is converted first.
+    pub async fn apply_streamed_price(&self, token_address: &str, streamed: StreamedPrice) -> Vec<SellEvent> {
+        let mut executed_sells = Vec::new();
+        let position = match self.positions.read().await.get(token_address) {
+            Some(position) => position.clone(),
+            None => return executed_sells,
+        };
+        let sol_usd = match self.sol_usd_price().await {
+            Ok(sol_usd) => sol_usd,
+            Err(e) => {
+                log::debug!("Dropping streamed tick for {}, no SOL/USD rate: {}", short(token_address), e);
+                return executed_sells;
+            }
+        };
+        
+        let tick = PriceTick {
+            price: streamed.price_sol * sol_usd,
+            timestamp: streamed.timestamp,
+        };
+        log::debug!("Streamed price for {} at slot {}: ${:.8}", short(token_address), streamed.slot, tick.price);
+        self.process_price_tick(&position, &tick, &mut executed_sells).await;
+        executed_sells
+    }
+
+    async fn sol_usd_price(&self) -> Result<Decimal> {
+        if let Some((price, fetched_at)) = *self.sol_usd_cache.read().await {
+            if fetched_at.elapsed() < SOL_USD_CACHE_TTL {
+                return Ok(price);
             }
         }
+        let price = self.get_jupiter_price(token_addresses::SOL).await?;
+        *self.sol_usd_cache.write().await = Some((price, Instant::now()));
+        Ok(price)
+    }
+
+    // Run one price tick through the ladder and exit strategy for a position
+    async fn process_price_tick(&self, position: &Position, tick: &PriceTick, executed_sells: &mut Vec<SellEvent>) {
+        // A zero price is a failed lookup, not a market move; it would read as -100%
+        if tick.price <= Decimal::ZERO {
+            log::debug!("Ignoring non-positive price tick for {}", short(&position.token_address));
+            return;
+        }
         
-        match position.strategy {
-            StrategyType::Conservative => {
-                let profit_percent = self.calculate_profit_percent(position.entry_price, current_price);
-                profit_percent >= Decimal::from(15) || profit_percent <= Decimal::from(-5)
-            },
-            StrategyType::Aggressive => {
-                let profit_percent = self.calculate_profit_percent(position.entry_price, current_price);
-                profit_percent >= Decimal::from(50) || profit_percent <= Decimal::from(-15)
+        let tracker = self.update_ath_tracker(position, tick).await;
+        
+        // Laddered take-profits fire once each, before the strategy trails the rest
+        if !self.execute_take_profit_tiers(position, &tracker, tick, executed_sells).await {
+            return;
+        }
+        
+        let (reason, started, sell_result) = match self.evaluate_exit_strategy(position, &tracker, tick).await {
+            ExitDecision::Hold => return,
+            ExitDecision::SellAll(reason) => {
+                log::info!("Exit triggered for {}: {}", short(&position.token_address), reason);
+                // Position and tracker are closed out by the sell itself
+                let started = Instant::now();
+                let result = self.sell_and_close(&position.token_address, reason.clone()).await;
+                (reason, started, result)
             },
-            StrategyType::ConservativeATH | StrategyType::AggressiveATH => {
-                self.check_ath_pullback_exit(&position.token_address, current_price).await
+            ExitDecision::SellFraction(fraction, reason) => {
+                log::info!(
+                    "Partial exit triggered for {}: selling {:.1}% - {}",
+                    short(&position.token_address), fraction * Decimal::from(100), reason
+                );
+                // Claimed before sending, as ladder tiers are, and released if the sell fails
+                if !self.set_exit_fired(&position.token_address, &reason.rule, true).await {
+                    return;
+                }
+                let started = Instant::now();
+                let result = self.sell_fraction(&position.token_address, fraction, reason.clone()).await;
+                if !result.as_ref().is_ok_and(|trade| trade.success) {
+                    self.set_exit_fired(&position.token_address, &reason.rule, false).await;
+                }
+                (reason, started, result)
             },
-        }
+        };
+        
+        executed_sells.push(SellEvent::new(position, reason, sell_result, started));
     }
 
-    // ATH pullback exit logic
-    async fn check_ath_pullback_exit(&self, token_address: &str, current_price: Decimal) -> bool {
-        let trackers = self.ath_tracker.read().await;
-        if let Some(tracker) = trackers.get(token_address) {
-            let profit_percent = self.calculate_profit_percent(tracker.entry_price, current_price);
-            let pullback_from_ath = if tracker.ath_price > Decimal::ZERO {
-                (tracker.ath_price - current_price) / tracker.ath_price * Decimal::from(100)
-            } else {
-                Decimal::ZERO
+    // Fire every due ladder tier for a position. Returns false when the exit strategy should
+    // sit this tick out: the position is sold out, or a tier sell failed.
+    async fn execute_take_profit_tiers(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick, executed_sells: &mut Vec<SellEvent>) -> bool {
+        for tier_index in strategy::due_take_profit_tiers(position, tick.price) {
+            // Re-read the position: earlier tiers in this pass reduce what is left
+            let current = match self.positions.read().await.get(&position.token_address) {
+                Some(current) => current.clone(),
+                None => return false,
             };
+            let tier = current.take_profit_ladder[tier_index];
+            let amount = current.tier_sell_amount(tier_index);
             
-            let should_exit = profit_percent >= tracker.min_profit_percent 
-                && pullback_from_ath >= tracker.pullback_percent;
+            log::info!(
+                "Take-profit tier {} hit for {}: +{}% reached, selling {}% ({} tokens)",
+                tier_index + 1, short(&position.token_address), tier.profit_percent, tier.sell_percent, amount
+            );
             
-            if should_exit {
-                log::info!(
-                    "ATH pullback triggered for {}: Profit: {:.2}%, Pullback: {:.2}%, ATH: ${:.8}",
-                    &token_address[..8], profit_percent, pullback_from_ath, tracker.ath_price
-                );
+            // Claim the tier before sending, so a crash mid-sell can't fire it a second time
+            if !self.set_tier_completed(&position.token_address, tier_index, true).await {
+                continue;
+            }
+            if amount > 0 {
+                let reason = ExitReason::triggered(ExitRule::TakeProfitTier(tier_index), &current, tracker, tick);
+                let started = Instant::now();
+                let sell_result = self.sell_partial(&position.token_address, amount, reason.clone()).await;
+                let event = SellEvent::new(&current, reason, sell_result, started);
+                let succeeded = event.trade.success;
+                executed_sells.push(event);
+                
+                // A failed tier is released and retried next cycle; nothing else sells this tick
+                if !succeeded {
+                    self.set_tier_completed(&position.token_address, tier_index, false).await;
+                    return false;
+                }
             }
             
-            should_exit
-        } else {
-            false
+            // The sell checks the wallet balance and closes the position itself if this
+            // tier took everything that was left
+            if !self.positions.read().await.contains_key(&position.token_address) {
+                return false;
+            }
+        }
+        
+        true
+    }
+
+    // Mark a ladder tier done (or release it) and persist. Returns false if it was already
+    // in that state.
+    async fn set_tier_completed(&self, token_address: &str, tier_index: usize, completed: bool) -> bool {
+        self.update_position(token_address, |position| {
+            set_membership(&mut position.completed_tiers, tier_index, completed)
+        }).await
+    }
+
+    // Same for a partial exit rule
+    async fn set_exit_fired(&self, token_address: &str, rule: &ExitRule, fired: bool) -> bool {
+        self.update_position(token_address, |position| {
+            set_membership(&mut position.fired_exits, rule.clone(), fired)
+        }).await
+    }
+
+    // Apply a change to a tracked position, persisting it if the change reports one
+    async fn update_position(&self, token_address: &str, change: impl FnOnce(&mut Position) -> bool) -> bool {
+        let mut positions = self.positions.write().await;
+        let Some(position) = positions.get_mut(token_address) else {
+            return false;
+        };
+        if !change(position) {
+            return false;
+        }
+        self.persist(&JournalEntry::UpsertPosition { position: Box::new(position.clone()) });
+        true
+    }
+
+    // Register a user-defined exit strategy, selectable with StrategyType::Registered(name)
+    pub async fn register_exit_strategy(&self, strategy: Arc<dyn ExitStrategy>) {
+        let name = strategy.name().to_string();
+        log::info!("Registered exit strategy: {}", name);
+        self.exit_strategies.write().await.insert(name, strategy);
+    }
+
+    async fn resolve_exit_strategy(&self, strategy: &StrategyType) -> Option<Arc<dyn ExitStrategy>> {
+        match strategy {
+            StrategyType::Registered(name) => self.exit_strategies.read().await.get(name).cloned(),
+            builtin => builtin.exit_strategy(),
+        }
+    }
+
+    // Record the tick on the position's ATH tracker and return the updated tracker
+    async fn update_ath_tracker(&self, position: &Position, tick: &PriceTick) -> ATHTracker {
+        let tracker = {
+            let mut trackers = self.ath_tracker.write().await;
+            let created = !trackers.contains_key(&position.token_address);
+            let tracker = trackers.entry(position.token_address.clone())
+                .or_insert_with(|| ATHTracker::new(position.entry_price, &position.strategy.params()));
+            let new_ath = tracker.update(tick);
+            if new_ath {
+                log::debug!("New ATH for {}: ${:.8}", short(&position.token_address), tick.price);
+            }
+            // A new ATH is journaled at once; other ticks only move the last price, so the
+            // writer coalesces them and journals the latest per token on its flush cadence
+            if created || new_ath {
+                self.persist(&JournalEntry::UpsertTracker {
+                    token_address: position.token_address.clone(),
+                    tracker: tracker.clone(),
+                });
+            } else if let Err(e) = self.state_store.stage_tracker(&position.token_address, tracker) {
+                log::error!("Failed to persist state change: {}", e);
+            }
+            tracker.clone()
+        };
+        self.compact_state_if_needed().await;
+        tracker
+    }
+
+    // Strategy evaluation with ATH logic
+    async fn evaluate_exit_strategy(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision {
+        match self.resolve_exit_strategy(&position.strategy).await {
+            Some(exit_strategy) => strategy::evaluate_exit(position, tracker, tick, exit_strategy.as_ref()),
+            None => {
+                log::warn!(
+                    "No exit strategy registered for {:?} on {}, holding",
+                    position.strategy, short(&position.token_address)
+                );
+                ExitDecision::Hold
+            }
+        }
+    }
+
+    // Fast sell implementation
+    pub async fn sell_position(&self, token_address: &str) -> Result<TradeResult> {
+        self.sell_and_close(token_address, ExitReason::manual()).await
+    }
+
+    // Sell the full balance and, on success, journal the round trip and drop the position
+    async fn sell_and_close(&self, token_address: &str, exit_reason: ExitReason) -> Result<TradeResult> {
+        let _sell_guard = self.lock_sells(token_address).await;
+        self.sell_and_close_locked(token_address, exit_reason).await
+    }
+
+    // Hold the token's sell lock while calling this
+    async fn sell_and_close_locked(&self, token_address: &str, exit_reason: ExitReason) -> Result<TradeResult> {
+        let start_time = Instant::now();
+        
+        log::info!("Starting sell for {}", short(token_address));
+        
+        let token_balance = self.get_token_balance(token_address).await?;
+        if token_balance == 0 {
+            return Err(anyhow!("No tokens to sell"));
+        }
+        
+        log::info!("Selling {} tokens", token_balance);
+        
+        // Sell through whichever venue execute_sell picks
+        let result = self.execute_sell(token_address, token_balance).await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(execution) => {
+                let fill = self.read_fill(&execution, token_address).await;
+                let lamports_received = fill.map(|fill| fill.sol_received_lamports()).unwrap_or(execution.amount_out);
+                let sol_received = lamports_received as f64 / LAMPORTS_PER_SOL as f64;
+                self.record_closed_trade(token_address, &execution, sol_received, exit_reason).await;
+                self.remove_position(token_address).await;
+                
+                Ok(TradeResult {
+                    signature: execution.signature,
+                    success: true,
+                    error: None,
+                    execution_time_ms: execution_time,
+                    platform_used: Some(execution.platform),
+                    tokens_received: None,
+                    sol_spent: None,
+                    sol_received: Some(sol_received),
+                    tip_lamports: execution.tip_lamports,
+                    fee_lamports: fill.map(|fill| fill.fee_lamports),
+                    slippage_percent: fill.and_then(|_| fill::slippage_percent(execution.amount_out, lamports_received)),
+                })
+            },
+            Err(failure) => Ok(TradeResult::failure(failure.platform, failure.error.to_string(), start_time)),
+        }
+    }
+
+    // Sell part of the current balance and keep the position open
+    async fn sell_fraction(&self, token_address: &str, fraction: Decimal, exit_reason: ExitReason) -> Result<TradeResult> {
+        if fraction >= Decimal::ONE {
+            return self.sell_and_close(token_address, exit_reason).await;
+        }
+        if fraction <= Decimal::ZERO {
+            return Err(anyhow!("Sell fraction must be positive, got {}", fraction));
+        }
+        
+        let _sell_guard = self.lock_sells(token_address).await;
+        let token_balance = self.get_token_balance(token_address).await?;
+        let amount = (Decimal::from(token_balance) * fraction).floor().to_u64().unwrap_or(0);
+        if amount == 0 {
+            return Err(anyhow!("Selling {} of {} tokens rounds to zero", fraction, token_balance));
+        }
+        
+        self.sell_partial_locked(token_address, amount, exit_reason).await
+    }
+
+    // Sell a specific raw token amount; selling the whole balance closes the position
+    pub async fn sell_amount(&self, token_address: &str, amount: u64) -> Result<TradeResult> {
+        self.sell_partial(token_address, amount, ExitReason::manual()).await
+    }
+
+    async fn sell_partial(&self, token_address: &str, amount: u64, exit_reason: ExitReason) -> Result<TradeResult> {
+        let _sell_guard = self.lock_sells(token_address).await;
+        self.sell_partial_locked(token_address, amount, exit_reason).await
+    }
+
+    // Hold the token's sell lock while calling this
+    async fn sell_partial_locked(&self, token_address: &str, amount: u64, exit_reason: ExitReason) -> Result<TradeResult> {
+        if amount == 0 {
+            return Err(anyhow!("Sell amount must be positive"));
+        }
+        
+        let start_time = Instant::now();
+        
+        let token_balance = self.get_token_balance(token_address).await?;
+        if amount >= token_balance {
+            return self.sell_and_close_locked(token_address, exit_reason).await;
+        }
+        
+        log::info!("Selling {} of {} tokens ({})", amount, token_balance, exit_reason);
+        
+        let result = self.execute_sell(token_address, amount).await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(execution) => {
+                let fill = self.read_fill(&execution, token_address).await;
+                let lamports_received = fill.map(|fill| fill.sol_received_lamports()).unwrap_or(execution.amount_out);
+                let sol_received = lamports_received as f64 / LAMPORTS_PER_SOL as f64;
+                let fee = match fill {
+                    Some(fill) => fill.fee_lamports,
+                    None => self.fetch_transaction_fee(&execution.signature).await,
+                };
+                
+                {
+                    let mut positions = self.positions.write().await;
+                    if let Some(position) = positions.get_mut(token_address) {
+                        position.amount_tokens = token_balance - amount;
+                        position.realized_sol_out += sol_received;
+                        position.realized_fee_lamports += fee;
+                        position.realized_tip_lamports += execution.tip_lamports.unwrap_or(0);
+                        self.persist(&JournalEntry::UpsertPosition { position: Box::new(position.clone()) });
+                    }
+                }
+                
+                Ok(TradeResult {
+                    signature: execution.signature,
+                    success: true,
+                    error: None,
+                    execution_time_ms: execution_time,
+                    platform_used: Some(execution.platform),
+                    tokens_received: None,
+                    sol_spent: None,
+                    sol_received: Some(sol_received),
+                    tip_lamports: execution.tip_lamports,
+                    fee_lamports: Some(fee),
+                    slippage_percent: fill.and_then(|_| fill::slippage_percent(execution.amount_out, lamports_received)),
+                })
+            },
+            Err(failure) => Ok(TradeResult::failure(failure.platform, failure.error.to_string(), start_time)),
+        }
+    }
+
+    // Serialise sells of one token: the daemon, streamed ticks and the control API can all
+    // decide to sell at once, and each must see the balance the previous sell left behind
+    async fn lock_sells(&self, token_address: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.sell_locks.lock().await;
+            // Only the map holds an idle lock, so it can go without racing a waiter
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(token_address.to_string()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+
+    // Journal a completed round trip for a tracked position
+    async fn record_closed_trade(&self, token_address: &str, sell: &Execution, sol_out: f64, exit_reason: ExitReason) {
+        let position = match self.positions.read().await.get(token_address) {
+            Some(position) => position.clone(),
+            None => {
+                log::debug!("Sold untracked token {}, nothing to journal", short(token_address));
+                return;
+            }
+        };
+        
+        let buy_fee = self.fetch_transaction_fee(&position.buy_signature).await;
+        let sell_fee = self.fetch_transaction_fee(&sell.signature).await;
+        let tips = position.realized_tip_lamports + sell.tip_lamports.unwrap_or(0);
+        let exit_time = Utc::now();
+        
+        let trade = ClosedTrade {
+            token_address: token_address.to_string(),
+            buy_signature: position.buy_signature.clone(),
+            sell_signature: sell.signature.clone(),
+            sol_in: position.sol_in,
+            sol_out: position.realized_sol_out + sol_out,
+            fees_sol: (buy_fee + sell_fee + position.realized_fee_lamports + tips) as f64 / LAMPORTS_PER_SOL as f64,
+            entry_time: position.entry_time,
+            exit_time,
+            hold_time_secs: (exit_time - position.entry_time).num_seconds(),
+            strategy: position.strategy.clone(),
+            exit_reason,
+        };
+        
+        if let Err(e) = self.trade_journal.record(trade) {
+            log::error!("Failed to record closed trade for {}: {}", short(token_address), e);
+        }
+    }
+
+    // Network fee paid by a confirmed transaction, 0 if it cannot be fetched
+    async fn fetch_transaction_fee(&self, signature: &str) -> u64 {
+        if PaperAccount::is_paper_signature(signature) {
+            return paper::SIMULATED_FEE_LAMPORTS;
+        }
+        
+        let signature = match Signature::from_str(signature) {
+            Ok(signature) => signature,
+            Err(_) => return 0,
+        };
+        
+        match self.rpc_client.get_transaction_with_config(&signature, Self::transaction_config()) {
+            Ok(tx) => tx.transaction.meta.map(|meta| meta.fee).unwrap_or(0),
+            Err(e) => {
+                log::warn!("Could not fetch fee for {}: {}", signature, e);
+                0
+            }
+        }
+    }
+
+    // Balance changes of a landed live trade, None in paper mode or if the transaction
+    // can't be read. getTransaction can lag the confirmation we waited on, so retry briefly.
+    async fn read_fill(&self, execution: &Execution, token_address: &str) -> Option<Fill> {
+        if self.paper.is_some() {
+            return None;
+        }
+        
+        let (signature, mint) = match (Signature::from_str(&execution.signature), Pubkey::from_str(token_address)) {
+            (Ok(signature), Ok(mint)) => (signature, mint),
+            _ => return None,
+        };
+        
+        for attempt in 1..=FILL_FETCH_ATTEMPTS {
+            match self.rpc_client.get_transaction_with_config(&signature, Self::transaction_config()) {
+                Ok(tx) => {
+                    let fill = tx.transaction.meta
+                        .ok_or_else(|| anyhow!("no metadata"))
+                        .and_then(|meta| Fill::from_meta(&meta, &self.keypair.pubkey(), &mint));
+                    return match fill {
+                        Ok(fill) => {
+                            log::debug!("Fill for {}: {:?}", signature, fill);
+                            Some(fill)
+                        },
+                        Err(e) => {
+                            log::warn!("Could not read fill for {}, using the quote: {}", signature, e);
+                            None
+                        }
+                    };
+                },
+                Err(e) if attempt == FILL_FETCH_ATTEMPTS => {
+                    log::warn!("Could not fetch {} for its fill, using the quote: {}", signature, e);
+                },
+                Err(_) => tokio::time::sleep(FILL_FETCH_DELAY).await,
+            }
+        }
+        None
+    }
+
+    // getTransaction settings that also return v0 transactions
+    fn transaction_config() -> RpcTransactionConfig {
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            // getTransaction does not serve processed commitment
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        }
+    }
+
+    pub fn closed_trades(&self) -> Vec<ClosedTrade> {
+        self.trade_journal.trades()
+    }
+
+    // Route a sell to the virtual wallet in paper mode, otherwise to Jupiter
+    async fn execute_sell(&self, token_address: &str, amount: u64) -> Result<Execution, SellFailure> {
+        match &self.paper {
+            Some(paper) => {
+                let (platform, lamports_out) = self.paper_sell_quote(token_address, amount).await
+                    .map_err(|error| SellFailure { platform: None, error })?;
+                let signature = paper.fill_sell(token_address, amount, lamports_out)
+                    .map_err(SellFailure::on(platform.clone()))?;
+                log::info!(
+                    "Paper sell filled on {:?}: {} tokens of {} -> {} lamports | Paper balance: {:.6} SOL",
+                    platform, amount, short(token_address), lamports_out, paper.sol_balance()
+                );
+                Ok(Execution {
+                    signature,
+                    amount_out: lamports_out,
+                    platform,
+                    tip_lamports: None,
+                })
+            },
+            None => match self.fetch_bonding_curve(token_address).await {
+                // Still on the curve: sell to it directly, Jupiter may not route it yet
+                Ok(curve) if !curve.complete => self.sell_pumpfun(token_address, &curve, amount).await
+                    .map_err(SellFailure::on(Platform::PumpFun)),
+                _ => match self.get_sell_quote(token_address, amount).await {
+                    Ok(quote) => self.sell_jupiter(quote).await.map_err(SellFailure::on(Platform::Jupiter)),
+                    // No Jupiter route yet: sell into the Raydium pool if there is one
+                    Err(e) => match self.find_raydium_pool(token_address).await {
+                        Ok(pool) => {
+                            log::warn!("Jupiter quote failed for {} ({}), selling on Raydium", short(token_address), e);
+                            self.sell_raydium(token_address, &pool, amount).await.map_err(SellFailure::on(Platform::Raydium))
+                        },
+                        Err(_) => Err(SellFailure::on(Platform::Jupiter)(e)),
+                    },
+                },
+            },
         }
     }
 
-    // Fast sell implementation
-    pub async fn sell_position(&self, token_address: &str) -> Result<TradeResult> {
-        let start_time = Instant::now();
-        
-        log::info!("Starting sell for {}", &token_address[..8]);
-        
-        let token_balance = self.get_token_balance(token_address).await?;
-        if token_balance == 0 {
-            return Err(anyhow!("No tokens to sell"));
+    // Lamports a live sell would quote, routed the same way: the bonding curve while it
+    // still trades, else Jupiter, else a Raydium pool
+    async fn paper_sell_quote(&self, token_address: &str, amount: u64) -> Result<(Platform, u64)> {
+        if let Ok(curve) = self.fetch_bonding_curve(token_address).await {
+            if !curve.complete {
+                let global = self.fetch_pump_global().await?;
+                let mint = self.fetch_mint(token_address).await?;
+                let quote = curve.sell_quote(&global, self.amount_after_transfer_fee(&mint, amount)?)?;
+                return Ok((Platform::PumpFun, quote.sol_lamports));
+            }
         }
         
-        log::info!("Selling {} tokens", token_balance);
-        
-        // Use Jupiter for selling (most reliable)
-        let result = self.sell_jupiter(token_address, token_balance).await;
-        let execution_time = start_time.elapsed().as_millis() as u64;
-        
-        match result {
-            Ok(signature) => Ok(TradeResult {
-                signature,
-                success: true,
-                error: None,
-                execution_time_ms: execution_time,
-                platform_used: Platform::Jupiter,
-                tokens_received: None,
-                sol_spent: None,
-            }),
-            Err(e) => Ok(TradeResult {
-                signature: String::new(),
-                success: false,
-                error: Some(e.to_string()),
-                execution_time_ms: execution_time,
-                platform_used: Platform::Jupiter,
-                tokens_received: None,
-                sol_spent: None,
-            }),
+        match self.get_sell_quote(token_address, amount).await {
+            Ok((_, lamports_out)) => Ok((Platform::Jupiter, lamports_out)),
+            Err(e) => match self.find_raydium_pool(token_address).await {
+                Ok(pool) => {
+                    let mint = self.fetch_mint(token_address).await?;
+                    let amount_in = self.amount_after_transfer_fee(&mint, amount)?;
+                    let quote = self.raydium_quote(&pool, &mint.address, amount_in).await?;
+                    Ok((Platform::Raydium, quote.amount_out))
+                },
+                Err(_) => Err(e),
+            },
         }
     }
 
-    // Jupiter sell implementation
-    async fn sell_jupiter(&self, token_address: &str, amount: u64) -> Result<String> {
-        let quote_url = format!(
-            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps=500",
-            self.jupiter_endpoint,
-            token_address,
-            token_addresses::SOL,
-            amount
-        );
-        
-        let quote: Value = tokio::task::spawn_blocking(move || {
-            ureq::get(&quote_url)
-                .timeout(Duration::from_secs(15))
-                .call()
-        }).await??.into_json()?;
-        
+    // Jupiter sell implementation, returns the signature and quoted lamports out
+    async fn sell_jupiter(&self, (quote, lamports_out): (Value, u64)) -> Result<Execution> {
         let swap_data = json!({
             "userPublicKey": self.keypair.pubkey().to_string(),
             "quoteResponse": quote,
@@ -792,24 +2628,53 @@ impl FastMemeTrader {
         });
         
         let swap_data_str = serde_json::to_string(&swap_data)?;
-        let swap_url = format!("{}/swap", self.jupiter_endpoint);
+        let swap_url = format!("{}/swap", self.endpoints.jupiter_api);
         
-        let swap_result: Value = tokio::task::spawn_blocking(move || {
-            ureq::post(&swap_url)
-                .timeout(Duration::from_secs(15))
-                .set("Content-Type", "application/json")
-                .send_string(&swap_data_str)
-        }).await??.into_json()?;
+        let response = self.http_post_json(swap_url, swap_data_str, Duration::from_secs(15)).await?;
+        if !response.is_success() {
+            return Err(anyhow!("Jupiter swap API error ({}): {}", response.status, response.body));
+        }
+        let swap_result = response.json()?;
         
         let transaction_b64 = swap_result["swapTransaction"].as_str()
             .ok_or_else(|| anyhow!("No transaction returned"))?;
         
-        self.execute_transaction_b64(transaction_b64).await
+        let submission = self.execute_transaction_b64(transaction_b64, self.resolve_tip(None)).await?;
+        Ok(Execution {
+            signature: submission.signature.to_string(),
+            amount_out: lamports_out,
+            platform: Platform::Jupiter,
+            tip_lamports: submission.tip_lamports,
+        })
+    }
+
+    // Token -> SOL quote, returns the raw quote and its lamports out
+    async fn get_sell_quote(&self, token_address: &str, amount: u64) -> Result<(Value, u64)> {
+        let quote_url = format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
+            self.endpoints.jupiter_api,
+            token_address,
+            token_addresses::SOL,
+            amount,
+            self.limits.sell_slippage_bps
+        );
+        
+        let response = self.http_get(quote_url, Duration::from_secs(15)).await?;
+        if !response.is_success() {
+            return Err(anyhow!("Jupiter quote API error ({}): {}", response.status, response.body));
+        }
+        let quote = response.json()?;
+        
+        let lamports_out = quote["outAmount"].as_str()
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| anyhow!("No outAmount in sell quote"))?;
+        
+        Ok((quote, lamports_out))
     }
 
     // Improved priority fee calculation
     async fn calculate_priority_fee(&self) -> u64 {
-        let url = format!("https://mainnet.helius-rpc.com/?api-key={}", self.helius_api_key);
+        let url = self.endpoints.rpc_url(&self.helius_api_key);
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": "1",
@@ -821,25 +2686,18 @@ impl FastMemeTrader {
         
         let request_body_str = serde_json::to_string(&request_body).unwrap_or_default();
         
-        if let Ok(response) = tokio::task::spawn_blocking(move || {
-            ureq::post(&url)
-                .timeout(Duration::from_secs(5))
-                .set("Content-Type", "application/json")
-                .send_string(&request_body_str)
-        }).await {
-            if let Ok(response) = response {
-                if let Ok(data) = response.into_json::<Value>() {
-                    if let Some(fee) = data["result"]["priorityFeeEstimate"].as_f64() {
-                        let calculated_fee = (fee as u64).min(self.max_priority_fee);
-                        log::debug!("Calculated priority fee: {} microlamports", calculated_fee);
-                        return calculated_fee;
-                    }
+        if let Ok(response) = self.http_post_json(url, request_body_str, Duration::from_secs(5)).await {
+            if let Ok(data) = response.json() {
+                if let Some(fee) = data["result"]["priorityFeeEstimate"].as_f64() {
+                    let calculated_fee = (fee as u64).min(self.fees.max_priority_fee);
+                    log::debug!("Calculated priority fee: {} microlamports", calculated_fee);
+                    return calculated_fee;
                 }
             }
         }
         
         log::warn!("Failed to get priority fee, using fallback");
-        150_000
+        self.fees.fallback_priority_fee
     }
 
     // Improved PumpFun detection
@@ -853,20 +2711,21 @@ impl FastMemeTrader {
             _ => {}
         }
         
-        let url = format!("https://frontend-api.pump.fun/coins/{}", token_address);
+        let url = format!("{}/coins/{}", self.endpoints.pump_frontend_api, token_address);
         
-        match tokio::task::spawn_blocking(move || {
-            ureq::get(&url)
-                .timeout(Duration::from_secs(3))
-                .call()
-        }).await {
-            Ok(Ok(response)) => {
-                let is_pumpfun = response.status() == 200;
-                log::debug!("PumpFun check for {}: {}", &token_address[..8], is_pumpfun);
-                is_pumpfun
+        match self.http_get(url, Duration::from_secs(3)).await {
+            Ok(response) if response.status == 200 => {
+                // Migrated coins are still listed, but their curve no longer trades
+                let on_curve = matches!(self.fetch_bonding_curve(token_address).await, Ok(curve) if !curve.complete);
+                log::debug!("PumpFun check for {}: listed, on curve: {}", short(token_address), on_curve);
+                on_curve
+            },
+            Ok(_) => {
+                log::debug!("PumpFun check for {}: not listed", short(token_address));
+                false
             },
             _ => {
-                log::debug!("PumpFun check failed for {}, assuming not PumpFun", &token_address[..8]);
+                log::debug!("PumpFun check failed for {}, assuming not PumpFun", short(token_address));
                 false
             }
         }
@@ -874,23 +2733,32 @@ impl FastMemeTrader {
 
     // Price fetching with multiple sources
     async fn get_current_price(&self, token_address: &str) -> Result<Decimal> {
-        // Try Jupiter price API first
-        if let Ok(price) = self.get_jupiter_price(token_address).await {
+        let feed = self.positions.read().await.get(token_address).and_then(|position| position.price_feed.clone());
+        
+        // Raydium pools and PumpFun curves are priced from chain; Jupiter lags or lacks them.
+        // Positions without a feed were routed through Jupiter and have no account to read.
+        let on_chain = match feed {
+            Some(feed @ PriceFeed::RaydiumVaults { .. }) => Some(self.get_raydium_vault_price(&feed).await),
+            Some(PriceFeed::BondingCurve { .. }) => Some(self.get_bonding_curve_price(token_address).await),
+            None => None,
+        };
+        if let Some(Ok(price)) = on_chain {
             return Ok(price);
         }
         
-        // Fallback to simple estimation
-        Ok(Decimal::from(0))
+        // Try Jupiter price API next
+        match self.get_jupiter_price(token_address).await {
+            Ok(price) if price > Decimal::ZERO => Ok(price),
+            Ok(price) => Err(anyhow!("Non-positive price {} for {}", price, short(token_address))),
+            // A missing price must never reach the strategies as a -100% move
+            Err(e) => Err(anyhow!("No price source answered for {}: {}", short(token_address), e)),
+        }
     }
     
     async fn get_jupiter_price(&self, token_address: &str) -> Result<Decimal> {
-        let url = format!("https://price.jup.ag/v4/price?ids={}", token_address);
+        let url = format!("{}/price?ids={}", self.endpoints.jupiter_price_api, token_address);
         
-        let response: Value = tokio::task::spawn_blocking(move || {
-            ureq::get(&url)
-                .timeout(Duration::from_secs(5))
-                .call()
-        }).await??.into_json()?;
+        let response = self.http_get(url, Duration::from_secs(5)).await?.json()?;
         
         if let Some(price_value) = response["data"][token_address]["price"].as_f64() {
             Ok(Decimal::try_from(price_value)?)
@@ -900,8 +2768,11 @@ impl FastMemeTrader {
     }
 
     async fn get_token_balance(&self, token_address: &str) -> Result<u64> {
-        let mint = Pubkey::from_str(token_address)?;
-        let ata = get_associated_token_address(&self.keypair.pubkey(), &mint);
+        if let Some(paper) = &self.paper {
+            return Ok(paper.token_balance(token_address));
+        }
+        
+        let ata = self.fetch_mint(token_address).await?.associated_token_address(&self.keypair.pubkey());
         
         match self.rpc_client.get_token_account_balance(&ata) {
             Ok(balance) => Ok(balance.amount.parse()?),
@@ -909,11 +2780,8 @@ impl FastMemeTrader {
         }
     }
 
-    fn calculate_profit_percent(&self, entry_price: Decimal, current_price: Decimal) -> Decimal {
-        if entry_price == Decimal::ZERO {
-            return Decimal::ZERO;
-        }
-        (current_price - entry_price) / entry_price * Decimal::from(100)
+    fn calculate_profit_percent(&self, entry_price: Option<Decimal>, current_price: Decimal) -> Option<Decimal> {
+        entry_price.map(|entry_price| strategy::profit_percent(entry_price, current_price))
     }
 
     // Status and monitoring methods
@@ -921,38 +2789,69 @@ impl FastMemeTrader {
         let trackers = self.ath_tracker.read().await;
         if let Some(tracker) = trackers.get(token_address) {
             let profit_percent = self.calculate_profit_percent(tracker.entry_price, tracker.last_price);
-            let pullback_from_ath = if tracker.ath_price > Decimal::ZERO {
-                (tracker.ath_price - tracker.last_price) / tracker.ath_price * Decimal::from(100)
-            } else {
-                Decimal::ZERO
+            let pullback_from_ath = strategy::pullback_from_ath(tracker.ath_price, tracker.last_price);
+            let stop = match tracker.max_drawdown_percent {
+                Some(max_drawdown) => format!("-{}%", max_drawdown),
+                None => "none".to_string(),
             };
             
             Some(format!(
-                "Entry: ${:.8} | ATH: ${:.8} | Current: ${:.8} | P&L: {:.2}% | Pullback: {:.2}%",
-                tracker.entry_price, tracker.ath_price, tracker.last_price, 
-                profit_percent, pullback_from_ath
+                "Entry: {} | ATH: ${:.8} | Current: ${:.8} | P&L: {} | Pullback: {:.2}% | Stop: {}",
+                format_entry_price(tracker.entry_price), tracker.ath_price, tracker.last_price,
+                format_profit_percent(profit_percent), pullback_from_ath, stop
             ))
         } else {
             None
         }
     }
 
-    pub async fn list_positions(&self) -> Vec<String> {
-        let positions = self.positions.read().await;
-        let mut result = Vec::new();
-        
-        for (token, position) in positions.iter() {
-            if let Ok(current_price) = self.get_current_price(token).await {
-                let profit_percent = self.calculate_profit_percent(position.entry_price, current_price);
-                result.push(format!(
-                    "{}: {:.0} tokens | Entry: ${:.8} | Current: ${:.8} | P&L: {:.2}% | Strategy: {:?}",
-                    &token[..8], position.amount_tokens, position.entry_price, current_price, 
-                    profit_percent, position.strategy
-                ));
-            }
+    pub async fn ath_tracker(&self, token_address: &str) -> Option<ATHTracker> {
+        self.ath_tracker.read().await.get(token_address).cloned()
+    }
+
+    // Every open position with a fresh price; price fields are None if it can't be fetched
+    pub async fn position_statuses(&self) -> Vec<PositionStatus> {
+        let positions: Vec<Position> = self.positions.read().await.values().cloned().collect();
+        let mut statuses = Vec::with_capacity(positions.len());
+        
+        for position in positions {
+            let current_price = self.get_current_price(&position.token_address).await.ok();
+            let profit_percent = current_price
+                .and_then(|price| self.calculate_profit_percent(position.entry_price, price));
+            let tracker = self.ath_tracker(&position.token_address).await;
+            let amount_ui = self.fetch_mint(&position.token_address).await.ok()
+                .map(|mint| mint.ui_amount(position.amount_tokens));
+            statuses.push(PositionStatus {
+                position,
+                amount_ui,
+                current_price,
+                profit_percent,
+                tracker,
+            });
         }
         
-        result
+        statuses
+    }
+
+    // One line per position; positions whose price lookup fails are still listed
+    pub async fn list_positions(&self) -> Vec<String> {
+        self.position_statuses().await.into_iter()
+            .map(|status| {
+                let position = &status.position;
+                let amount = match status.amount_ui {
+                    Some(amount) => format!("{} tokens", amount.normalize()),
+                    None => format!("{} raw units", position.amount_tokens),
+                };
+                let current_price = status.current_price
+                    .map(|price| format!("${:.8}", price))
+                    .unwrap_or_else(|| "n/a".to_string());
+                format!(
+                    "{}: {} | Entry: {} | Current: {} | P&L: {} | Strategy: {:?}",
+                    short(&position.token_address), amount, format_entry_price(position.entry_price), current_price,
+                    format_profit_percent(status.profit_percent), position.strategy
+                )
+            })
+            .collect()
     }
 
     pub async fn emergency_sell_all(&self) -> Vec<TradeResult> {
@@ -964,79 +2863,76 @@ impl FastMemeTrader {
             positions_guard.keys().cloned().collect()
         };
         
-        // Execute sells sequentially for stability
+        // Execute sells sequentially for stability. A successful sell removes its own
+        // position; failed ones stay tracked since the wallet still holds the tokens.
         for token_address in positions {
-            log::warn!("Emergency selling {}", &token_address[..8]);
-            if let Ok(trade_result) = self.sell_position(&token_address).await {
-                results.push(trade_result);
+            log::warn!("Emergency selling {}", short(&token_address));
+            let started = Instant::now();
+            match self.sell_and_close(&token_address, ExitReason::emergency()).await {
+                Ok(trade_result) => results.push(trade_result),
+                Err(e) => results.push(TradeResult::failure(None, e.to_string(), started)),
             }
         }
         
-        // Clear all positions
-        {
-            let mut positions_guard = self.positions.write().await;
-            positions_guard.clear();
-        }
-        {
-            let mut trackers_guard = self.ath_tracker.write().await;
-            trackers_guard.clear();
-        }
-        
-        log::warn!("Emergency sell completed, {} positions liquidated", results.len());
+        let sold = results.iter().filter(|result| result.success).count();
+        log::warn!("Emergency sell completed, {} of {} positions liquidated", sold, results.len());
         results
     }
 
     // Health check
     pub async fn health_check(&self) -> Result<String> {
-        // Check SOL balance
-        let sol_balance = self.rpc_client.get_balance(&self.keypair.pubkey())?;
-        let sol_amount = sol_balance as f64 / LAMPORTS_PER_SOL as f64;
+        Ok(self.health_report().await?.to_string())
+    }
+
+    pub async fn health_report(&self) -> Result<HealthReport> {
+        // Check SOL balance (virtual in paper mode)
+        let sol_amount = match &self.paper {
+            Some(paper) => paper.sol_balance(),
+            None => self.rpc_client.get_balance(&self.keypair.pubkey())? as f64 / LAMPORTS_PER_SOL as f64,
+        };
         
         // Check positions count
         let positions_count = self.positions.read().await.len();
         
         // Test Jupiter connectivity
-        let jupiter_test = self.get_current_price(token_addresses::BONK).await.is_ok();
+        // Query the price API directly: get_current_price also tries the bonding curve
+        let jupiter_test = self.get_jupiter_price(token_addresses::BONK).await.is_ok();
         
         // Test RPC connectivity
         let rpc_test = self.rpc_client.get_latest_blockhash().is_ok();
         
-        Ok(format!(
-            "Health: SOL Balance: {:.6} | Positions: {} | Jupiter: {} | RPC: {}",
-            sol_amount, 
-            positions_count, 
-            if jupiter_test { "✅" } else { "❌" },
-            if rpc_test { "✅" } else { "❌" }
-        ))
+        Ok(HealthReport {
+            paper: self.is_paper(),
+            sol_balance: sol_amount,
+            positions: positions_count,
+            jupiter_ok: jupiter_test,
+            rpc_ok: rpc_test,
+        })
+    }
+
+    pub fn trade_stats(&self) -> TradeStats {
+        self.trade_journal.stats()
     }
     
-    // Performance metrics
+    // Performance metrics: realized results from the trade journal
     pub async fn get_performance_stats(&self) -> String {
-        let positions = self.positions.read().await;
-        let mut total_profit = Decimal::ZERO;
-        let mut winning_trades = 0;
-        let total_trades = positions.len();
+        let stats = self.trade_journal.stats();
+        let active_trades = self.positions.read().await.len();
         
-        for position in positions.values() {
-            if let Ok(current_price) = self.get_current_price(&position.token_address).await {
-                let profit_percent = self.calculate_profit_percent(position.entry_price, current_price);
-                total_profit += profit_percent;
-                if profit_percent > Decimal::ZERO {
-                    winning_trades += 1;
-                }
-            }
+        let mut report = format!(
+            "Performance: Closed Trades: {} | Win Rate: {:.1}% | Realized P&L: {:.6} SOL | Avg Return: {:.2}% | Fees: {:.6} SOL | Active Trades: {}",
+            stats.closed_trades, stats.win_rate(), stats.realized_pnl_sol,
+            stats.avg_return_percent, stats.total_fees_sol, active_trades
+        );
+        if let Some(paper) = &self.paper {
+            let balances = paper.balances();
+            report.push_str(&format!(
+                " | Paper Balance: {:.6} SOL (started with {:.6})",
+                balances.sol_lamports as f64 / LAMPORTS_PER_SOL as f64,
+                balances.starting_lamports as f64 / LAMPORTS_PER_SOL as f64
+            ));
         }
-        
-        let win_rate = if total_trades > 0 {
-            (winning_trades as f64 / total_trades as f64) * 100.0
-        } else {
-            0.0
-        };
-        
-        format!(
-            "Performance: Active Trades: {} | Win Rate: {:.1}% | Avg P&L: {:.2}%",
-            total_trades, win_rate, total_profit / Decimal::from(total_trades.max(1))
-        )
+        report
     }
 }
 
@@ -1056,6 +2952,7 @@ pub async fn example_usage() -> Result<()> {
         amount_sol: 0.01,
         slippage_bps: 100,
         strategy: StrategyType::ConservativeATH,
+        ..Default::default()
     };
     
     let result = trader.buy_fast(config).await;
@@ -1073,4 +2970,667 @@ pub async fn example_usage() -> Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+
+    // Every API is down: price lookups fail rather than return data
+    struct UnavailableHttp;
+
+    impl HttpClient for UnavailableHttp {
+        fn get(&self, _url: &str, _timeout: Duration) -> Result<HttpResponse> {
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+
+        fn post_json(&self, _url: &str, _body: &str, _timeout: Duration) -> Result<HttpResponse> {
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+    }
+
+    // Answers Jupiter quotes with a fixed output and nothing else, recording every GET
+    struct QuoteHttp {
+        out_amount: u64,
+        requested: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl QuoteHttp {
+        fn new(out_amount: u64) -> Arc<Self> {
+            Arc::new(Self { out_amount, requested: std::sync::Mutex::new(Vec::new()) })
+        }
+
+        fn requested(&self) -> Vec<String> {
+            self.requested.lock().unwrap().clone()
+        }
+    }
+
+    impl HttpClient for QuoteHttp {
+        fn get(&self, url: &str, _timeout: Duration) -> Result<HttpResponse> {
+            self.requested.lock().unwrap().push(url.to_string());
+            if url.contains("/jupiter/quote?") {
+                let body = json!({ "outAmount": self.out_amount.to_string(), "routePlan": [] }).to_string();
+                return Ok(HttpResponse { status: 200, body });
+            }
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+
+        fn post_json(&self, _url: &str, _body: &str, _timeout: Duration) -> Result<HttpResponse> {
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+    }
+
+    // Answers the Jupiter price API for SOL only, so on-chain SOL prices convert to USD
+    struct SolPriceHttp;
+
+    impl HttpClient for SolPriceHttp {
+        fn get(&self, url: &str, _timeout: Duration) -> Result<HttpResponse> {
+            if url.ends_with(&format!("/price?ids={}", token_addresses::SOL)) {
+                let body = json!({ "data": { token_addresses::SOL: { "price": 150.0 } } }).to_string();
+                return Ok(HttpResponse { status: 200, body });
+            }
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+
+        fn post_json(&self, _url: &str, _body: &str, _timeout: Duration) -> Result<HttpResponse> {
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+    }
+
+    // A block engine that accepts every bundle and never reports one landed
+    struct PendingBundleHttp;
+
+    impl HttpClient for PendingBundleHttp {
+        fn get(&self, _url: &str, _timeout: Duration) -> Result<HttpResponse> {
+            Ok(HttpResponse { status: 503, body: "unavailable".to_string() })
+        }
+
+        fn post_json(&self, _url: &str, body: &str, _timeout: Duration) -> Result<HttpResponse> {
+            let result = if body.contains("sendBundle") {
+                json!("bundle-1")
+            } else {
+                json!({ "context": { "slot": 1 }, "value": [{ "bundle_id": "bundle-1", "status": "Pending" }] })
+            };
+            Ok(HttpResponse { status: 200, body: json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string() })
+        }
+    }
+
+    // Trader with no reachable RPC, journaling to a fresh temp dir
+    fn trader_with(name: &str, http_client: Arc<dyn HttpClient>, mode: ExecutionMode) -> FastMemeTrader {
+        let state_dir = std::env::temp_dir().join(format!("fast-meme-trader-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        let options = TraderOptions {
+            state_dir,
+            mode,
+            endpoints: Endpoints::all_at("http://127.0.0.1:1"),
+            http_client,
+            ..Default::default()
+        };
+        FastMemeTrader::new_with_options(&Keypair::new().to_base58_string(), "test".to_string(), options).unwrap()
+    }
+
+    // Every API is down as well
+    fn offline_trader(name: &str) -> FastMemeTrader {
+        trader_with(name, Arc::new(UnavailableHttp), ExecutionMode::Live)
+    }
+
+    // Paper trader whose sells fill at the given quote
+    fn paper_trader(name: &str, http_client: Arc<dyn HttpClient>) -> FastMemeTrader {
+        trader_with(name, http_client, ExecutionMode::Paper { starting_sol: 1.0 })
+    }
+
+    async fn open_position(trader: &FastMemeTrader, entry_price: Decimal) -> Position {
+        let strategy = StrategyType::ConservativeATH;
+        let position = Position {
+            token_address: Keypair::new().pubkey().to_string(),
+            entry_price: Some(entry_price),
+            amount_tokens: 1_000_000,
+            entry_time: Utc::now(),
+            strategy: strategy.clone(),
+            buy_signature: "test".to_string(),
+            sol_in: 0.1,
+            realized_sol_out: 0.0,
+            realized_fee_lamports: 0,
+            realized_tip_lamports: 0,
+            initial_amount_tokens: 1_000_000,
+            take_profit_ladder: Vec::new(),
+            completed_tiers: Vec::new(),
+            fired_exits: Vec::new(),
+            time_exits: TimeExitRules::default(),
+            price_feed: None,
+        };
+        let tracker = ATHTracker::new(Some(entry_price), &strategy.params());
+        trader.positions.write().await.insert(position.token_address.clone(), position.clone());
+        trader.ath_tracker.write().await.insert(position.token_address.clone(), tracker);
+        position
+    }
+
+    #[tokio::test]
+    async fn quotes_go_through_the_injected_http_client() {
+        let http = QuoteHttp::new(123_456);
+        let trader = trader_with("injected-http", http.clone(), ExecutionMode::Live);
+        let token = Keypair::new().pubkey().to_string();
+        let config = TradeConfig { token_address: token.clone(), slippage_bps: 0, ..Default::default() };
+
+        let quote = trader.get_jupiter_quote(&config, 5_000).await.unwrap();
+        assert_eq!(quote["outAmount"], "123456");
+        assert_eq!(http.requested(), vec![format!(
+            "http://127.0.0.1:1/jupiter/quote?inputMint={}&outputMint={}&amount=5000&slippageBps=100",
+            token_addresses::SOL, token
+        )]);
+
+        // Error statuses from the client surface as errors
+        let offline = offline_trader("injected-http-offline");
+        let error = offline.get_jupiter_quote(&config, 5_000).await.unwrap_err();
+        assert!(error.to_string().contains("503"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_price_lookup_does_not_trigger_max_drawdown() {
+        let trader = offline_trader("failed-lookup");
+        let position = open_position(&trader, Decimal::ONE).await;
+
+        assert!(trader.get_current_price(&position.token_address).await.is_err());
+        let sells = trader.monitor_positions().await;
+
+        assert!(sells.is_empty());
+        assert!(trader.positions.read().await.contains_key(&position.token_address));
+        assert_eq!(trader.ath_tracker(&position.token_address).await.unwrap().last_price, Decimal::ONE);
+    }
+
+    // getMultipleAccounts response holding SPL token accounts with the given amounts
+    fn token_account_data(amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    // getMultipleAccounts response holding accounts with the given data
+    fn accounts_response(accounts: &[Vec<u8>]) -> Value {
+        let accounts: Vec<Value> = accounts.iter().map(|data| {
+            json!({
+                "lamports": 2_039_280,
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "owner": spl_token::id().to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len(),
+            })
+        }).collect();
+        json!({ "context": { "slot": 1 }, "value": accounts })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn raydium_positions_poll_their_pool_vaults() {
+        let mut trader = trader_with("raydium-poll", Arc::new(SolPriceHttp), ExecutionMode::Live);
+        let (pool, token_vault, sol_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        // AMM v4 pool with token as base and SOL as quote, owing 0.5 SOL to the protocol
+        let mut pool_data = vec![0u8; raydium::AMM_V4_POOL_LEN as usize];
+        pool_data[200..208].copy_from_slice(&500_000_000u64.to_le_bytes());
+        pool_data[336..368].copy_from_slice(token_vault.as_ref());
+        pool_data[368..400].copy_from_slice(sol_vault.as_ref());
+        // 2 SOL against 1,000 whole tokens with 6 decimals, then the same with the pool
+        let vaults = [token_account_data(1_000_000_000), token_account_data(2_000_000_000)];
+        let mocks: solana_rpc_client::mock_sender::MocksMap = [
+            accounts_response(&vaults),
+            accounts_response(&[vaults[0].clone(), vaults[1].clone(), pool_data]),
+        ]
+            .into_iter()
+            .map(|response| (solana_client::rpc_request::RpcRequest::GetMultipleAccounts, response))
+            .collect();
+        trader.rpc_client = RpcClient::new_mock_with_mocks_map("succeeds", mocks);
+        let position = open_position(&trader, Decimal::ONE).await;
+        let mut feed = PriceFeed::RaydiumVaults {
+            pool: None,
+            token_vault: token_vault.to_string(),
+            sol_vault: sol_vault.to_string(),
+            token_decimals: 6,
+        };
+        trader.positions.write().await.get_mut(&position.token_address).unwrap().price_feed = Some(feed.clone());
+
+        let price = trader.get_current_price(&position.token_address).await.unwrap();
+        assert_eq!(price, Decimal::new(2, 3) * Decimal::from(150));
+
+        // What the vaults owe the protocol is left out, as a Raydium quote leaves it out
+        if let PriceFeed::RaydiumVaults { pool: feed_pool, .. } = &mut feed {
+            *feed_pool = Some(pool.to_string());
+        }
+        trader.positions.write().await.get_mut(&position.token_address).unwrap().price_feed = Some(feed);
+        let price = trader.get_current_price(&position.token_address).await.unwrap();
+        assert_eq!(price, Decimal::new(15, 4) * Decimal::from(150));
+
+        // Without a feed the same token has no price source at all
+        let unpriced = open_position(&trader, Decimal::ONE).await;
+        assert!(trader.get_current_price(&unpriced.token_address).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn zero_price_tick_is_ignored() {
+        let trader = offline_trader("zero-tick");
+        let position = open_position(&trader, Decimal::ONE).await;
+
+        let mut sells = Vec::new();
+        let tick = PriceTick { price: Decimal::ZERO, timestamp: Utc::now() };
+        trader.process_price_tick(&position, &tick, &mut sells).await;
+
+        assert!(sells.is_empty());
+        assert!(trader.positions.read().await.contains_key(&position.token_address));
+        assert_eq!(trader.ath_tracker(&position.token_address).await.unwrap().last_price, Decimal::ONE);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn built_in_exits_use_the_strategy_params() {
+        let trader = offline_trader("built-in-exits");
+        let entry = Decimal::ONE;
+        let base = open_position(&trader, entry).await;
+
+        for strategy in [StrategyType::Conservative, StrategyType::Aggressive] {
+            let params = strategy.params();
+            let position = Position { strategy: strategy.clone(), ..base.clone() };
+            let tracker = ATHTracker::new(Some(entry), &params);
+            let exit = strategy.exit_strategy().unwrap();
+            let at = |percent: Decimal| PriceTick {
+                price: entry * (Decimal::ONE_HUNDRED + percent) / Decimal::ONE_HUNDRED,
+                timestamp: Utc::now(),
+            };
+
+            let take_profit = params.take_profit_percent.unwrap();
+            let stop_loss = params.stop_loss_percent.unwrap();
+            assert!(matches!(exit.evaluate(&position, &tracker, &at(take_profit)), ExitDecision::SellAll(_)));
+            assert!(matches!(exit.evaluate(&position, &tracker, &at(take_profit - Decimal::ONE)), ExitDecision::Hold));
+            assert!(matches!(exit.evaluate(&position, &tracker, &at(-stop_loss)), ExitDecision::SellAll(_)));
+            assert!(matches!(exit.evaluate(&position, &tracker, &at(Decimal::ONE - stop_loss)), ExitDecision::Hold));
+        }
+    }
+
+    #[test]
+    fn short_never_slices_past_the_input() {
+        assert_eq!(short("So11111111111111111111111111111111111111112"), "So111111");
+        assert_eq!(short("abc"), "abc");
+        assert_eq!(short(""), "");
+        assert_eq!(short("ééééééééé"), "éééééééé");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_emergency_sells_are_reported_and_keep_their_positions() {
+        let trader = offline_trader("emergency-sell");
+        let first = open_position(&trader, Decimal::ONE).await;
+        let second = open_position(&trader, Decimal::ONE).await;
+
+        let results = trader.emergency_sell_all().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| !result.success && result.error.is_some()));
+        let positions = trader.positions.read().await;
+        assert!(positions.contains_key(&first.token_address));
+        assert!(positions.contains_key(&second.token_address));
+        assert_eq!(trader.ath_tracker.read().await.len(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_exit_sells_are_timed_from_when_they_started() {
+        let trader = offline_trader("failed-exit-timing");
+        let position = open_position(&trader, Decimal::ONE).await;
+        let started = Instant::now() - Duration::from_millis(250);
+
+        let event = SellEvent::new(&position, ExitReason::manual(), Err(anyhow!("no balance")), started);
+        assert!(!event.trade.success);
+        assert!(event.trade.execution_time_ms >= 250, "{}ms", event.trade.execution_time_ms);
+        assert!(event.trade.platform_used.is_none());
+    }
+
+    // getAccountInfo answered once with a PumpFun curve that is still trading
+    fn live_curve_mocks() -> solana_client::rpc_client::Mocks {
+        let mut curve = pumpfun::BONDING_CURVE_DISCRIMINATOR.to_vec();
+        for reserve in [1_000_000_000u64, 30_000_000_000, 800_000_000, 0, 1_000_000_000] {
+            curve.extend_from_slice(&reserve.to_le_bytes());
+        }
+        curve.push(0);
+        let account = json!({
+            "lamports": 1_000_000,
+            "data": [base64::engine::general_purpose::STANDARD.encode(&curve), "base64"],
+            "owner": pumpfun::PROGRAM_ID.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": curve.len(),
+        });
+        solana_client::rpc_client::Mocks::from([(
+            solana_client::rpc_request::RpcRequest::GetAccountInfo,
+            json!({ "context": { "slot": 1 }, "value": account }),
+        )])
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_curve_feeds_are_priced_from_the_curve() {
+        let mut trader = trader_with("curve-feed", Arc::new(SolPriceHttp), ExecutionMode::Live);
+        trader.rpc_client = RpcClient::new_mock_with_mocks("succeeds", live_curve_mocks());
+        let position = open_position(&trader, Decimal::ONE).await;
+
+        // A Jupiter-routed position never reads the curve, so Jupiter's silence is final
+        assert!(trader.get_current_price(&position.token_address).await.is_err());
+
+        let curve = pumpfun::bonding_curve_address(&Pubkey::from_str(&position.token_address).unwrap());
+        trader.positions.write().await.get_mut(&position.token_address).unwrap().price_feed =
+            Some(PriceFeed::BondingCurve { curve: curve.to_string() });
+        assert!(trader.get_current_price(&position.token_address).await.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_sells_name_the_venue_they_were_tried_on() {
+        // No curve and no Raydium pool: the Jupiter quote is what failed
+        let trader = offline_trader("sell-venue-jupiter");
+        let token = Keypair::new().pubkey().to_string();
+        let failure = trader.execute_sell(&token, 1_000).await.err().unwrap();
+        assert!(matches!(failure.platform, Some(Platform::Jupiter)));
+
+        // A live curve is sold into directly, so its failure is a PumpFun one
+        let mut trader = trader_with("sell-venue-pumpfun", Arc::new(UnavailableHttp), ExecutionMode::Live);
+        trader.rpc_client = RpcClient::new_mock_with_mocks("succeeds", live_curve_mocks());
+        let failure = trader.execute_sell(&token, 1_000).await.err().unwrap();
+        assert!(matches!(failure.platform, Some(Platform::PumpFun)), "{}", failure.error);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn closed_trades_count_jito_tips_as_fees() {
+        let trader = offline_trader("tips");
+        let position = open_position(&trader, Decimal::ONE).await;
+        {
+            let mut positions = trader.positions.write().await;
+            let position = positions.get_mut(&position.token_address).unwrap();
+            position.buy_signature = format!("{}buy", paper::PAPER_SIGNATURE_PREFIX);
+            // Buy tip plus one partial exit's tip and fee
+            position.realized_tip_lamports = 200_000;
+            position.realized_fee_lamports = 5_000;
+        }
+
+        let sell = Execution {
+            signature: format!("{}sell", paper::PAPER_SIGNATURE_PREFIX),
+            amount_out: 0,
+            platform: Platform::Jupiter,
+            tip_lamports: Some(100_000),
+        };
+        trader.record_closed_trade(&position.token_address, &sell, 0.2, ExitReason::manual()).await;
+
+        let trades = trader.closed_trades();
+        assert_eq!(trades.len(), 1);
+        let expected_lamports = 2 * paper::SIMULATED_FEE_LAMPORTS + 5_000 + 300_000;
+        assert!((trades[0].fees_sol - expected_lamports as f64 / LAMPORTS_PER_SOL as f64).abs() < 1e-12);
+        assert!((trades[0].pnl_sol() - (0.2 - 0.1 - trades[0].fees_sol)).abs() < 1e-12);
+    }
+
+    // Signature statuses, in the order they are asked for: Some(true) landed, None not seen
+    fn signature_statuses(statuses: &[Option<bool>]) -> solana_rpc_client::mock_sender::MocksMap {
+        statuses.iter().map(|landed| {
+            let status = landed.map(|_| json!({ "slot": 1, "confirmations": null, "err": null, "status": { "Ok": null }, "confirmationStatus": "finalized" }));
+            let response = json!({ "context": { "slot": 1 }, "value": [status] });
+            (solana_client::rpc_request::RpcRequest::GetSignatureStatuses, response)
+        }).collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bundles_landing_during_the_rpc_fallback_keep_their_tip() {
+        // Not seen after the bundle timeout, confirmed over RPC, then the tip transfer's status
+        for (tip_landed, expected_tip) in [(Some(true), Some(25_000)), (None, None)] {
+            let mut trader = trader_with("late-bundle", Arc::new(PendingBundleHttp), ExecutionMode::Live);
+            let mocks = signature_statuses(&[None, Some(true), tip_landed]);
+            trader.rpc_client = RpcClient::new_mock_with_mocks_map("succeeds", mocks);
+            trader.transactions.simulate = false;
+            trader.jito.bundle_timeout_ms = 20;
+            trader.jito.status_poll_ms = 10;
+
+            let payer = trader.keypair.pubkey();
+            let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+            let transaction = Transaction::new_signed_with_payer(&[transfer], Some(&payer), &[&trader.keypair], Hash::default());
+
+            let submission = trader.submit(VersionedTransaction::from(transaction), Some(25_000)).await.unwrap();
+            assert_eq!(submission.tip_lamports, expected_tip);
+        }
+    }
+
+    #[test]
+    fn ladders_parse_and_validate() {
+        let ladder = TakeProfitTier::parse_ladder(" 30:25 , 60%:25%,").unwrap();
+        assert_eq!(ladder, vec![
+            TakeProfitTier { profit_percent: Decimal::from(30), sell_percent: Decimal::from(25) },
+            TakeProfitTier { profit_percent: Decimal::from(60), sell_percent: Decimal::from(25) },
+        ]);
+        assert!(TakeProfitTier::parse_ladder("").unwrap().is_empty());
+
+        for invalid in ["30", "30:abc", "x:25", "0:25", "-10:25", "30:0", "30:101", "30:60,60:50"] {
+            assert!(TakeProfitTier::parse_ladder(invalid).is_err(), "{} was accepted", invalid);
+        }
+    }
+
+    // Paper position holding 1,000,000 tokens with a +50% sell-25% tier
+    async fn laddered_position(trader: &FastMemeTrader, strategy: StrategyType) -> Position {
+        let mut position = open_position(trader, Decimal::ONE).await;
+        trader.paper.as_ref().unwrap().fill_buy(&position.token_address, 0, 1_000_000).unwrap();
+        position.strategy = strategy;
+        position.take_profit_ladder = TakeProfitTier::parse_ladder("50:25").unwrap();
+        trader.positions.write().await.insert(position.token_address.clone(), position.clone());
+        position
+    }
+
+    fn tick(price: Decimal) -> PriceTick {
+        PriceTick { price, timestamp: Utc::now() }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn take_profit_tiers_fire_once() {
+        let trader = paper_trader("tier-once", QuoteHttp::new(50_000_000));
+        let position = laddered_position(&trader, StrategyType::ConservativeATH).await;
+
+        let mut sells = Vec::new();
+        trader.process_price_tick(&position, &tick(Decimal::TWO), &mut sells).await;
+        assert_eq!(sells.len(), 1);
+        assert!(sells[0].trade.success);
+
+        let after = trader.positions.read().await[&position.token_address].clone();
+        assert_eq!(after.completed_tiers, vec![0]);
+        assert_eq!(after.amount_tokens, 750_000);
+        assert_eq!(trader.get_token_balance(&position.token_address).await.unwrap(), 750_000);
+
+        // The tier stays done on later ticks above it
+        let mut sells = Vec::new();
+        trader.process_price_tick(&after, &tick(Decimal::TWO), &mut sells).await;
+        assert!(sells.is_empty());
+        assert_eq!(trader.get_token_balance(&position.token_address).await.unwrap(), 750_000);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_tier_is_released_and_skips_the_exit_strategy() {
+        // No quotes, so every sell fails
+        let trader = paper_trader("tier-failed", Arc::new(UnavailableHttp));
+        // Conservative takes profit at +15%, so it would sell everything on the same tick
+        let position = laddered_position(&trader, StrategyType::Conservative).await;
+
+        let mut sells = Vec::new();
+        trader.process_price_tick(&position, &tick(Decimal::TWO), &mut sells).await;
+
+        assert_eq!(sells.len(), 1);
+        assert!(!sells[0].trade.success);
+        assert_eq!(sells[0].reason.rule, ExitRule::TakeProfitTier(0));
+        let after = trader.positions.read().await[&position.token_address].clone();
+        assert!(after.completed_tiers.is_empty());
+        assert_eq!(after.amount_tokens, 1_000_000);
+    }
+
+    // Sells half the position once the price doubles
+    struct HalfAtDouble;
+
+    impl ExitStrategy for HalfAtDouble {
+        fn name(&self) -> &str {
+            "half-at-double"
+        }
+
+        fn evaluate(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision {
+            match position.entry_price {
+                Some(entry_price) if tick.price >= entry_price * Decimal::TWO => {
+                    let rule = ExitRule::Strategy("first-half".to_string());
+                    ExitDecision::SellFraction(Decimal::new(5, 1), ExitReason::triggered(rule, position, tracker, tick))
+                },
+                _ => ExitDecision::Hold,
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn registered_strategies_are_dispatched_by_name() {
+        let trader = offline_trader("registry");
+        let registered = StrategyType::Registered("half-at-double".to_string());
+        assert!(trader.resolve_exit_strategy(&registered).await.is_none());
+
+        trader.register_exit_strategy(Arc::new(HalfAtDouble)).await;
+        assert_eq!(trader.resolve_exit_strategy(&registered).await.unwrap().name(), "half-at-double");
+        assert_eq!(trader.resolve_exit_strategy(&StrategyType::Aggressive).await.unwrap().name(), "Aggressive");
+        assert!(trader.resolve_exit_strategy(&StrategyType::Registered("unknown".to_string())).await.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn registered_partial_exits_fire_once() {
+        let trader = paper_trader("partial-once", QuoteHttp::new(50_000_000));
+        trader.register_exit_strategy(Arc::new(HalfAtDouble)).await;
+        let mut position = open_position(&trader, Decimal::ONE).await;
+        trader.paper.as_ref().unwrap().fill_buy(&position.token_address, 0, 1_000_000).unwrap();
+        position.strategy = StrategyType::Registered("half-at-double".to_string());
+        trader.positions.write().await.insert(position.token_address.clone(), position.clone());
+
+        let mut sells = Vec::new();
+        trader.process_price_tick(&position, &tick(Decimal::TWO), &mut sells).await;
+        assert_eq!(sells.len(), 1);
+        assert!(sells[0].trade.success);
+        assert_eq!(trader.get_token_balance(&position.token_address).await.unwrap(), 500_000);
+
+        let after = trader.positions.read().await[&position.token_address].clone();
+        assert_eq!(after.fired_exits, vec![ExitRule::Strategy("first-half".to_string())]);
+
+        for price in [Decimal::TWO, Decimal::from(3)] {
+            let mut sells = Vec::new();
+            trader.process_price_tick(&after, &tick(price), &mut sells).await;
+            assert!(sells.is_empty());
+        }
+        assert_eq!(trader.get_token_balance(&position.token_address).await.unwrap(), 500_000);
+    }
+
+    #[tokio::test]
+    async fn sells_of_one_token_are_serialised() {
+        let trader = offline_trader("sell-locks");
+        let held = trader.lock_sells("token-a").await;
+
+        // Another token is independent, the same token waits for the first sell
+        let _other = trader.lock_sells("token-b").await;
+        let waiting = tokio::time::timeout(Duration::from_millis(50), trader.lock_sells("token-a")).await;
+        assert!(waiting.is_err());
+
+        drop(held);
+        let reacquired = tokio::time::timeout(Duration::from_millis(50), trader.lock_sells("token-a")).await;
+        assert!(reacquired.is_ok());
+    }
+
+    #[test]
+    fn send_action_only_resigns_once_the_original_cannot_land() {
+        let landed = Some(Some(Ok(())));
+        let failed = Some(Some(Err(TransactionError::InsufficientFundsForFee)));
+        let not_found = Some(None);
+
+        assert_eq!(next_send_action(landed.clone(), Some(true)), SendAction::Landed);
+        assert_eq!(next_send_action(landed, Some(false)), SendAction::Landed);
+        assert_eq!(
+            next_send_action(failed, Some(false)),
+            SendAction::FailedOnChain(TransactionError::InsufficientFundsForFee)
+        );
+
+        // Still valid: the original may land, so it is resent rather than re-signed
+        assert_eq!(next_send_action(not_found.clone(), Some(true)), SendAction::Resend);
+        assert_eq!(next_send_action(not_found.clone(), Some(false)), SendAction::Resign);
+
+        // Failed lookups prove nothing either way
+        assert_eq!(next_send_action(None, Some(false)), SendAction::Resend);
+        assert_eq!(next_send_action(not_found, None), SendAction::Resend);
+        assert_eq!(next_send_action(None, None), SendAction::Resend);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unpriced_positions_are_still_listed() {
+        let trader = offline_trader("list-unpriced");
+        let position = open_position(&trader, Decimal::ONE).await;
+
+        let lines = trader.list_positions().await;
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with(short(&position.token_address)));
+        assert!(lines[0].contains("Current: n/a | P&L: n/a"));
+        assert!(lines[0].contains("1000000 raw units"));
+    }
+
+    fn custom(input: &str) -> Result<StrategyParams> {
+        match StrategyType::from_str(input)? {
+            StrategyType::Custom(params) => Ok(params),
+            other => panic!("expected a custom strategy, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_custom_strategy_rules() {
+        let params = custom("custom: tp=30%, sl=10, pb=8.5,min=2,dd=20").unwrap();
+        assert_eq!(params.take_profit_percent, Some(Decimal::from(30)));
+        assert_eq!(params.stop_loss_percent, Some(Decimal::from(10)));
+        assert_eq!(params.pullback_percent, Some(Decimal::new(85, 1)));
+        assert_eq!(params.min_profit_percent, Some(Decimal::from(2)));
+        assert_eq!(params.max_drawdown_percent, Some(Decimal::from(20)));
+
+        // Keys and prefix are case-insensitive; empty rules are skipped
+        assert_eq!(custom("CUSTOM:TP=15,,").unwrap().take_profit_percent, Some(Decimal::from(15)));
+        assert!(matches!(StrategyType::from_str("Conservative_ATH").unwrap(), StrategyType::ConservativeATH));
+    }
+
+    #[test]
+    fn rejects_malformed_custom_strategies() {
+        for input in [
+            "custom:",
+            "custom:tp",
+            "custom:tp=abc",
+            "custom:tp=",
+            "custom:xx=5",
+            "custom;tp=5",
+            "moonshot",
+        ] {
+            assert!(StrategyType::from_str(input).is_err(), "{} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_custom_strategy_rules() {
+        let error = custom("custom:tp=20,sl=5,tp=40").unwrap_err();
+        assert!(error.to_string().contains("'tp' is set more than once"));
+    }
+
+    #[test]
+    fn rejects_out_of_range_custom_strategies() {
+        for input in [
+            "custom:tp=-5",
+            "custom:sl=100",
+            "custom:pb=0",
+            "custom:pb=100",
+            "custom:pb=10,dd=0",
+            "custom:pb=10,dd=150",
+            // Max drawdown only applies alongside a pullback
+            "custom:tp=20,dd=30",
+            // Nothing that can ever exit
+            "custom:min=5",
+        ] {
+            assert!(custom(input).is_err(), "{} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn preset_strategy_params_are_valid() {
+        for strategy in [
+            StrategyType::Conservative,
+            StrategyType::Aggressive,
+            StrategyType::ConservativeATH,
+            StrategyType::AggressiveATH,
+        ] {
+            strategy.params().validate().unwrap();
+        }
+    }
+}
@@ -1,8 +1,15 @@
 // src/main.rs - Simplified entry point that delegates to CLI
 
-use fast_meme_trader::{FastMemeTrader, example_usage};
-use anyhow::Result;
+use fast_meme_trader::{short, FastMemeTrader, StrategyType, TakeProfitTier, example_usage};
+use fast_meme_trader::backtest::{self, BacktestConfig, BacktestReport, FillModel};
+use fast_meme_trader::config::TraderConfig;
+use fast_meme_trader::daemon;
+use anyhow::{anyhow, Result};
 use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const DEFAULT_PAPER_BALANCE_SOL: f64 = 10.0;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -25,6 +32,8 @@ async fn main() -> Result<()> {
             println!("🔍 Running health check...");
             run_health_check().await
         },
+        Some("backtest") => run_backtest(&args[2..]),
+        Some("daemon") => run_daemon(&args[2..]).await,
         Some("--help") | Some("-h") => {
             print_help();
             Ok(())
@@ -34,6 +43,8 @@ async fn main() -> Result<()> {
             println!("Use 'cargo run --bin cli' for interactive mode");
             println!("Use 'cargo run example' for example usage");
             println!("Use 'cargo run health' for health check");
+            println!("Use 'cargo run backtest <prices.csv>' to replay strategies offline");
+            println!("Use 'cargo run daemon' to monitor positions in the background");
             println!("Use 'cargo run --help' for more options");
             Ok(())
         }
@@ -53,6 +64,181 @@ async fn run_health_check() -> Result<()> {
     Ok(())
 }
 
+// Monitor positions until SIGINT/SIGTERM, finishing in-flight sells and flushing state on exit
+async fn run_daemon(args: &[String]) -> Result<()> {
+    let mut config = TraderConfig::load()?;
+    
+    let mut options = args.iter().peekable();
+    while let Some(flag) = options.next() {
+        match flag.as_str() {
+            "--interval" => {
+                let value = options.next().ok_or_else(|| anyhow!("Missing value for --interval"))?;
+                config.daemon.monitor_interval_secs = value.parse()
+                    .map_err(|_| anyhow!("Invalid --interval: {}", value))?;
+            },
+            "--heartbeat" => {
+                let value = options.next().ok_or_else(|| anyhow!("Missing value for --heartbeat"))?;
+                config.daemon.heartbeat_interval_secs = value.parse()
+                    .map_err(|_| anyhow!("Invalid --heartbeat: {}", value))?;
+            },
+            "--paper" => {
+                let starting_sol = match options.peek().and_then(|value| value.parse::<f64>().ok()) {
+                    Some(starting_sol) => {
+                        options.next();
+                        starting_sol
+                    },
+                    None => config.paper_starting_sol.unwrap_or(DEFAULT_PAPER_BALANCE_SOL),
+                };
+                config.paper_starting_sol = Some(starting_sol);
+            },
+            "--api" => config.api.enabled = true,
+            other => return Err(anyhow!("Unknown daemon option: {}", other)),
+        }
+    }
+    config.validate()?;
+    
+    let private_key = std::env::var("WALLET_PRIVATE_KEY")
+        .map_err(|_| anyhow!("WALLET_PRIVATE_KEY not set in .env file"))?;
+    let helius_api_key = std::env::var("HELIUS_API_KEY")
+        .map_err(|_| anyhow!("HELIUS_API_KEY not set in .env file"))?;
+    
+    // Install signal handlers before the trader exists so an early signal is not fatal
+    let shutdown = daemon::spawn_shutdown_listener();
+    let trader = Arc::new(FastMemeTrader::from_config(&private_key, helius_api_key, &config)?);
+    
+    // The API stops taking requests on the shutdown signal, or when the daemon returns early
+    let (stop_api, api_shutdown) = tokio::sync::watch::channel(false);
+    tokio::spawn({
+        let stop_api = stop_api.clone();
+        let mut shutdown = shutdown.clone();
+        async move {
+            if shutdown.wait_for(|stop| *stop).await.is_ok() {
+                let _ = stop_api.send(true);
+            }
+        }
+    });
+    let control_api = match config.api.enabled {
+        true => Some(spawn_control_api(&trader, &config, api_shutdown)?),
+        false => None,
+    };
+    
+    let result = daemon::run(&trader, &config.daemon, shutdown).await;
+    
+    // Let in-flight handlers (e.g. a /sell) finish, then flush what they wrote
+    if let Some(control_api) = control_api {
+        let _ = stop_api.send(true);
+        if let Err(e) = control_api.await {
+            log::error!("Control API task failed: {}", e);
+        }
+        trader.flush_state().await?;
+    }
+    
+    result?;
+    Ok(())
+}
+
+#[cfg(feature = "control-api")]
+fn spawn_control_api(
+    trader: &Arc<FastMemeTrader>, config: &TraderConfig, shutdown: tokio::sync::watch::Receiver<bool>,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let trader = trader.clone();
+    let api_config = config.api.clone();
+    Ok(tokio::spawn(async move {
+        if let Err(e) = fast_meme_trader::api::serve(trader, &api_config, shutdown).await {
+            log::error!("Control API failed: {}", e);
+        }
+    }))
+}
+
+#[cfg(not(feature = "control-api"))]
+fn spawn_control_api(
+    _trader: &Arc<FastMemeTrader>, _config: &TraderConfig, _shutdown: tokio::sync::watch::Receiver<bool>,
+) -> Result<tokio::task::JoinHandle<()>> {
+    Err(anyhow!("The control API is enabled but this binary was built without it; rebuild with --features control-api"))
+}
+
+// Offline strategy comparison: no wallet or network needed
+fn run_backtest(args: &[String]) -> Result<()> {
+    let path = args.first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or_else(|| anyhow!("Usage: cargo run backtest <prices.csv|prices.json> [OPTIONS]"))?;
+    
+    let mut strategies = vec![StrategyType::ConservativeATH];
+    let mut config = BacktestConfig::default();
+    let mut fill_model = FillModel::default();
+    
+    let mut options = args[1..].iter();
+    while let Some(flag) = options.next() {
+        let value = options.next().ok_or_else(|| anyhow!("Missing value for {}", flag))?;
+        match flag.as_str() {
+            "--strategy" => {
+                strategies = if value == "all" {
+                    vec![
+                        StrategyType::Conservative,
+                        StrategyType::Aggressive,
+                        StrategyType::ConservativeATH,
+                        StrategyType::AggressiveATH,
+                    ]
+                } else {
+                    value.split(';').map(StrategyType::from_str).collect::<Result<Vec<_>>>()?
+                };
+            },
+            "--amount" => config.amount_sol = value.parse().map_err(|_| anyhow!("Invalid --amount: {}", value))?,
+            "--slippage-bps" => fill_model.slippage_bps = value.parse().map_err(|_| anyhow!("Invalid --slippage-bps: {}", value))?,
+            "--fee" => fill_model.fee_sol_per_trade = value.parse().map_err(|_| anyhow!("Invalid --fee: {}", value))?,
+            "--ladder" => config.take_profit_ladder = TakeProfitTier::parse_ladder(value)?,
+            other => return Err(anyhow!("Unknown backtest option: {}", other)),
+        }
+    }
+    config.fill_model = fill_model;
+    
+    let series = backtest::load_price_series(path)?;
+    println!("📈 Backtesting {} tokens from {}", series.len(), path);
+    println!("💰 {} SOL per trade | Slippage: {} bps | Fee: {} SOL per tx",
+        config.amount_sol, fill_model.slippage_bps, fill_model.fee_sol_per_trade);
+    
+    let mut reports = Vec::new();
+    for strategy in strategies {
+        let report = backtest::run_backtest(&series, &BacktestConfig {
+            strategy,
+            ..config.clone()
+        })?;
+        print_backtest_report(&report);
+        reports.push(report);
+    }
+    
+    if reports.len() > 1 {
+        println!("\n📊 Strategy Comparison");
+        println!("{:<40} {:>7} {:>9} {:>14} {:>11}", "Strategy", "Trades", "Win Rate", "P&L (SOL)", "Avg Return");
+        for report in &reports {
+            println!(
+                "{:<40} {:>7} {:>8.1}% {:>14.6} {:>10.2}%",
+                format!("{:?}", report.strategy), report.stats.closed_trades, report.stats.win_rate(),
+                report.stats.realized_pnl_sol, report.stats.avg_return_percent
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+fn print_backtest_report(report: &BacktestReport) {
+    println!("\n🎯 Strategy: {:?}", report.strategy);
+    for trade in &report.trades {
+        println!(
+            "   {}: {:+.6} SOL ({:+.2}%) | Held: {}s | Exit: {}",
+            short(&trade.token_address),
+            trade.pnl_sol(), trade.return_percent(), trade.hold_time_secs, trade.exit_reason
+        );
+    }
+    println!(
+        "   Trades: {} | Win Rate: {:.1}% | P&L: {:.6} SOL | Avg Return: {:.2}% | Best: {:.2}% | Worst: {:.2}% | Fees: {:.6} SOL",
+        report.stats.closed_trades, report.stats.win_rate(), report.stats.realized_pnl_sol,
+        report.stats.avg_return_percent, report.stats.best_return_percent,
+        report.stats.worst_return_percent, report.stats.total_fees_sol
+    );
+}
+
 fn print_help() {
     println!("Fast Solana Meme Trading Bot v0.3.1");
     println!();
@@ -63,8 +249,25 @@ fn print_help() {
     println!("COMMANDS:");
     println!("    example    Run example trading session");
     println!("    health     Check bot health and connectivity");
+    println!("    backtest   Replay a price series through exit strategies (offline)");
+    println!("    daemon     Monitor positions on an interval until SIGINT/SIGTERM");
     println!("    --help     Show this help message");
     println!();
+    println!("BACKTEST OPTIONS:");
+    println!("    cargo run backtest <prices.csv|prices.json> [OPTIONS]");
+    println!("    --strategy S      conservative, aggressive, conservative-ath, aggressive-ath,");
+    println!("                      custom:tp=30,sl=10,pb=8,min=3,dd=20, ';'-separated list, or 'all'");
+    println!("    --amount SOL      Position size per token (default 0.1)");
+    println!("    --slippage-bps N  Simulated fill slippage (default 100)");
+    println!("    --fee SOL         Simulated fee per transaction (default 0.0001)");
+    println!("    --ladder L        Take-profit ladder, e.g. 30:25,60:25");
+    println!();
+    println!("DAEMON OPTIONS:");
+    println!("    --interval SECS   Seconds between monitor cycles (default 10)");
+    println!("    --heartbeat SECS  Seconds between heartbeat log lines (default 60)");
+    println!("    --paper [SOL]     Simulate fills against a virtual wallet");
+    println!("    --api             Serve the local control API (needs --features control-api)");
+    println!();
     println!("ENVIRONMENT:");
     println!("    WALLET_PRIVATE_KEY    Your Solana wallet private key (base58)");
     println!("    HELIUS_API_KEY       Your Helius RPC API key");
@@ -0,0 +1,173 @@
+// src/mint.rs - Mint accounts for both token programs
+// Legacy SPL Token and Token-2022 mints share the base layout; Token-2022 adds
+// extensions after it, of which only the transfer fee changes trade amounts
+
+use rust_decimal::Decimal;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use spl_token_2022::state::Mint;
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, Clone)]
+pub struct MintInfo {
+    pub address: Pubkey,
+    // The program that owns the mint, and so every token account for it
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    pub transfer_fee: Option<TransferFeeConfig>,
+}
+
+impl MintInfo {
+    pub fn decode(address: Pubkey, account: &Account) -> Result<Self> {
+        if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+            return Err(anyhow!("{} is not a token mint (owned by {})", address, account.owner));
+        }
+
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)
+            .map_err(|e| anyhow!("Invalid mint account {}: {}", address, e))?;
+        Ok(Self {
+            address,
+            token_program: account.owner,
+            decimals: state.base.decimals,
+            transfer_fee: state.get_extension::<TransferFeeConfig>().ok().copied(),
+        })
+    }
+
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program == spl_token_2022::id()
+    }
+
+    // The owner's associated token account under the mint's program
+    pub fn associated_token_address(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.address, &self.token_program)
+    }
+
+    // Withheld from a transfer of `amount` in the given epoch
+    pub fn transfer_fee(&self, amount: u64, epoch: u64) -> u64 {
+        self.transfer_fee
+            .and_then(|config| config.calculate_epoch_fee(epoch, amount))
+            .unwrap_or(0)
+    }
+
+    // What the recipient actually receives when `amount` is sent
+    pub fn amount_after_transfer_fee(&self, amount: u64, epoch: u64) -> u64 {
+        amount.saturating_sub(self.transfer_fee(amount, epoch))
+    }
+
+    // Raw base units as whole tokens
+    pub fn ui_amount(&self, raw_amount: u64) -> Decimal {
+        Decimal::try_from_i128_with_scale(raw_amount as i128, self.decimals as u32)
+            .unwrap_or_else(|_| Decimal::from(raw_amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_pack::Pack;
+    use spl_token_2022::extension::transfer_fee::TransferFee;
+    use spl_token_2022::extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut};
+
+    fn mint(decimals: u8) -> Mint {
+        Mint { decimals, is_initialized: true, supply: 1_000_000, ..Mint::default() }
+    }
+
+    fn legacy_account(decimals: u8) -> Account {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        let legacy = spl_token::state::Mint { decimals, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(legacy, &mut data).unwrap();
+        Account { data, owner: spl_token::id(), ..Account::default() }
+    }
+
+    // A fee of `older` bps before `epoch` and `newer` bps from it, capped at `maximum_fee`
+    fn fee_config(older: u16, newer: u16, epoch: u64, maximum_fee: u64) -> TransferFeeConfig {
+        TransferFeeConfig {
+            older_transfer_fee: TransferFee {
+                epoch: 0.into(),
+                maximum_fee: maximum_fee.into(),
+                transfer_fee_basis_points: older.into(),
+            },
+            newer_transfer_fee: TransferFee {
+                epoch: epoch.into(),
+                maximum_fee: maximum_fee.into(),
+                transfer_fee_basis_points: newer.into(),
+            },
+            ..TransferFeeConfig::default()
+        }
+    }
+
+    fn token_2022_account(decimals: u8, config: TransferFeeConfig) -> Account {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        *state.init_extension::<TransferFeeConfig>(true).unwrap() = config;
+        state.base = mint(decimals);
+        state.pack_base();
+        state.init_account_type().unwrap();
+        Account { data, owner: spl_token_2022::id(), ..Account::default() }
+    }
+
+    #[test]
+    fn decodes_legacy_mints() {
+        let address = Pubkey::new_unique();
+        let info = MintInfo::decode(address, &legacy_account(9)).unwrap();
+
+        assert_eq!(info.token_program, spl_token::id());
+        assert!(!info.is_token_2022());
+        assert_eq!(info.decimals, 9);
+        assert!(info.transfer_fee.is_none());
+        assert_eq!(info.amount_after_transfer_fee(1_000, 0), 1_000);
+        assert_eq!(
+            info.associated_token_address(&address),
+            get_associated_token_address_with_program_id(&address, &address, &spl_token::id())
+        );
+    }
+
+    #[test]
+    fn decodes_token_2022_transfer_fees() {
+        let info = MintInfo::decode(Pubkey::new_unique(), &token_2022_account(6, fee_config(100, 100, 0, u64::MAX))).unwrap();
+
+        assert!(info.is_token_2022());
+        assert_eq!(info.decimals, 6);
+        assert!(info.transfer_fee.is_some());
+        // 1% of the amount, rounded up
+        assert_eq!(info.transfer_fee(1_000_000, 0), 10_000);
+        assert_eq!(info.transfer_fee(150, 0), 2);
+        assert_eq!(info.amount_after_transfer_fee(1_000_000, 0), 990_000);
+    }
+
+    #[test]
+    fn transfer_fee_follows_the_epoch_and_cap() {
+        let info = MintInfo {
+            address: Pubkey::new_unique(),
+            token_program: spl_token_2022::id(),
+            decimals: 6,
+            transfer_fee: Some(fee_config(50, 200, 10, 15_000)),
+        };
+
+        // The newer rate only applies from its epoch
+        assert_eq!(info.transfer_fee(1_000_000, 9), 5_000);
+        assert_eq!(info.transfer_fee(1_000_000, 10), 15_000);
+        assert_eq!(info.transfer_fee(500_000, 10), 10_000);
+        assert_eq!(info.transfer_fee(0, 10), 0);
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_mints() {
+        let not_a_mint = Account { owner: Pubkey::new_unique(), ..legacy_account(6) };
+        assert!(MintInfo::decode(Pubkey::new_unique(), &not_a_mint).is_err());
+
+        let truncated = Account { data: vec![0; 10], ..legacy_account(6) };
+        assert!(MintInfo::decode(Pubkey::new_unique(), &truncated).is_err());
+    }
+
+    #[test]
+    fn ui_amount_applies_the_decimals() {
+        let info = MintInfo::decode(Pubkey::new_unique(), &legacy_account(6)).unwrap();
+        assert_eq!(info.ui_amount(1_500_000), Decimal::new(15, 1));
+        assert_eq!(info.ui_amount(1), Decimal::new(1, 6));
+    }
+}
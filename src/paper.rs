@@ -0,0 +1,211 @@
+// src/paper.rs - Virtual wallet for paper trading
+// Fills are simulated from real quotes; balances persist across restarts
+
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anyhow::{anyhow, Result};
+
+const ACCOUNT_FILE: &str = "paper_account.json";
+
+// Charged on every simulated fill, roughly a base fee plus a modest priority fee
+pub const SIMULATED_FEE_LAMPORTS: u64 = 10_000;
+
+// Signatures of simulated fills start with this, so they are never looked up on chain
+pub const PAPER_SIGNATURE_PREFIX: &str = "paper-";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperBalances {
+    pub sol_lamports: u64,
+    pub starting_lamports: u64,
+    pub tokens: HashMap<String, u64>,
+    pub fills: u64,
+}
+
+pub struct PaperAccount {
+    path: PathBuf,
+    balances: Mutex<PaperBalances>,
+}
+
+impl PaperAccount {
+    // Open the account in dir, funding a new one with starting_sol
+    pub fn open(dir: impl AsRef<Path>, starting_sol: f64) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("Failed to create paper directory {}: {}", dir.display(), e))?;
+        let path = dir.join(ACCOUNT_FILE);
+
+        let balances = if path.exists() {
+            let data = fs::read_to_string(&path)?;
+            serde_json::from_str(&data)
+                .map_err(|e| anyhow!("Corrupt paper account {}: {}", path.display(), e))?
+        } else {
+            let lamports = (starting_sol * LAMPORTS_PER_SOL as f64) as u64;
+            let balances = PaperBalances {
+                sol_lamports: lamports,
+                starting_lamports: lamports,
+                ..PaperBalances::default()
+            };
+            Self::write(&path, &balances)?;
+            balances
+        };
+
+        log::info!(
+            "Paper account loaded from {}: {:.6} SOL, {} token balances",
+            path.display(), balances.sol_lamports as f64 / LAMPORTS_PER_SOL as f64, balances.tokens.len()
+        );
+
+        Ok(Self {
+            path,
+            balances: Mutex::new(balances),
+        })
+    }
+
+    pub fn is_paper_signature(signature: &str) -> bool {
+        signature.starts_with(PAPER_SIGNATURE_PREFIX)
+    }
+
+    pub fn balances(&self) -> PaperBalances {
+        self.balances.lock().map(|balances| balances.clone()).unwrap_or_default()
+    }
+
+    pub fn sol_balance(&self) -> f64 {
+        self.balances().sol_lamports as f64 / LAMPORTS_PER_SOL as f64
+    }
+
+    pub fn token_balance(&self, token_address: &str) -> u64 {
+        self.balances.lock()
+            .map(|balances| balances.tokens.get(token_address).copied().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    // Debit SOL plus the simulated fee and credit the quoted tokens
+    pub fn fill_buy(&self, token_address: &str, lamports_in: u64, tokens_out: u64) -> Result<String> {
+        let mut balances = self.balances.lock().map_err(|_| anyhow!("Paper account lock poisoned"))?;
+
+        let cost = lamports_in + SIMULATED_FEE_LAMPORTS;
+        if cost > balances.sol_lamports {
+            return Err(anyhow!(
+                "Insufficient paper balance: need {:.6} SOL, have {:.6} SOL",
+                cost as f64 / LAMPORTS_PER_SOL as f64,
+                balances.sol_lamports as f64 / LAMPORTS_PER_SOL as f64
+            ));
+        }
+
+        let mut updated = balances.clone();
+        updated.sol_lamports -= cost;
+        *updated.tokens.entry(token_address.to_string()).or_insert(0) += tokens_out;
+        let signature = Self::next_signature(&mut updated);
+
+        Self::write(&self.path, &updated)?;
+        *balances = updated;
+        Ok(signature)
+    }
+
+    // Debit the tokens and credit the quoted SOL less the simulated fee
+    pub fn fill_sell(&self, token_address: &str, tokens_in: u64, lamports_out: u64) -> Result<String> {
+        let mut balances = self.balances.lock().map_err(|_| anyhow!("Paper account lock poisoned"))?;
+
+        let held = balances.tokens.get(token_address).copied().unwrap_or(0);
+        if tokens_in > held {
+            return Err(anyhow!("Insufficient paper tokens: selling {}, holding {}", tokens_in, held));
+        }
+
+        let mut updated = balances.clone();
+        if tokens_in == held {
+            updated.tokens.remove(token_address);
+        } else {
+            updated.tokens.insert(token_address.to_string(), held - tokens_in);
+        }
+        updated.sol_lamports = (updated.sol_lamports + lamports_out).saturating_sub(SIMULATED_FEE_LAMPORTS);
+        let signature = Self::next_signature(&mut updated);
+
+        Self::write(&self.path, &updated)?;
+        *balances = updated;
+        Ok(signature)
+    }
+
+    fn next_signature(balances: &mut PaperBalances) -> String {
+        balances.fills += 1;
+        format!("{}{}-{}", PAPER_SIGNATURE_PREFIX, Utc::now().timestamp_millis(), balances.fills)
+    }
+
+    fn write(path: &Path, balances: &PaperBalances) -> Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(serde_json::to_string_pretty(balances)?.as_bytes())?;
+            tmp.sync_all()?;
+        }
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fresh_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fast-meme-trader-paper-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn balances_persist_across_reopen() {
+        let dir = fresh_dir("persist");
+        let account = PaperAccount::open(&dir, 1.0).unwrap();
+        account.fill_buy("token", 100_000_000, 5_000).unwrap();
+        account.fill_sell("token", 2_000, 50_000_000).unwrap();
+
+        // The starting balance only funds a new account
+        let reopened = PaperAccount::open(&dir, 10.0).unwrap();
+        let balances = reopened.balances();
+        assert_eq!(balances.starting_lamports, LAMPORTS_PER_SOL);
+        assert_eq!(balances.sol_lamports, LAMPORTS_PER_SOL - 100_000_000 + 50_000_000 - 2 * SIMULATED_FEE_LAMPORTS);
+        assert_eq!(reopened.token_balance("token"), 3_000);
+        assert_eq!(balances.fills, 2);
+    }
+
+    #[test]
+    fn rejects_overdrafts_without_changing_balances() {
+        let dir = fresh_dir("overdraft");
+        let account = PaperAccount::open(&dir, 0.1).unwrap();
+
+        // The simulated fee counts towards the cost
+        assert!(account.fill_buy("token", 100_000_000, 5_000).is_err());
+        account.fill_buy("token", 100_000_000 - SIMULATED_FEE_LAMPORTS, 5_000).unwrap();
+        assert_eq!(account.balances().sol_lamports, 0);
+
+        assert!(account.fill_sell("token", 5_001, 1).is_err());
+        assert!(account.fill_sell("other", 1, 1).is_err());
+        assert_eq!(account.token_balance("token"), 5_000);
+
+        let reopened = PaperAccount::open(&dir, 0.1).unwrap();
+        assert_eq!(reopened.balances().fills, 1);
+    }
+
+    #[test]
+    fn selling_everything_drops_the_token() {
+        let account = PaperAccount::open(fresh_dir("sell-all"), 1.0).unwrap();
+        let signature = account.fill_buy("token", 1_000, 5_000).unwrap();
+        account.fill_sell("token", 5_000, 1_000).unwrap();
+
+        assert!(PaperAccount::is_paper_signature(&signature));
+        assert!(!account.balances().tokens.contains_key("token"));
+    }
+
+    #[test]
+    fn corrupt_account_file_is_an_error() {
+        let dir = fresh_dir("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(ACCOUNT_FILE), "{ not json").unwrap();
+        assert!(PaperAccount::open(&dir, 1.0).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+// src/price_stream.rs - Push price updates over the RPC websocket
+// Subscribes with accountSubscribe to the account each position's price lives in:
+// the bonding curve for PumpFun tokens, the pool and its vaults for Raydium ones. Other
+// tokens, and any gap while reconnecting, fall back to polling.
+
+use crate::pumpfun::BondingCurve;
+use crate::raydium;
+use crate::Position;
+use futures::stream::{BoxStream, SelectAll, StreamExt};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, watch, RwLock};
+use anyhow::{anyhow, Result};
+
+// How often the open positions are re-checked for subscriptions to add or drop
+const RESUBSCRIBE_CHECK: Duration = Duration::from_secs(2);
+
+// Ticks are small and coalesced by the consumer, so a short queue is enough
+const CHANNEL_CAPACITY: usize = 256;
+
+// Wrapped SOL, the SOL side of every Raydium pool we trade
+const SOL_DECIMALS: u32 = 9;
+
+// Where a position's price can be streamed from, chosen when it is opened
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceFeed {
+    BondingCurve { curve: String },
+    // A Raydium AMM v4 or CPMM pool: the price is the ratio of the two vault balances,
+    // less what the pool account says each vault owes the protocol. Feeds stored before
+    // the pool was recorded have none and price from the raw balances.
+    RaydiumVaults {
+        #[serde(default)]
+        pool: Option<String>,
+        token_vault: String,
+        sol_vault: String,
+        token_decimals: u8,
+    },
+}
+
+// One subscribed account and the role it plays in its feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedAccount {
+    Curve,
+    TokenVault,
+    SolVault,
+    Pool,
+}
+
+// Token address, the account's role, its new state and the slot it was seen at
+type AccountUpdate = (String, FeedAccount, Option<Account>, u64);
+
+// Latest raw vault balances for a Raydium feed as (amount, slot), until both sides
+// have been seen. A swap moves both vaults in one slot, so only same-slot pairs price.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct VaultBalances {
+    pub(crate) token: Option<(u64, u64)>,
+    pub(crate) sol: Option<(u64, u64)>,
+    // Latest (token, sol) owed to the protocol, from the pool account
+    pub(crate) owed: (u64, u64),
+}
+
+// A price observed on chain, in SOL per whole token
+#[derive(Debug, Clone, Copy)]
+pub struct StreamedPrice {
+    pub price_sol: Decimal,
+    pub slot: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+pub struct StreamSettings {
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    pub reconnect_delay: Duration,
+}
+
+// Stream prices for whatever positions are open until shutdown. The receiver
+// yields (token_address, price) pairs as account updates arrive.
+pub fn spawn(
+    settings: StreamSettings,
+    positions: Arc<RwLock<HashMap<String, Position>>>,
+    mut shutdown: watch::Receiver<bool>,
+) -> mpsc::Receiver<(String, StreamedPrice)> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+
+    tokio::spawn(async move {
+        while !*shutdown.borrow() {
+            match stream_session(&settings, &positions, &sender, &mut shutdown).await {
+                Ok(()) => {},
+                Err(e) => log::warn!("Price stream disconnected, polling only until reconnected: {}", e),
+            }
+            if sender.is_closed() {
+                break;
+            }
+            tokio::select! {
+                _ = shutdown.changed() => {},
+                _ = tokio::time::sleep(settings.reconnect_delay) => {},
+            }
+        }
+        log::debug!("Price stream stopped");
+    });
+
+    receiver
+}
+
+// Feeds to watch, keyed by token address
+async fn wanted_feeds(positions: &RwLock<HashMap<String, Position>>) -> BTreeMap<String, PriceFeed> {
+    positions.read().await
+        .iter()
+        .filter_map(|(token, position)| Some((token.clone(), position.price_feed.clone()?)))
+        .collect()
+}
+
+// The accounts behind a feed; None if a stored address does not parse
+fn feed_accounts(feed: &PriceFeed) -> Option<Vec<(Pubkey, FeedAccount)>> {
+    match feed {
+        PriceFeed::BondingCurve { curve } => Some(vec![(Pubkey::from_str(curve).ok()?, FeedAccount::Curve)]),
+        PriceFeed::RaydiumVaults { pool, token_vault, sol_vault, .. } => {
+            let mut accounts = vec![
+                (Pubkey::from_str(token_vault).ok()?, FeedAccount::TokenVault),
+                (Pubkey::from_str(sol_vault).ok()?, FeedAccount::SolVault),
+            ];
+            if let Some(pool) = pool {
+                accounts.push((Pubkey::from_str(pool).ok()?, FeedAccount::Pool));
+            }
+            Some(accounts)
+        },
+    }
+}
+
+// One websocket connection. Returns Ok when the set of positions changes so the
+// caller resubscribes, Err when the connection fails.
+async fn stream_session(
+    settings: &StreamSettings,
+    positions: &RwLock<HashMap<String, Position>>,
+    sender: &mpsc::Sender<(String, StreamedPrice)>,
+    shutdown: &mut watch::Receiver<bool>,
+) -> Result<()> {
+    let feeds = wanted_feeds(positions).await;
+    if feeds.is_empty() {
+        // Nothing to watch; check again shortly without holding a connection open
+        tokio::select! {
+            _ = shutdown.changed() => {},
+            _ = tokio::time::sleep(RESUBSCRIBE_CHECK) => {},
+        }
+        return Ok(());
+    }
+
+    let client = PubsubClient::new(&settings.ws_url).await
+        .map_err(|e| anyhow!("websocket connect failed: {}", e))?;
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(settings.commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    let mut updates: SelectAll<BoxStream<'_, AccountUpdate>> = SelectAll::new();
+    let mut unsubscribers = Vec::with_capacity(feeds.len());
+    for (token_address, feed) in &feeds {
+        let Some(accounts) = feed_accounts(feed) else {
+            log::warn!("Invalid price feed for {}, polling only", token_address);
+            continue;
+        };
+        for (address, role) in accounts {
+            let (stream, unsubscribe) = client.account_subscribe(&address, Some(config.clone())).await
+                .map_err(|e| anyhow!("accountSubscribe failed for {}: {}", token_address, e))?;
+            let token_address = token_address.clone();
+            updates.push(
+                stream
+                    .map(move |response| {
+                        (token_address.clone(), role, response.value.decode::<Account>(), response.context.slot)
+                    })
+                    .boxed(),
+            );
+            unsubscribers.push(unsubscribe);
+        }
+    }
+    log::info!("Streaming on-chain prices for {} positions", feeds.len());
+
+    let mut vaults: HashMap<String, VaultBalances> = HashMap::new();
+
+    let mut resubscribe_check = tokio::time::interval(RESUBSCRIBE_CHECK);
+    let result = loop {
+        tokio::select! {
+            _ = shutdown.changed() => break Ok(()),
+            _ = resubscribe_check.tick() => {
+                if wanted_feeds(positions).await != feeds {
+                    break Ok(());
+                }
+            },
+            update = updates.next() => {
+                let Some((token_address, role, account, slot)) = update else {
+                    break Err(anyhow!("subscription stream closed"));
+                };
+                let Some(account) = account else {
+                    continue;
+                };
+                let price_sol = match (role, feeds.get(&token_address)) {
+                    (FeedAccount::Curve, _) => curve_price(&token_address, &account.data),
+                    (FeedAccount::Pool, Some(feed)) => {
+                        // Owed amounts only adjust the next vault pair; they are not a price on their own
+                        if let Some(owed) = owed_amounts(feed, &account.data) {
+                            vaults.entry(token_address.clone()).or_default().owed = owed;
+                        }
+                        None
+                    },
+                    (_, Some(PriceFeed::RaydiumVaults { token_decimals, .. })) => {
+                        let balances = vaults.entry(token_address.clone()).or_default();
+                        let amount = raydium::token_account_amount(&account.data).ok();
+                        vault_update(balances, role, amount, slot, *token_decimals)
+                    },
+                    _ => None,
+                };
+                let Some(price_sol) = price_sol else {
+                    continue;
+                };
+                let price = StreamedPrice { price_sol, slot, timestamp: Utc::now() };
+                if sender.send((token_address, price)).await.is_err() {
+                    break Ok(());
+                }
+            },
+        }
+    };
+
+    drop(updates);
+    for unsubscribe in unsubscribers {
+        unsubscribe().await;
+    }
+    result
+}
+
+fn curve_price(token_address: &str, data: &[u8]) -> Option<Decimal> {
+    match BondingCurve::decode(data) {
+        // A migrated curve is frozen; the token's price now comes from polling
+        Ok(curve) if curve.complete => None,
+        Ok(curve) => curve.price_sol(),
+        Err(e) => {
+            log::debug!("Ignoring account update for {}: {}", token_address, e);
+            None
+        }
+    }
+}
+
+// Record one vault's new balance and price the pool if the other side is from the
+// same slot. Until then the update is half a swap and yields nothing.
+fn vault_update(balances: &mut VaultBalances, role: FeedAccount, amount: Option<u64>, slot: u64, token_decimals: u8) -> Option<Decimal> {
+    let seen = amount.map(|amount| (amount, slot));
+    match role {
+        FeedAccount::TokenVault => balances.token = seen,
+        _ => balances.sol = seen,
+    }
+    vault_price(*balances, token_decimals)
+}
+
+// (token, sol) owed to the protocol according to a Raydium feed's pool account
+pub(crate) fn owed_amounts(feed: &PriceFeed, pool_data: &[u8]) -> Option<(u64, u64)> {
+    let PriceFeed::RaydiumVaults { pool: Some(pool), token_vault, sol_vault, .. } = feed else {
+        return None;
+    };
+    let owed = raydium::owed_by_vault(Pubkey::from_str(pool).ok()?, pool_data).ok()?;
+    let owed_by = |vault: &str| owed.iter().find(|(address, _)| address.to_string() == vault).map(|(_, amount)| *amount);
+    Some((owed_by(token_vault)?, owed_by(sol_vault)?))
+}
+
+// SOL per whole token from the pool's tradable reserves: the vault balances less
+// what they owe the protocol, as RaydiumPool::reserves() quotes a swap
+pub(crate) fn vault_price(balances: VaultBalances, token_decimals: u8) -> Option<Decimal> {
+    let ((token_amount, token_slot), (sol_amount, sol_slot)) = (balances.token?, balances.sol?);
+    if token_slot != sol_slot {
+        return None;
+    }
+    let (token_amount, sol_amount) = (token_amount.saturating_sub(balances.owed.0), sol_amount.saturating_sub(balances.owed.1));
+    let tokens = Decimal::try_from_i128_with_scale(token_amount as i128, token_decimals as u32).ok()?;
+    let sol = Decimal::try_from_i128_with_scale(sol_amount as i128, SOL_DECIMALS).ok()?;
+    if tokens.is_zero() || sol.is_zero() {
+        return None;
+    }
+    Some(sol / tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_price_needs_both_sides() {
+        let token_only = VaultBalances { token: Some((1_000_000, 1)), ..VaultBalances::default() };
+        assert_eq!(vault_price(token_only, 6), None);
+
+        // 2 SOL against 1,000 whole tokens with 6 decimals
+        let balances = VaultBalances { token: Some((1_000_000_000, 1)), sol: Some((2_000_000_000, 1)), owed: (0, 0) };
+        assert_eq!(vault_price(balances, 6), Some(Decimal::new(2, 3)));
+
+        // Owed amounts are not tradable: 1.5 SOL against 1,000 tokens
+        let owing = VaultBalances { owed: (0, 500_000_000), ..balances };
+        assert_eq!(vault_price(owing, 6), Some(Decimal::new(15, 4)));
+
+        let drained = VaultBalances { token: Some((0, 1)), sol: Some((2_000_000_000, 1)), owed: (0, 0) };
+        assert_eq!(vault_price(drained, 6), None);
+    }
+
+    #[test]
+    fn half_applied_swaps_are_not_priced() {
+        let mut balances = VaultBalances::default();
+        assert_eq!(vault_update(&mut balances, FeedAccount::TokenVault, Some(1_000_000_000), 10, 6), None);
+        assert_eq!(vault_update(&mut balances, FeedAccount::SolVault, Some(2_000_000_000), 10, 6), Some(Decimal::new(2, 3)));
+
+        // A swap at slot 11: the token side alone would read as a 25% drop
+        let emitted: Vec<_> = [
+            (FeedAccount::TokenVault, 800_000_000),
+            (FeedAccount::SolVault, 2_500_000_000),
+        ]
+            .into_iter()
+            .filter_map(|(role, amount)| vault_update(&mut balances, role, Some(amount), 11, 6))
+            .collect();
+        assert_eq!(emitted, vec![Decimal::new(3125, 6)]);
+    }
+
+    #[test]
+    fn feeds_map_to_their_accounts() {
+        let curve = Pubkey::new_unique();
+        let feed = PriceFeed::BondingCurve { curve: curve.to_string() };
+        assert_eq!(feed_accounts(&feed), Some(vec![(curve, FeedAccount::Curve)]));
+
+        let (token_vault, sol_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let feed = PriceFeed::RaydiumVaults {
+            pool: None,
+            token_vault: token_vault.to_string(),
+            sol_vault: sol_vault.to_string(),
+            token_decimals: 6,
+        };
+        assert_eq!(
+            feed_accounts(&feed),
+            Some(vec![(token_vault, FeedAccount::TokenVault), (sol_vault, FeedAccount::SolVault)])
+        );
+
+        let pool = Pubkey::new_unique();
+        let with_pool = PriceFeed::RaydiumVaults {
+            pool: Some(pool.to_string()),
+            token_vault: token_vault.to_string(),
+            sol_vault: sol_vault.to_string(),
+            token_decimals: 6,
+        };
+        assert_eq!(feed_accounts(&with_pool).unwrap()[2], (pool, FeedAccount::Pool));
+
+        // Feeds persisted before the pool was recorded still load
+        let stored = r#"{"kind":"raydium_vaults","token_vault":"a","sol_vault":"b","token_decimals":6}"#;
+        let PriceFeed::RaydiumVaults { pool, .. } = serde_json::from_str(stored).unwrap() else {
+            panic!("expected a Raydium feed");
+        };
+        assert_eq!(pool, None);
+
+        let invalid = PriceFeed::BondingCurve { curve: "not-a-key".to_string() };
+        assert_eq!(feed_accounts(&invalid), None);
+    }
+}
@@ -0,0 +1,563 @@
+// src/pumpfun.rs - PumpFun bonding curve accounts and program instructions
+// Curve state is read straight from chain and trades are built locally, no third-party API involved
+
+use rust_decimal::Decimal;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::program as system_program;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use anyhow::{anyhow, Result};
+
+pub const PROGRAM_ID: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+pub const GLOBAL: Pubkey = pubkey!("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf");
+pub const EVENT_AUTHORITY: Pubkey = pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
+// Separate program holding the fee configuration, passed to every trade
+pub const FEE_PROGRAM_ID: Pubkey = pubkey!("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ");
+
+// Anchor instruction discriminators: sha256("global:buy")[..8] and sha256("global:sell")[..8]
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+// Every PumpFun mint uses 6 decimals
+pub const TOKEN_DECIMALS: u32 = 6;
+
+// Anchor account discriminator: sha256("account:Global")[..8]
+const GLOBAL_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+
+// Offsets into the Global account, after the discriminator, initialized flag and authority
+const GLOBAL_FEE_RECIPIENT_OFFSET: usize = 8 + 1 + 32;
+const GLOBAL_FEE_BPS_OFFSET: usize = GLOBAL_FEE_RECIPIENT_OFFSET + 32 + 4 * 8;
+// After withdraw_authority, enable_migrate and pool_migration_fee
+const GLOBAL_CREATOR_FEE_BPS_OFFSET: usize = GLOBAL_FEE_BPS_OFFSET + 8 + 32 + 1 + 8;
+
+// Anchor account discriminator: sha256("account:BondingCurve")[..8]
+pub(crate) const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+// Discriminator, five u64 reserves/supply fields and the completion flag
+const BONDING_CURVE_MIN_LEN: usize = 8 + 5 * 8 + 1;
+
+// Curves created or resized since creator fees were introduced also store the creator
+const CREATOR_OFFSET: usize = BONDING_CURVE_MIN_LEN;
+
+pub fn bonding_curve_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PROGRAM_ID).0
+}
+
+// The bonding curve's token account, holding the tokens still for sale. Newer
+// coins are Token-2022 mints, so the mint's program is part of the derivation.
+pub fn associated_bonding_curve_address(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(&bonding_curve_address(mint), mint, token_program)
+}
+
+// Collects the creator's share of trading fees
+pub fn creator_vault_address(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], &PROGRAM_ID).0
+}
+
+// Trading volume accounts, updated on every buy
+pub fn global_volume_accumulator_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_volume_accumulator"], &PROGRAM_ID).0
+}
+
+pub fn user_volume_accumulator_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"user_volume_accumulator", user.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn fee_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_config", PROGRAM_ID.as_ref()], &FEE_PROGRAM_ID).0
+}
+
+// The protocol-wide settings account; only the fee fields matter for trading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Global {
+    pub fee_recipient: Pubkey,
+    pub fee_basis_points: u64,
+    // Zero on accounts written before creator fees were introduced
+    pub creator_fee_basis_points: u64,
+}
+
+impl Global {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < GLOBAL_FEE_BPS_OFFSET + 8 {
+            return Err(anyhow!("Global account too short: {} bytes", data.len()));
+        }
+        if data[..8] != GLOBAL_DISCRIMINATOR {
+            return Err(anyhow!("Account is not the PumpFun global account"));
+        }
+
+        let fee_recipient = &data[GLOBAL_FEE_RECIPIENT_OFFSET..GLOBAL_FEE_RECIPIENT_OFFSET + 32];
+        let creator_fee_basis_points = if data.len() >= GLOBAL_CREATOR_FEE_BPS_OFFSET + 8 {
+            read_u64(data, GLOBAL_CREATOR_FEE_BPS_OFFSET)
+        } else {
+            0
+        };
+        Ok(Self {
+            fee_recipient: Pubkey::new_from_array(fee_recipient.try_into().expect("slice is 32 bytes")),
+            fee_basis_points: read_u64(data, GLOBAL_FEE_BPS_OFFSET),
+            creator_fee_basis_points,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BondingCurve {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    // Set once the curve has migrated; it no longer trades after that
+    pub complete: bool,
+    pub creator: Option<Pubkey>,
+}
+
+impl BondingCurve {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < BONDING_CURVE_MIN_LEN {
+            return Err(anyhow!("Bonding curve account too short: {} bytes", data.len()));
+        }
+        if data[..8] != BONDING_CURVE_DISCRIMINATOR {
+            return Err(anyhow!("Account is not a PumpFun bonding curve"));
+        }
+
+        Ok(Self {
+            virtual_token_reserves: read_u64(data, 8),
+            virtual_sol_reserves: read_u64(data, 16),
+            real_token_reserves: read_u64(data, 24),
+            real_sol_reserves: read_u64(data, 32),
+            token_total_supply: read_u64(data, 40),
+            complete: data[48] != 0,
+            creator: data.get(CREATOR_OFFSET..CREATOR_OFFSET + 32)
+                .map(|bytes| Pubkey::new_from_array(bytes.try_into().expect("slice is 32 bytes"))),
+        })
+    }
+
+    // Spot price in SOL per whole token, from the virtual reserves
+    pub fn price_sol(&self) -> Option<Decimal> {
+        if self.virtual_token_reserves == 0 {
+            return None;
+        }
+        Some(price_sol(self.virtual_sol_reserves as u128, self.virtual_token_reserves as u128))
+    }
+
+    // Tokens bought for a total SOL spend, fee included. Constant product on the
+    // virtual reserves, capped at the tokens actually left on the curve.
+    pub fn buy_quote(&self, global: &Global, sol_in_lamports: u64) -> Result<CurveQuote> {
+        self.check_tradable()?;
+        if sol_in_lamports == 0 {
+            return Err(anyhow!("Buy amount must be positive"));
+        }
+
+        // The fees are charged on top of the SOL that reaches the curve
+        let total_fee_bps = (global.fee_basis_points + self.creator_fee_bps(global)) as u128;
+        let sol_to_curve = (sol_in_lamports as u128 * 10_000 / (10_000 + total_fee_bps)) as u64;
+        let fee_lamports = sol_in_lamports - sol_to_curve;
+
+        let sol_reserves = self.virtual_sol_reserves as u128;
+        let token_reserves = self.virtual_token_reserves as u128;
+        let new_sol_reserves = sol_reserves + sol_to_curve as u128;
+        let new_token_reserves = (sol_reserves * token_reserves).div_ceil(new_sol_reserves);
+        let tokens_out = ((token_reserves - new_token_reserves) as u64).min(self.real_token_reserves);
+
+        Ok(CurveQuote {
+            sol_lamports: sol_in_lamports,
+            tokens: tokens_out,
+            fee_lamports,
+            price_before: price_sol(sol_reserves, token_reserves),
+            price_after: price_sol(new_sol_reserves, token_reserves - tokens_out as u128),
+            price_impact_percent: self.price_impact(sol_to_curve, tokens_out),
+        })
+    }
+
+    // SOL received for selling tokens, after the fees
+    pub fn sell_quote(&self, global: &Global, tokens_in: u64) -> Result<CurveQuote> {
+        self.check_tradable()?;
+        if tokens_in == 0 {
+            return Err(anyhow!("Sell amount must be positive"));
+        }
+
+        let sol_reserves = self.virtual_sol_reserves as u128;
+        let token_reserves = self.virtual_token_reserves as u128;
+        let new_token_reserves = token_reserves + tokens_in as u128;
+        let sol_from_curve = (sol_reserves - (sol_reserves * token_reserves).div_ceil(new_token_reserves)) as u64;
+        // The curve can never pay out more SOL than it really holds
+        let sol_from_curve = sol_from_curve.min(self.real_sol_reserves);
+        // The program rounds the protocol and creator fees up separately
+        let fee_lamports = fee(sol_from_curve, global.fee_basis_points) + fee(sol_from_curve, self.creator_fee_bps(global));
+
+        Ok(CurveQuote {
+            sol_lamports: sol_from_curve - fee_lamports,
+            tokens: tokens_in,
+            fee_lamports,
+            price_before: price_sol(sol_reserves, token_reserves),
+            price_after: price_sol(sol_reserves - sol_from_curve as u128, new_token_reserves),
+            price_impact_percent: self.price_impact(sol_from_curve, tokens_in),
+        })
+    }
+
+    // The creator's cut only applies to curves that record a creator
+    fn creator_fee_bps(&self, global: &Global) -> u64 {
+        match self.creator {
+            Some(creator) if creator != Pubkey::default() => global.creator_fee_basis_points,
+            _ => 0,
+        }
+    }
+
+    // Accounts shared by the buy and sell instructions
+    fn trade_accounts(&self, mint: &Pubkey, token_program: &Pubkey, user: &Pubkey) -> Result<TradeAccounts> {
+        let creator = self.creator
+            .ok_or_else(|| anyhow!("Bonding curve has no creator recorded, cannot derive the creator vault"))?;
+        Ok(TradeAccounts {
+            bonding_curve: bonding_curve_address(mint),
+            associated_bonding_curve: associated_bonding_curve_address(mint, token_program),
+            associated_user: get_associated_token_address_with_program_id(user, mint, token_program),
+            creator_vault: creator_vault_address(&creator),
+        })
+    }
+
+    // Buy exactly `tokens`, paying at most `max_sol_cost` lamports including the fee
+    pub fn buy_instruction(
+        &self, global: &Global, mint: &Pubkey, token_program: &Pubkey, user: &Pubkey, tokens: u64, max_sol_cost: u64,
+    ) -> Result<Instruction> {
+        let accounts = self.trade_accounts(mint, token_program, user)?;
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(GLOBAL, false),
+                AccountMeta::new(global.fee_recipient, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(accounts.bonding_curve, false),
+                AccountMeta::new(accounts.associated_bonding_curve, false),
+                AccountMeta::new(accounts.associated_user, false),
+                AccountMeta::new(*user, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new(accounts.creator_vault, false),
+                AccountMeta::new_readonly(EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(global_volume_accumulator_address(), false),
+                AccountMeta::new(user_volume_accumulator_address(user), false),
+                AccountMeta::new_readonly(fee_config_address(), false),
+                AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+            ],
+            data: instruction_data(BUY_DISCRIMINATOR, tokens, max_sol_cost),
+        })
+    }
+
+    // Sell `tokens`, failing unless at least `min_sol_output` lamports come back after the fee
+    pub fn sell_instruction(
+        &self, global: &Global, mint: &Pubkey, token_program: &Pubkey, user: &Pubkey, tokens: u64, min_sol_output: u64,
+    ) -> Result<Instruction> {
+        let accounts = self.trade_accounts(mint, token_program, user)?;
+        Ok(Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(GLOBAL, false),
+                AccountMeta::new(global.fee_recipient, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(accounts.bonding_curve, false),
+                AccountMeta::new(accounts.associated_bonding_curve, false),
+                AccountMeta::new(accounts.associated_user, false),
+                AccountMeta::new(*user, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(accounts.creator_vault, false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new_readonly(EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new_readonly(fee_config_address(), false),
+                AccountMeta::new_readonly(FEE_PROGRAM_ID, false),
+            ],
+            data: instruction_data(SELL_DISCRIMINATOR, tokens, min_sol_output),
+        })
+    }
+
+    fn check_tradable(&self) -> Result<()> {
+        if self.complete {
+            return Err(anyhow!("Bonding curve is complete, the token has migrated"));
+        }
+        if self.virtual_sol_reserves == 0 || self.virtual_token_reserves == 0 {
+            return Err(anyhow!("Bonding curve has empty reserves"));
+        }
+        Ok(())
+    }
+
+    // How far the average fill price is from spot, as a positive percentage
+    fn price_impact(&self, sol_lamports: u64, tokens: u64) -> Decimal {
+        let spot = price_sol(self.virtual_sol_reserves as u128, self.virtual_token_reserves as u128);
+        if tokens == 0 || spot.is_zero() {
+            return Decimal::ZERO;
+        }
+        let fill = price_sol(sol_lamports as u128, tokens as u128);
+        ((fill - spot) / spot * Decimal::from(100)).abs()
+    }
+}
+
+// Expected result of a trade against the curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveQuote {
+    // SOL spent on a buy (fee included) or received on a sell (fee deducted)
+    pub sol_lamports: u64,
+    pub tokens: u64,
+    pub fee_lamports: u64,
+    // Spot prices in SOL per whole token
+    pub price_before: Decimal,
+    pub price_after: Decimal,
+    pub price_impact_percent: Decimal,
+}
+
+struct TradeAccounts {
+    bonding_curve: Pubkey,
+    associated_bonding_curve: Pubkey,
+    associated_user: Pubkey,
+    creator_vault: Pubkey,
+}
+
+// Slippage bounds around a quote, in basis points
+pub fn max_sol_cost(quote: &CurveQuote, slippage_bps: u16) -> u64 {
+    (quote.sol_lamports as u128 * (10_000 + slippage_bps as u128) / 10_000) as u64
+}
+
+pub fn min_sol_output(quote: &CurveQuote, slippage_bps: u16) -> u64 {
+    (quote.sol_lamports as u128 * 10_000u128.saturating_sub(slippage_bps as u128) / 10_000) as u64
+}
+
+// A basis-point fee on a lamport amount, rounded up
+fn fee(lamports: u64, basis_points: u64) -> u64 {
+    (lamports as u128 * basis_points as u128).div_ceil(10_000) as u64
+}
+
+fn instruction_data(discriminator: [u8; 8], amount: u64, sol_limit: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&discriminator);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&sol_limit.to_le_bytes());
+    data
+}
+
+// SOL per whole token for a lamport and raw token amount
+fn price_sol(lamports: u128, raw_tokens: u128) -> Decimal {
+    if raw_tokens == 0 {
+        return Decimal::ZERO;
+    }
+    let sol = Decimal::from(lamports) / Decimal::from(LAMPORTS_PER_SOL);
+    let tokens = Decimal::from(raw_tokens) / Decimal::from(10u64.pow(TOKEN_DECIMALS));
+    sol / tokens
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A freshly launched curve
+    fn new_curve() -> BondingCurve {
+        BondingCurve {
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: Some(Pubkey::new_unique()),
+        }
+    }
+
+    // Current mainnet rates: 0.95% protocol fee plus 0.05% to the creator
+    fn global() -> Global {
+        Global { fee_recipient: Pubkey::new_unique(), fee_basis_points: 95, creator_fee_basis_points: 5 }
+    }
+
+    fn encode_global(global: &Global) -> Vec<u8> {
+        let mut data = GLOBAL_DISCRIMINATOR.to_vec();
+        data.push(1);
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(global.fee_recipient.as_ref());
+        data.extend_from_slice(&[0; 4 * 8]);
+        data.extend_from_slice(&global.fee_basis_points.to_le_bytes());
+        data.extend_from_slice(&[0; 32 + 1 + 8]);
+        data.extend_from_slice(&global.creator_fee_basis_points.to_le_bytes());
+        data
+    }
+
+    fn encode(curve: &BondingCurve) -> Vec<u8> {
+        let mut data = BONDING_CURVE_DISCRIMINATOR.to_vec();
+        for value in [
+            curve.virtual_token_reserves,
+            curve.virtual_sol_reserves,
+            curve.real_token_reserves,
+            curve.real_sol_reserves,
+            curve.token_total_supply,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(curve.complete as u8);
+        if let Some(creator) = curve.creator {
+            data.extend_from_slice(creator.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_with_and_without_creator() {
+        let curve = new_curve();
+        assert_eq!(BondingCurve::decode(&encode(&curve)).unwrap(), curve);
+
+        let legacy = BondingCurve { creator: None, ..curve };
+        assert_eq!(BondingCurve::decode(&encode(&legacy)).unwrap(), legacy);
+
+        let mut wrong_account = encode(&curve);
+        wrong_account[0] ^= 1;
+        assert!(BondingCurve::decode(&wrong_account).is_err());
+        assert!(BondingCurve::decode(&encode(&curve)[..BONDING_CURVE_MIN_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn decodes_the_global_fee_rates() {
+        let global = global();
+        let data = encode_global(&global);
+        assert_eq!(Global::decode(&data).unwrap(), global);
+
+        // Written before creator fees existed
+        let legacy = Global::decode(&data[..GLOBAL_CREATOR_FEE_BPS_OFFSET]).unwrap();
+        assert_eq!(legacy.fee_basis_points, 95);
+        assert_eq!(legacy.creator_fee_basis_points, 0);
+
+        let mut wrong_account = data.clone();
+        wrong_account[0] ^= 1;
+        assert!(Global::decode(&wrong_account).is_err());
+        assert!(Global::decode(&data[..GLOBAL_FEE_BPS_OFFSET]).is_err());
+    }
+
+    #[test]
+    fn spot_price_is_virtual_sol_per_whole_token() {
+        // 30 SOL against 1.073B whole tokens
+        let expected = Decimal::from(30) / Decimal::from(1_073_000_000u64);
+        assert_eq!(new_curve().price_sol(), Some(expected));
+    }
+
+    #[test]
+    fn buy_takes_the_fee_before_the_curve() {
+        let quote = new_curve().buy_quote(&global(), LAMPORTS_PER_SOL).unwrap();
+
+        assert_eq!(quote.sol_lamports, LAMPORTS_PER_SOL);
+        assert_eq!(quote.fee_lamports, 9_900_991);
+        assert_eq!(quote.tokens, 34_281_150_129_545);
+        assert!(quote.price_after > quote.price_before);
+        assert!(quote.price_impact_percent > Decimal::ZERO);
+    }
+
+    #[test]
+    fn creator_fee_only_applies_when_the_curve_has_a_creator() {
+        let with_creator = new_curve().buy_quote(&global(), LAMPORTS_PER_SOL).unwrap();
+        let legacy = BondingCurve { creator: None, ..new_curve() }.buy_quote(&global(), LAMPORTS_PER_SOL).unwrap();
+
+        // 0.95% instead of 1% on top of what reaches the curve
+        assert_eq!(legacy.fee_lamports, 9_410_600);
+        assert!(legacy.tokens > with_creator.tokens);
+
+        let no_fees = Global { fee_basis_points: 0, creator_fee_basis_points: 0, ..global() };
+        assert_eq!(new_curve().buy_quote(&no_fees, LAMPORTS_PER_SOL).unwrap().fee_lamports, 0);
+    }
+
+    #[test]
+    fn buy_is_capped_at_the_real_token_reserves() {
+        let curve = BondingCurve { real_token_reserves: 1_000, ..new_curve() };
+        assert_eq!(curve.buy_quote(&global(), LAMPORTS_PER_SOL).unwrap().tokens, 1_000);
+    }
+
+    #[test]
+    fn selling_the_bought_tokens_returns_less_than_was_paid() {
+        let curve = new_curve();
+        let buy = curve.buy_quote(&global(), LAMPORTS_PER_SOL).unwrap();
+        let after_buy = BondingCurve {
+            virtual_token_reserves: curve.virtual_token_reserves - buy.tokens,
+            virtual_sol_reserves: curve.virtual_sol_reserves + buy.sol_lamports - buy.fee_lamports,
+            real_token_reserves: curve.real_token_reserves - buy.tokens,
+            real_sol_reserves: buy.sol_lamports - buy.fee_lamports,
+            ..curve
+        };
+
+        let sell = after_buy.sell_quote(&global(), buy.tokens).unwrap();
+        assert_eq!(sell.fee_lamports, 9_900_991);
+        assert_eq!(sell.sol_lamports, 980_198_017);
+        assert!(sell.price_after < sell.price_before);
+    }
+
+    #[test]
+    fn sell_never_pays_more_than_the_real_sol_reserves() {
+        let quote = new_curve().sell_quote(&global(), 1_000_000_000_000).unwrap();
+        assert_eq!(quote.sol_lamports, 0);
+        assert_eq!(quote.fee_lamports, 0);
+    }
+
+    #[test]
+    fn completed_or_empty_curves_do_not_trade() {
+        let complete = BondingCurve { complete: true, ..new_curve() };
+        assert!(complete.buy_quote(&global(), LAMPORTS_PER_SOL).is_err());
+        assert!(complete.sell_quote(&global(), 1).is_err());
+
+        let empty = BondingCurve { virtual_token_reserves: 0, ..new_curve() };
+        assert!(empty.buy_quote(&global(), LAMPORTS_PER_SOL).is_err());
+        assert_eq!(empty.price_sol(), None);
+
+        assert!(new_curve().buy_quote(&global(), 0).is_err());
+        assert!(new_curve().sell_quote(&global(), 0).is_err());
+    }
+
+    #[test]
+    fn slippage_bounds_widen_the_quote() {
+        let quote = new_curve().buy_quote(&global(), LAMPORTS_PER_SOL).unwrap();
+        assert_eq!(max_sol_cost(&quote, 100), 1_010_000_000);
+        assert_eq!(min_sol_output(&quote, 100), 990_000_000);
+        assert_eq!(min_sol_output(&quote, 20_000), 0);
+    }
+
+    #[test]
+    fn buy_instruction_encodes_amount_and_limit() {
+        let (curve, global) = (new_curve(), global());
+        let (mint, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let instruction = curve.buy_instruction(&global, &mint, &spl_token::id(), &user, 42, 1_000).unwrap();
+
+        assert_eq!(instruction.data[..8], BUY_DISCRIMINATOR);
+        assert_eq!(instruction.data[8..16], 42u64.to_le_bytes());
+        assert_eq!(instruction.data[16..], 1_000u64.to_le_bytes());
+        assert_eq!(instruction.accounts[1].pubkey, global.fee_recipient);
+        assert_eq!(instruction.accounts[3].pubkey, bonding_curve_address(&mint));
+        assert!(instruction.accounts[6].is_signer);
+
+        let legacy = BondingCurve { creator: None, ..curve };
+        assert!(legacy.sell_instruction(&global, &mint, &spl_token::id(), &user, 42, 1_000).is_err());
+    }
+
+    #[test]
+    fn trade_instructions_end_with_the_volume_and_fee_accounts() {
+        let (curve, global) = (new_curve(), global());
+        let (mint, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = |instruction: Instruction| instruction.accounts.iter().map(|meta| meta.pubkey).collect::<Vec<_>>();
+
+        let buy = keys(curve.buy_instruction(&global, &mint, &spl_token::id(), &user, 42, 1_000).unwrap());
+        assert_eq!(buy.len(), 16);
+        assert_eq!(buy[8], spl_token::id());
+        assert_eq!(buy[9], creator_vault_address(&curve.creator.unwrap()));
+        assert_eq!(buy[12..], [
+            global_volume_accumulator_address(),
+            user_volume_accumulator_address(&user),
+            fee_config_address(),
+            FEE_PROGRAM_ID,
+        ]);
+
+        // Sells swap the creator vault and token program, and skip the volume accounts
+        let sell = keys(curve.sell_instruction(&global, &mint, &spl_token::id(), &user, 42, 1_000).unwrap());
+        assert_eq!(sell.len(), 14);
+        assert_eq!(sell[8], creator_vault_address(&curve.creator.unwrap()));
+        assert_eq!(sell[9], spl_token::id());
+        assert_eq!(sell[12..], [fee_config_address(), FEE_PROGRAM_ID]);
+    }
+}
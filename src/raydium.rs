@@ -0,0 +1,545 @@
+// src/raydium.rs - Raydium AMM v4 and CPMM pools
+// Pool state is decoded from chain and swaps are built locally, so tokens can be
+// traded as soon as a pool exists, before any aggregator has indexed it
+
+use rust_decimal::Decimal;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use anyhow::{anyhow, Result};
+
+pub const AMM_V4_PROGRAM_ID: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+pub const AMM_V4_AUTHORITY: Pubkey = pubkey!("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+pub const CPMM_PROGRAM_ID: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+pub const AMM_V4_POOL_LEN: u64 = 752;
+pub const AMM_V4_BASE_MINT_OFFSET: usize = 400;
+pub const AMM_V4_QUOTE_MINT_OFFSET: usize = 432;
+
+pub const CPMM_TOKEN_0_MINT_OFFSET: usize = 168;
+pub const CPMM_TOKEN_1_MINT_OFFSET: usize = 200;
+
+// AMM v4 instruction index for swap_base_in
+const AMM_V4_SWAP_BASE_IN: u8 = 9;
+
+// Anchor discriminators: sha256("account:PoolState")[..8] and sha256("global:swap_base_input")[..8]
+const CPMM_POOL_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+const CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+
+// CPMM trade fees are expressed in millionths
+const CPMM_FEE_DENOMINATOR: u64 = 1_000_000;
+
+// OpenBook/Serum market v3 layout: 5 byte padding, then the account flags
+const MARKET_MIN_LEN: usize = 349;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmmV4Pool {
+    pub address: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub target_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    // Owed to the protocol, still sitting in the vaults
+    pub need_take_pnl_base: u64,
+    pub need_take_pnl_quote: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+}
+
+impl AmmV4Pool {
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() != AMM_V4_POOL_LEN as usize {
+            return Err(anyhow!("Not an AMM v4 pool: {} bytes", data.len()));
+        }
+        Ok(Self {
+            address,
+            swap_fee_numerator: read_u64(data, 176),
+            swap_fee_denominator: read_u64(data, 184),
+            need_take_pnl_base: read_u64(data, 192),
+            need_take_pnl_quote: read_u64(data, 200),
+            base_vault: read_pubkey(data, 336),
+            quote_vault: read_pubkey(data, 368),
+            base_mint: read_pubkey(data, AMM_V4_BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, AMM_V4_QUOTE_MINT_OFFSET),
+            open_orders: read_pubkey(data, 496),
+            market: read_pubkey(data, 528),
+            market_program: read_pubkey(data, 560),
+            target_orders: read_pubkey(data, 592),
+        })
+    }
+
+    // Owed to the protocol out of the (base, quote) vaults
+    pub fn owed(&self) -> (u64, u64) {
+        (self.need_take_pnl_base, self.need_take_pnl_quote)
+    }
+}
+
+// The OpenBook market accounts an AMM v4 swap still has to pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketAccounts {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+impl MarketAccounts {
+    pub fn decode(market: &Pubkey, market_program: &Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < MARKET_MIN_LEN {
+            return Err(anyhow!("Market account too short: {} bytes", data.len()));
+        }
+        let nonce = read_u64(data, 45);
+        let vault_signer = Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], market_program)
+            .map_err(|e| anyhow!("Invalid market vault signer nonce: {}", e))?;
+        Ok(Self {
+            base_vault: read_pubkey(data, 117),
+            quote_vault: read_pubkey(data, 165),
+            event_queue: read_pubkey(data, 253),
+            bids: read_pubkey(data, 285),
+            asks: read_pubkey(data, 317),
+            vault_signer,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpmmPool {
+    pub address: Pubkey,
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    // Either mint may be a Token-2022 mint
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation: Pubkey,
+    // Protocol and fund fees accrue in the vaults until collected
+    pub fees_token_0: u64,
+    pub fees_token_1: u64,
+}
+
+impl CpmmPool {
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < 373 || data[..8] != CPMM_POOL_DISCRIMINATOR {
+            return Err(anyhow!("Not a CPMM pool account"));
+        }
+        Ok(Self {
+            address,
+            amm_config: read_pubkey(data, 8),
+            token_0_vault: read_pubkey(data, 72),
+            token_1_vault: read_pubkey(data, 104),
+            token_0_mint: read_pubkey(data, CPMM_TOKEN_0_MINT_OFFSET),
+            token_1_mint: read_pubkey(data, CPMM_TOKEN_1_MINT_OFFSET),
+            token_0_program: read_pubkey(data, 232),
+            token_1_program: read_pubkey(data, 264),
+            observation: read_pubkey(data, 296),
+            fees_token_0: read_u64(data, 341).saturating_add(read_u64(data, 357)),
+            fees_token_1: read_u64(data, 349).saturating_add(read_u64(data, 365)),
+        })
+    }
+
+    pub fn authority() -> Pubkey {
+        Pubkey::find_program_address(&[b"vault_and_lp_mint_auth_seed"], &CPMM_PROGRAM_ID).0
+    }
+
+    // Owed to the protocol out of the (token 0, token 1) vaults
+    pub fn owed(&self) -> (u64, u64) {
+        (self.fees_token_0, self.fees_token_1)
+    }
+}
+
+// What each vault of a raw AMM v4 or CPMM pool account still owes the protocol, as
+// (vault, amount) in pool order. This is what reserves() leaves out of a quote.
+pub fn owed_by_vault(address: Pubkey, data: &[u8]) -> Result<[(Pubkey, u64); 2]> {
+    if let Ok(pool) = AmmV4Pool::decode(address, data) {
+        let (base, quote) = pool.owed();
+        return Ok([(pool.base_vault, base), (pool.quote_vault, quote)]);
+    }
+    let pool = CpmmPool::decode(address, data)
+        .map_err(|_| anyhow!("Not a Raydium pool account: {}", address))?;
+    let (token_0, token_1) = pool.owed();
+    Ok([(pool.token_0_vault, token_0), (pool.token_1_vault, token_1)])
+}
+
+// trade_fee_rate from a CPMM AmmConfig account, in millionths
+pub fn decode_cpmm_trade_fee_rate(data: &[u8]) -> Result<u64> {
+    if data.len() < 20 {
+        return Err(anyhow!("CPMM config account too short: {} bytes", data.len()));
+    }
+    Ok(read_u64(data, 12))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RaydiumPool {
+    AmmV4 { pool: AmmV4Pool, market: MarketAccounts },
+    Cpmm { pool: CpmmPool, trade_fee_rate: u64 },
+}
+
+// One side of a pool from the point of view of a swap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolSide {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub token_program: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub price_impact_percent: Decimal,
+}
+
+impl RaydiumPool {
+    pub fn address(&self) -> Pubkey {
+        match self {
+            RaydiumPool::AmmV4 { pool, .. } => pool.address,
+            RaydiumPool::Cpmm { pool, .. } => pool.address,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RaydiumPool::AmmV4 { .. } => "AMM v4",
+            RaydiumPool::Cpmm { .. } => "CPMM",
+        }
+    }
+
+    // Both sides in pool order: (base, quote) for AMM v4, (token 0, token 1) for CPMM
+    pub fn sides(&self) -> (PoolSide, PoolSide) {
+        match self {
+            RaydiumPool::AmmV4 { pool, .. } => (
+                PoolSide { mint: pool.base_mint, vault: pool.base_vault, token_program: spl_token::id() },
+                PoolSide { mint: pool.quote_mint, vault: pool.quote_vault, token_program: spl_token::id() },
+            ),
+            RaydiumPool::Cpmm { pool, .. } => (
+                PoolSide { mint: pool.token_0_mint, vault: pool.token_0_vault, token_program: pool.token_0_program },
+                PoolSide { mint: pool.token_1_mint, vault: pool.token_1_vault, token_program: pool.token_1_program },
+            ),
+        }
+    }
+
+    // (input, output) sides for a swap from input_mint
+    pub fn swap_sides(&self, input_mint: &Pubkey) -> Result<(PoolSide, PoolSide)> {
+        let (first, second) = self.sides();
+        if first.mint == *input_mint {
+            Ok((first, second))
+        } else if second.mint == *input_mint {
+            Ok((second, first))
+        } else {
+            Err(anyhow!("Mint {} is not in pool {}", input_mint, self.address()))
+        }
+    }
+
+    // Tradable reserves in pool order, from the raw vault balances
+    pub fn reserves(&self, vault_amounts: (u64, u64)) -> (u64, u64) {
+        let (owed_first, owed_second) = match self {
+            RaydiumPool::AmmV4 { pool, .. } => pool.owed(),
+            RaydiumPool::Cpmm { pool, .. } => pool.owed(),
+        };
+        (vault_amounts.0.saturating_sub(owed_first), vault_amounts.1.saturating_sub(owed_second))
+    }
+
+    // Constant product output for amount_in, with the pool's fee taken from the input
+    pub fn quote(&self, input_mint: &Pubkey, amount_in: u64, vault_amounts: (u64, u64)) -> Result<SwapQuote> {
+        let (input, _) = self.swap_sides(input_mint)?;
+        let (first, second) = self.reserves(vault_amounts);
+        let (reserve_in, reserve_out) = if input.mint == self.sides().0.mint { (first, second) } else { (second, first) };
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(anyhow!("Pool {} has empty reserves", self.address()));
+        }
+
+        let (fee_numerator, fee_denominator) = match self {
+            RaydiumPool::AmmV4 { pool, .. } => (pool.swap_fee_numerator, pool.swap_fee_denominator.max(1)),
+            RaydiumPool::Cpmm { trade_fee_rate, .. } => (*trade_fee_rate, CPMM_FEE_DENOMINATOR),
+        };
+        let fee = (amount_in as u128 * fee_numerator as u128).div_ceil(fee_denominator as u128) as u64;
+        let amount_in_after_fee = amount_in.saturating_sub(fee) as u128;
+        let amount_out = (reserve_out as u128 * amount_in_after_fee / (reserve_in as u128 + amount_in_after_fee)) as u64;
+
+        let spot = Decimal::from(reserve_out) / Decimal::from(reserve_in);
+        let price_impact_percent = if amount_in == 0 {
+            Decimal::ZERO
+        } else {
+            let fill = Decimal::from(amount_out) / Decimal::from(amount_in);
+            ((spot - fill) / spot * Decimal::from(100)).abs()
+        };
+
+        Ok(SwapQuote { amount_in, amount_out, fee, price_impact_percent })
+    }
+
+    // Exact-input swap between the user's token accounts for the two mints
+    pub fn swap_instruction(
+        &self,
+        user: &Pubkey,
+        input_mint: &Pubkey,
+        user_source: &Pubkey,
+        user_destination: &Pubkey,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<Instruction> {
+        let (input, output) = self.swap_sides(input_mint)?;
+        let instruction = match self {
+            RaydiumPool::AmmV4 { pool, market } => {
+                let mut data = Vec::with_capacity(17);
+                data.push(AMM_V4_SWAP_BASE_IN);
+                data.extend_from_slice(&amount_in.to_le_bytes());
+                data.extend_from_slice(&min_amount_out.to_le_bytes());
+                Instruction {
+                    program_id: AMM_V4_PROGRAM_ID,
+                    accounts: vec![
+                        AccountMeta::new_readonly(spl_token::id(), false),
+                        AccountMeta::new(pool.address, false),
+                        AccountMeta::new_readonly(AMM_V4_AUTHORITY, false),
+                        AccountMeta::new(pool.open_orders, false),
+                        AccountMeta::new(pool.target_orders, false),
+                        AccountMeta::new(pool.base_vault, false),
+                        AccountMeta::new(pool.quote_vault, false),
+                        AccountMeta::new_readonly(pool.market_program, false),
+                        AccountMeta::new(pool.market, false),
+                        AccountMeta::new(market.bids, false),
+                        AccountMeta::new(market.asks, false),
+                        AccountMeta::new(market.event_queue, false),
+                        AccountMeta::new(market.base_vault, false),
+                        AccountMeta::new(market.quote_vault, false),
+                        AccountMeta::new_readonly(market.vault_signer, false),
+                        AccountMeta::new(*user_source, false),
+                        AccountMeta::new(*user_destination, false),
+                        AccountMeta::new_readonly(*user, true),
+                    ],
+                    data,
+                }
+            },
+            RaydiumPool::Cpmm { pool, .. } => {
+                let mut data = Vec::with_capacity(24);
+                data.extend_from_slice(&CPMM_SWAP_BASE_INPUT);
+                data.extend_from_slice(&amount_in.to_le_bytes());
+                data.extend_from_slice(&min_amount_out.to_le_bytes());
+                Instruction {
+                    program_id: CPMM_PROGRAM_ID,
+                    accounts: vec![
+                        AccountMeta::new_readonly(*user, true),
+                        AccountMeta::new_readonly(CpmmPool::authority(), false),
+                        AccountMeta::new_readonly(pool.amm_config, false),
+                        AccountMeta::new(pool.address, false),
+                        AccountMeta::new(*user_source, false),
+                        AccountMeta::new(*user_destination, false),
+                        AccountMeta::new(input.vault, false),
+                        AccountMeta::new(output.vault, false),
+                        AccountMeta::new_readonly(input.token_program, false),
+                        AccountMeta::new_readonly(output.token_program, false),
+                        AccountMeta::new_readonly(input.mint, false),
+                        AccountMeta::new_readonly(output.mint, false),
+                        AccountMeta::new(pool.observation, false),
+                    ],
+                    data,
+                }
+            },
+        };
+        Ok(instruction)
+    }
+}
+
+// Minimum output after slippage, in basis points
+pub fn min_amount_out(quote: &SwapQuote, slippage_bps: u16) -> u64 {
+    (quote.amount_out as u128 * 10_000u128.saturating_sub(slippage_bps as u128) / 10_000) as u64
+}
+
+// The amount field of an SPL token account; Token-2022 accounts share the base layout
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    if data.len() < 72 {
+        return Err(anyhow!("Token account too short: {} bytes", data.len()));
+    }
+    Ok(read_u64(data, 64))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&data[offset..offset + 32]);
+    Pubkey::new_from_array(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_u64(data: &mut [u8], offset: usize, value: u64) {
+        data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_pubkey(data: &mut [u8], offset: usize, key: &Pubkey) {
+        data[offset..offset + 32].copy_from_slice(key.as_ref());
+    }
+
+    fn keys<const N: usize>() -> [Pubkey; N] {
+        std::array::from_fn(|_| Pubkey::new_unique())
+    }
+
+    fn amm_v4_pool() -> AmmV4Pool {
+        let [address, base_vault, quote_vault, base_mint, quote_mint, open_orders, market, market_program, target_orders] =
+            keys();
+        AmmV4Pool {
+            address,
+            base_vault,
+            quote_vault,
+            base_mint,
+            quote_mint,
+            open_orders,
+            target_orders,
+            market,
+            market_program,
+            need_take_pnl_base: 7,
+            need_take_pnl_quote: 11,
+            swap_fee_numerator: 25,
+            swap_fee_denominator: 10_000,
+        }
+    }
+
+    #[test]
+    fn decodes_amm_v4_pool_at_its_offsets() {
+        let expected = amm_v4_pool();
+        let mut data = vec![0u8; AMM_V4_POOL_LEN as usize];
+        put_u64(&mut data, 176, expected.swap_fee_numerator);
+        put_u64(&mut data, 184, expected.swap_fee_denominator);
+        put_u64(&mut data, 192, expected.need_take_pnl_base);
+        put_u64(&mut data, 200, expected.need_take_pnl_quote);
+        put_pubkey(&mut data, 336, &expected.base_vault);
+        put_pubkey(&mut data, 368, &expected.quote_vault);
+        put_pubkey(&mut data, AMM_V4_BASE_MINT_OFFSET, &expected.base_mint);
+        put_pubkey(&mut data, AMM_V4_QUOTE_MINT_OFFSET, &expected.quote_mint);
+        put_pubkey(&mut data, 496, &expected.open_orders);
+        put_pubkey(&mut data, 528, &expected.market);
+        put_pubkey(&mut data, 560, &expected.market_program);
+        put_pubkey(&mut data, 592, &expected.target_orders);
+
+        assert_eq!(AmmV4Pool::decode(expected.address, &data).unwrap(), expected);
+        assert!(AmmV4Pool::decode(expected.address, &data[..700]).is_err());
+    }
+
+    #[test]
+    fn decodes_cpmm_pool_at_its_offsets() {
+        let [address, amm_config, token_0_vault, token_1_vault, token_0_mint, token_1_mint, observation] = keys();
+        let mut data = vec![0u8; 637];
+        data[..8].copy_from_slice(&CPMM_POOL_DISCRIMINATOR);
+        put_pubkey(&mut data, 8, &amm_config);
+        put_pubkey(&mut data, 72, &token_0_vault);
+        put_pubkey(&mut data, 104, &token_1_vault);
+        put_pubkey(&mut data, CPMM_TOKEN_0_MINT_OFFSET, &token_0_mint);
+        put_pubkey(&mut data, CPMM_TOKEN_1_MINT_OFFSET, &token_1_mint);
+        put_pubkey(&mut data, 232, &spl_token_2022::id());
+        put_pubkey(&mut data, 264, &spl_token::id());
+        put_pubkey(&mut data, 296, &observation);
+        // Protocol fees then fund fees, for token 0 and token 1
+        put_u64(&mut data, 341, 1);
+        put_u64(&mut data, 349, 2);
+        put_u64(&mut data, 357, 10);
+        put_u64(&mut data, 365, 20);
+
+        let pool = CpmmPool::decode(address, &data).unwrap();
+        assert_eq!(pool, CpmmPool {
+            address,
+            amm_config,
+            token_0_vault,
+            token_1_vault,
+            token_0_mint,
+            token_1_mint,
+            token_0_program: spl_token_2022::id(),
+            token_1_program: spl_token::id(),
+            observation,
+            fees_token_0: 11,
+            fees_token_1: 22,
+        });
+
+        assert_eq!(owed_by_vault(address, &data).unwrap(), [(token_0_vault, 11), (token_1_vault, 22)]);
+
+        data[0] ^= 1;
+        assert!(CpmmPool::decode(address, &data).is_err());
+        assert!(owed_by_vault(address, &data).is_err());
+    }
+
+    #[test]
+    fn decodes_market_accounts_and_vault_signer() {
+        let [market, market_program, base_vault, quote_vault, event_queue, bids, asks] = keys();
+        // Not every nonce yields an off-curve address; take the first that does
+        let nonce = (0u64..)
+            .find(|nonce| Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], &market_program).is_ok())
+            .unwrap();
+
+        let mut data = vec![0u8; 388];
+        put_u64(&mut data, 45, nonce);
+        put_pubkey(&mut data, 117, &base_vault);
+        put_pubkey(&mut data, 165, &quote_vault);
+        put_pubkey(&mut data, 253, &event_queue);
+        put_pubkey(&mut data, 285, &bids);
+        put_pubkey(&mut data, 317, &asks);
+
+        let accounts = MarketAccounts::decode(&market, &market_program, &data).unwrap();
+        assert_eq!(accounts.base_vault, base_vault);
+        assert_eq!(accounts.quote_vault, quote_vault);
+        assert_eq!(accounts.event_queue, event_queue);
+        assert_eq!(accounts.bids, bids);
+        assert_eq!(accounts.asks, asks);
+        assert_eq!(
+            accounts.vault_signer,
+            Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], &market_program).unwrap()
+        );
+        assert!(MarketAccounts::decode(&market, &market_program, &data[..MARKET_MIN_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn reads_fee_rate_and_token_amounts() {
+        let mut config = vec![0u8; 236];
+        put_u64(&mut config, 12, 2_500);
+        assert_eq!(decode_cpmm_trade_fee_rate(&config).unwrap(), 2_500);
+
+        let mut token_account = vec![0u8; 165];
+        put_u64(&mut token_account, 64, 123_456);
+        assert_eq!(token_account_amount(&token_account).unwrap(), 123_456);
+        assert!(token_account_amount(&token_account[..71]).is_err());
+    }
+
+    #[test]
+    fn quotes_constant_product_net_of_owed_fees() {
+        let pool = amm_v4_pool();
+        let market = MarketAccounts {
+            bids: Pubkey::new_unique(),
+            asks: Pubkey::new_unique(),
+            event_queue: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            vault_signer: Pubkey::new_unique(),
+        };
+        let (base_mint, quote_mint) = (pool.base_mint, pool.quote_mint);
+        let raydium = RaydiumPool::AmmV4 { pool, market };
+
+        // Vaults hold the owed amounts on top of 1,000,000 base and 2,000,000 quote
+        let vaults = (1_000_007, 2_000_011);
+        assert_eq!(raydium.reserves(vaults), (1_000_000, 2_000_000));
+
+        let quote = raydium.quote(&quote_mint, 10_000, vaults).unwrap();
+        assert_eq!(quote.fee, 25);
+        // 1,000,000 * 9,975 / (2,000,000 + 9,975)
+        assert_eq!(quote.amount_out, 4_962);
+
+        let (input, output) = raydium.swap_sides(&base_mint).unwrap();
+        assert_eq!((input.mint, output.mint), (base_mint, quote_mint));
+        assert!(raydium.swap_sides(&Pubkey::new_unique()).is_err());
+        assert!(raydium.quote(&base_mint, 1, (7, 11)).is_err());
+
+        assert_eq!(min_amount_out(&quote, 100), 4_912);
+    }
+}
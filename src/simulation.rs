@@ -0,0 +1,337 @@
+// src/simulation.rs - Pre-flight simulation results
+// Turns a failed simulateTransaction into a reason a user can act on, and sizes the
+// compute budget from what the simulation actually consumed
+
+use crate::{pumpfun, raydium};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+// Highest limit a transaction may request; used while simulating so the
+// simulation itself never runs out of compute
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+// SetComputeUnitLimit's tag in the compute budget program's instruction enum
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+
+// Program error codes that mean the swap's minimum out / maximum in was breached
+const JUPITER_SLIPPAGE_EXCEEDED: u32 = 6001;
+const PUMPFUN_TOO_MUCH_SOL_REQUIRED: u32 = 6002;
+const PUMPFUN_TOO_LITTLE_SOL_RECEIVED: u32 = 6003;
+const RAYDIUM_AMM_V4_EXCEEDED_SLIPPAGE: u32 = 30;
+const RAYDIUM_CPMM_EXCEEDED_SLIPPAGE: u32 = 6005;
+
+// System program: a transfer larger than the sender's balance
+const SYSTEM_RESULT_WITH_NEGATIVE_LAMPORTS: u32 = 1;
+
+// SPL Token errors
+const TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+const TOKEN_UNINITIALIZED_STATE: u32 = 9;
+
+// Anchor's framework error for an account that was never created
+const ANCHOR_ACCOUNT_NOT_INITIALIZED: u32 = 3012;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SimulationError {
+    #[error("Simulation failed: slippage tolerance exceeded in {program}")]
+    SlippageExceeded { program: String },
+    #[error("Simulation failed: insufficient funds ({0})")]
+    InsufficientFunds(String),
+    #[error("Simulation failed: account not initialized ({0})")]
+    AccountNotInitialized(String),
+    #[error(
+        "Simulation failed: {program} returned error {code} in instruction {instruction}{}",
+        .message.as_ref().map(|m| format!(": {}", m)).unwrap_or_default()
+    )]
+    ProgramError { program: String, instruction: u8, code: u32, message: Option<String> },
+    #[error("Simulation failed: {0}")]
+    Other(String),
+}
+
+impl SimulationError {
+    // Classify a simulation failure using the transaction error and the program logs
+    pub fn decode(error: &TransactionError, logs: &[String]) -> Self {
+        match error {
+            TransactionError::InsufficientFundsForFee => Self::InsufficientFunds("not enough SOL for the fee".to_string()),
+            TransactionError::InsufficientFundsForRent { account_index } => {
+                Self::InsufficientFunds(format!("account {} would fall below rent exemption", account_index))
+            },
+            TransactionError::AccountNotFound => Self::AccountNotInitialized("fee payer has never been funded".to_string()),
+            TransactionError::InstructionError(index, InstructionError::Custom(code)) => decode_custom(*index, *code, logs),
+            TransactionError::InstructionError(index, error) => decode_instruction(*index, error, logs),
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+fn decode_custom(instruction: u8, code: u32, logs: &[String]) -> SimulationError {
+    let program_id = failed_program(logs);
+    let program = program_id.map(program_name).unwrap_or_else(|| "unknown program".to_string());
+    let anchor = anchor_error(logs);
+
+    let slippage = match program_id {
+        Some(id) if id == JUPITER_PROGRAM_ID => code == JUPITER_SLIPPAGE_EXCEEDED,
+        Some(id) if id == pumpfun::PROGRAM_ID => {
+            code == PUMPFUN_TOO_MUCH_SOL_REQUIRED || code == PUMPFUN_TOO_LITTLE_SOL_RECEIVED
+        },
+        Some(id) if id == raydium::AMM_V4_PROGRAM_ID => code == RAYDIUM_AMM_V4_EXCEEDED_SLIPPAGE,
+        Some(id) if id == raydium::CPMM_PROGRAM_ID => code == RAYDIUM_CPMM_EXCEEDED_SLIPPAGE,
+        _ => false,
+    } || anchor.as_ref().is_some_and(|(name, _)| name.to_lowercase().contains("slippage"));
+    if slippage {
+        return SimulationError::SlippageExceeded { program };
+    }
+
+    if program_id == Some(solana_system_interface::program::id()) && code == SYSTEM_RESULT_WITH_NEGATIVE_LAMPORTS {
+        return SimulationError::InsufficientFunds("not enough SOL for the trade".to_string());
+    }
+    let is_token_program = program_id.is_some_and(|id| id == spl_token::id() || id == spl_token_2022::id());
+    if is_token_program && code == TOKEN_INSUFFICIENT_FUNDS {
+        return SimulationError::InsufficientFunds("token balance too low".to_string());
+    }
+    if (is_token_program && code == TOKEN_UNINITIALIZED_STATE) || code == ANCHOR_ACCOUNT_NOT_INITIALIZED {
+        return SimulationError::AccountNotInitialized(
+            anchor.map(|(_, message)| message).unwrap_or_else(|| format!("{} instruction {}", program, instruction)),
+        );
+    }
+
+    SimulationError::ProgramError {
+        program,
+        instruction,
+        code,
+        message: anchor.map(|(name, message)| format!("{} - {}", name, message)),
+    }
+}
+
+fn decode_instruction(instruction: u8, error: &InstructionError, logs: &[String]) -> SimulationError {
+    let logged = |needle: &str| logs.iter().any(|line| line.to_lowercase().contains(needle));
+    match error {
+        InstructionError::InsufficientFunds => SimulationError::InsufficientFunds(format!("instruction {}", instruction)),
+        InstructionError::UninitializedAccount => {
+            SimulationError::AccountNotInitialized(format!("instruction {}", instruction))
+        },
+        InstructionError::InvalidAccountData | InstructionError::AccountNotExecutable if logged("uninitialized") => {
+            SimulationError::AccountNotInitialized(format!("instruction {}", instruction))
+        },
+        other => SimulationError::Other(format!("instruction {}: {}", instruction, other)),
+    }
+}
+
+// The innermost program that reported failure, from "Program <id> failed: ..." lines
+fn failed_program(logs: &[String]) -> Option<Pubkey> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program ")?.split_once(" failed:"))
+        .find_map(|(id, _)| Pubkey::from_str(id).ok())
+}
+
+// Anchor programs log "AnchorError ... Error Code: <name>. Error Number: <n>. Error Message: <msg>."
+fn anchor_error(logs: &[String]) -> Option<(String, String)> {
+    let line = logs.iter().find(|line| line.contains("AnchorError"))?;
+    let name = line.split("Error Code: ").nth(1)?.split('.').next()?.trim().to_string();
+    let message = line.split("Error Message: ").nth(1)
+        .map(|m| m.trim().trim_end_matches('.').to_string())
+        .unwrap_or_default();
+    Some((name, message))
+}
+
+fn program_name(id: Pubkey) -> String {
+    let name = if id == JUPITER_PROGRAM_ID {
+        "Jupiter"
+    } else if id == pumpfun::PROGRAM_ID {
+        "PumpFun"
+    } else if id == raydium::AMM_V4_PROGRAM_ID {
+        "Raydium AMM v4"
+    } else if id == raydium::CPMM_PROGRAM_ID {
+        "Raydium CPMM"
+    } else if id == spl_token::id() {
+        "SPL Token"
+    } else if id == spl_token_2022::id() {
+        "Token-2022"
+    } else if id == spl_associated_token_account::id() {
+        "Associated Token Account"
+    } else if id == solana_system_interface::program::id() {
+        "System Program"
+    } else {
+        return id.to_string();
+    };
+    name.to_string()
+}
+
+// Limit to request given what the simulation consumed, with headroom for state
+// that changes between simulation and landing
+pub fn compute_unit_limit(units_consumed: u64, margin_percent: u32) -> u32 {
+    let with_margin = units_consumed.saturating_mul(100 + margin_percent as u64) / 100;
+    with_margin.clamp(1, MAX_COMPUTE_UNITS as u64) as u32
+}
+
+// Rewrite the SetComputeUnitLimit instruction in place. Returns false when the
+// message has none, in which case the runtime default applies.
+pub fn set_compute_unit_limit(message: &mut VersionedMessage, units: u32) -> bool {
+    let (account_keys, instructions) = match message {
+        VersionedMessage::Legacy(message) => (&message.account_keys, &mut message.instructions),
+        VersionedMessage::V0(message) => (&message.account_keys, &mut message.instructions),
+    };
+
+    // Program ids are always static keys, even in v0 messages
+    let Some(instruction) = instructions.iter_mut().find(|instruction| {
+        account_keys.get(instruction.program_id_index as usize) == Some(&solana_compute_budget_interface::id())
+            && instruction.data.first() == Some(&SET_COMPUTE_UNIT_LIMIT_TAG)
+    }) else {
+        return false;
+    };
+
+    let mut data = vec![SET_COMPUTE_UNIT_LIMIT_TAG];
+    data.extend_from_slice(&units.to_le_bytes());
+    instruction.data = data;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_compute_budget_interface::ComputeBudgetInstruction;
+    use solana_sdk::message::{v0, Message};
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn custom(code: u32) -> TransactionError {
+        TransactionError::InstructionError(2, InstructionError::Custom(code))
+    }
+
+    #[test]
+    fn decodes_slippage_per_program() {
+        let jupiter = logs(&[&format!("Program {} failed: custom program error: 0x1771", JUPITER_PROGRAM_ID)]);
+        assert_eq!(
+            SimulationError::decode(&custom(JUPITER_SLIPPAGE_EXCEEDED), &jupiter),
+            SimulationError::SlippageExceeded { program: "Jupiter".to_string() }
+        );
+
+        let pumpfun = logs(&[&format!("Program {} failed: custom program error: 0x1772", pumpfun::PROGRAM_ID)]);
+        assert_eq!(
+            SimulationError::decode(&custom(PUMPFUN_TOO_MUCH_SOL_REQUIRED), &pumpfun),
+            SimulationError::SlippageExceeded { program: "PumpFun".to_string() }
+        );
+
+        let amm = logs(&[&format!("Program {} failed: custom program error: 0x1e", raydium::AMM_V4_PROGRAM_ID)]);
+        assert_eq!(
+            SimulationError::decode(&custom(RAYDIUM_AMM_V4_EXCEEDED_SLIPPAGE), &amm),
+            SimulationError::SlippageExceeded { program: "Raydium AMM v4".to_string() }
+        );
+
+        // The same code from another program is not slippage
+        assert!(matches!(
+            SimulationError::decode(&custom(JUPITER_SLIPPAGE_EXCEEDED), &pumpfun),
+            SimulationError::ProgramError { code: 6001, .. }
+        ));
+    }
+
+    #[test]
+    fn decodes_anchor_errors_from_logs() {
+        let program = Pubkey::new_unique();
+        let lines = logs(&[
+            "Program log: AnchorError occurred. Error Code: SlippageToleranceExceeded. Error Number: 6040. Error Message: Slippage tolerance exceeded.",
+            &format!("Program {} failed: custom program error: 0x1798", program),
+        ]);
+        assert_eq!(
+            SimulationError::decode(&custom(6040), &lines),
+            SimulationError::SlippageExceeded { program: program.to_string() }
+        );
+
+        let lines = logs(&[
+            "Program log: AnchorError caused by account: pool. Error Code: AccountNotInitialized. Error Number: 3012. Error Message: The program expected this account to be already initialized.",
+        ]);
+        assert_eq!(
+            SimulationError::decode(&custom(ANCHOR_ACCOUNT_NOT_INITIALIZED), &lines),
+            SimulationError::AccountNotInitialized("The program expected this account to be already initialized".to_string())
+        );
+    }
+
+    #[test]
+    fn decodes_funds_and_account_errors() {
+        let system = logs(&[&format!("Program {} failed: custom program error: 0x1", solana_system_interface::program::id())]);
+        assert!(matches!(SimulationError::decode(&custom(1), &system), SimulationError::InsufficientFunds(_)));
+
+        let token = logs(&[&format!("Program {} failed: custom program error: 0x1", spl_token_2022::id())]);
+        assert_eq!(
+            SimulationError::decode(&custom(TOKEN_INSUFFICIENT_FUNDS), &token),
+            SimulationError::InsufficientFunds("token balance too low".to_string())
+        );
+        assert!(matches!(
+            SimulationError::decode(&custom(TOKEN_UNINITIALIZED_STATE), &token),
+            SimulationError::AccountNotInitialized(_)
+        ));
+
+        assert!(matches!(
+            SimulationError::decode(&TransactionError::InsufficientFundsForFee, &[]),
+            SimulationError::InsufficientFunds(_)
+        ));
+        assert!(matches!(
+            SimulationError::decode(&TransactionError::AccountNotFound, &[]),
+            SimulationError::AccountNotInitialized(_)
+        ));
+        assert!(matches!(
+            SimulationError::decode(&TransactionError::BlockhashNotFound, &[]),
+            SimulationError::Other(_)
+        ));
+        assert_eq!(
+            SimulationError::decode(&TransactionError::InstructionError(0, InstructionError::UninitializedAccount), &[]),
+            SimulationError::AccountNotInitialized("instruction 0".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_program_errors_keep_the_code() {
+        let error = SimulationError::decode(&custom(42), &[]);
+        assert_eq!(
+            error,
+            SimulationError::ProgramError { program: "unknown program".to_string(), instruction: 2, code: 42, message: None }
+        );
+        assert_eq!(error.to_string(), "Simulation failed: unknown program returned error 42 in instruction 2");
+    }
+
+    #[test]
+    fn compute_unit_limit_adds_margin_within_bounds() {
+        assert_eq!(compute_unit_limit(100_000, 20), 120_000);
+        assert_eq!(compute_unit_limit(100_000, 0), 100_000);
+        assert_eq!(compute_unit_limit(0, 20), 1);
+        assert_eq!(compute_unit_limit(1_300_000, 20), MAX_COMPUTE_UNITS);
+        assert_eq!(compute_unit_limit(u64::MAX, 100), MAX_COMPUTE_UNITS);
+    }
+
+    fn limit_of(message: &VersionedMessage) -> Option<u32> {
+        let instruction = message.instructions().iter()
+            .find(|instruction| instruction.data.first() == Some(&SET_COMPUTE_UNIT_LIMIT_TAG))?;
+        Some(u32::from_le_bytes(instruction.data[1..5].try_into().ok()?))
+    }
+
+    #[test]
+    fn set_compute_unit_limit_rewrites_legacy_and_v0_messages() {
+        let payer = Pubkey::new_unique();
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_price(1_000),
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+        ];
+
+        let mut legacy = VersionedMessage::Legacy(Message::new(&instructions, Some(&payer)));
+        assert!(set_compute_unit_limit(&mut legacy, 123_456));
+        assert_eq!(limit_of(&legacy), Some(123_456));
+
+        let v0 = v0::Message::try_compile(&payer, &instructions, &[], Default::default()).unwrap();
+        let mut v0 = VersionedMessage::V0(v0);
+        assert!(set_compute_unit_limit(&mut v0, 654_321));
+        assert_eq!(limit_of(&v0), Some(654_321));
+        // The price instruction is left alone
+        assert_eq!(v0.instructions().len(), 2);
+
+        let mut without_limit = VersionedMessage::Legacy(Message::new(&instructions[..1], Some(&payer)));
+        assert!(!set_compute_unit_limit(&mut without_limit, 1));
+    }
+}
@@ -0,0 +1,542 @@
+// src/state.rs - Durable position and ATH tracker storage
+// JSON snapshot plus an append-only write-ahead journal, compacted on open.
+// Writes go through one background thread so callers never block on disk I/O.
+// Per-tick tracker updates are coalesced there and flushed on a short cadence.
+
+use crate::{ATHTracker, Position};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use anyhow::{anyhow, Result};
+
+const SNAPSHOT_FILE: &str = "state.json";
+const JOURNAL_FILE: &str = "state.wal";
+const LOCK_FILE: &str = "state.lock";
+const DEFAULT_STATE_DIR: &str = ".trader_state";
+
+// Compact the journal into a fresh snapshot once it grows past this many entries
+const COMPACT_THRESHOLD: usize = 1_000;
+
+// Longest a staged tracker update waits before the writer journals it
+const TRACKER_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Everything the trader needs to resume managing open positions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistedState {
+    pub positions: HashMap<String, Position>,
+    pub trackers: HashMap<String, ATHTracker>,
+}
+
+// A single state mutation, written to the journal before it is considered durable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalEntry {
+    UpsertPosition { position: Box<Position> },
+    RemovePosition { token_address: String },
+    UpsertTracker { token_address: String, tracker: ATHTracker },
+    RemoveTracker { token_address: String },
+}
+
+impl PersistedState {
+    fn apply(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::UpsertPosition { position } => {
+                self.positions.insert(position.token_address.clone(), *position);
+            },
+            JournalEntry::RemovePosition { token_address } => {
+                self.positions.remove(&token_address);
+            },
+            JournalEntry::UpsertTracker { token_address, tracker } => {
+                self.trackers.insert(token_address, tracker);
+            },
+            JournalEntry::RemoveTracker { token_address } => {
+                self.trackers.remove(&token_address);
+            },
+        }
+    }
+}
+
+pub struct StateStore {
+    dir: PathBuf,
+    // None only while dropping, so the writer sees the channel close
+    writer: Option<Sender<WriterCommand>>,
+    writer_thread: Option<JoinHandle<()>>,
+    journal_entries: Arc<Mutex<usize>>,
+    // Held for the store's lifetime; the OS releases it if the process dies
+    _lock: File,
+}
+
+// Work for the writer thread, processed strictly in the order it was queued
+enum WriterCommand {
+    Append(String),
+    // Latest journal line for a token's tracker; replaces any still staged for it
+    StageTracker(String, String),
+    Compact(Box<PersistedState>, oneshot::Sender<Result<()>>),
+}
+
+impl StateStore {
+    // Default location, overridable with TRADER_STATE_DIR
+    pub fn default_dir() -> PathBuf {
+        std::env::var("TRADER_STATE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_STATE_DIR))
+    }
+
+    // Open the store, replay the journal over the last snapshot and compact
+    pub fn open(dir: impl AsRef<Path>) -> Result<(Self, PersistedState)> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .map_err(|e| anyhow!("Failed to create state directory {}: {}", dir.display(), e))?;
+        let lock = Self::lock_dir(&dir)?;
+
+        let mut state = Self::read_snapshot(&dir.join(SNAPSHOT_FILE))?;
+        let replayed = Self::replay_journal(&dir.join(JOURNAL_FILE), &mut state)?;
+
+        if replayed > 0 {
+            log::info!("Replayed {} journal entries from {}", replayed, dir.display());
+        }
+
+        // Fold the replayed journal into the snapshot so the next start is clean
+        Self::write_snapshot(&dir, &state)?;
+        // Append mode keeps writes at the end of the file after compaction truncates it
+        let journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(JOURNAL_FILE))?;
+        journal.set_len(0)?;
+
+        log::info!(
+            "State loaded from {}: {} positions, {} ATH trackers",
+            dir.display(), state.positions.len(), state.trackers.len()
+        );
+
+        let (writer, commands) = mpsc::channel();
+        let journal_entries = Arc::new(Mutex::new(0));
+        let writer_dir = dir.clone();
+        let writer_entries = journal_entries.clone();
+        let writer_thread = std::thread::Builder::new()
+            .name("state-writer".to_string())
+            .spawn(move || run_writer(writer_dir, journal, commands, writer_entries))
+            .map_err(|e| anyhow!("Failed to start state writer: {}", e))?;
+
+        let store = Self {
+            dir,
+            writer: Some(writer),
+            writer_thread: Some(writer_thread),
+            journal_entries,
+            _lock: lock,
+        };
+        Ok((store, state))
+    }
+
+    // Every open rewrites the snapshot and truncates the journal, so two traders on
+    // one directory would silently erase each other's positions
+    fn lock_dir(dir: &Path) -> Result<File> {
+        let path = dir.join(LOCK_FILE);
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| anyhow!("Failed to open state lock {}: {}", path.display(), e))?;
+        match lock.try_lock() {
+            Ok(()) => Ok(lock),
+            Err(TryLockError::WouldBlock) => Err(anyhow!(
+                "State directory {} is in use by another trader process; stop it, use its control API, \
+                 or point this one at a different TRADER_STATE_DIR",
+                dir.display()
+            )),
+            Err(TryLockError::Error(e)) => Err(anyhow!("Failed to lock state directory {}: {}", dir.display(), e)),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Queue a mutation for the journal. The writer appends and fsyncs it in order
+    // with every other entry; use compact() to wait for it to reach disk.
+    pub fn record(&self, entry: &JournalEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        self.send(WriterCommand::Append(line))?;
+
+        let mut entries = self.journal_entries.lock().map_err(|_| anyhow!("State journal lock poisoned"))?;
+        *entries += 1;
+        Ok(())
+    }
+
+    // Queue a tracker update that may be coalesced with later ones for the same token.
+    // The writer journals the latest within TRACKER_FLUSH_INTERVAL, or sooner if
+    // another entry is appended behind it.
+    pub fn stage_tracker(&self, token_address: &str, tracker: &ATHTracker) -> Result<()> {
+        let entry = JournalEntry::UpsertTracker { token_address: token_address.to_string(), tracker: tracker.clone() };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.send(WriterCommand::StageTracker(token_address.to_string(), line))
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.journal_entries.lock().map(|entries| *entries >= COMPACT_THRESHOLD).unwrap_or(false)
+    }
+
+    // Queue a full snapshot behind everything recorded so far; the writer saves it
+    // atomically and truncates the journal. The receiver resolves once it is on disk.
+    pub fn compact(&self, state: PersistedState) -> oneshot::Receiver<Result<()>> {
+        let (done, receiver) = oneshot::channel();
+        if let Ok(mut entries) = self.journal_entries.lock() {
+            *entries = 0;
+        }
+        if let Err(e) = self.send(WriterCommand::Compact(Box::new(state), done)) {
+            log::error!("Failed to queue state compaction: {}", e);
+        }
+        receiver
+    }
+
+    fn send(&self, command: WriterCommand) -> Result<()> {
+        self.writer.as_ref()
+            .and_then(|writer| writer.send(command).ok())
+            .ok_or_else(|| anyhow!("State writer has stopped"))
+    }
+
+    fn read_snapshot(path: &Path) -> Result<PersistedState> {
+        if !path.exists() {
+            return Ok(PersistedState::default());
+        }
+
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data)
+            .map_err(|e| anyhow!("Corrupt state snapshot {}: {}", path.display(), e))
+    }
+
+    fn replay_journal(path: &Path, state: &mut PersistedState) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let mut replayed = 0;
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // A torn final write from a crash is expected; anything after it is unusable
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(entry) => {
+                    state.apply(entry);
+                    replayed += 1;
+                },
+                Err(e) => {
+                    log::warn!("Stopping journal replay at unreadable entry: {}", e);
+                    break;
+                }
+            }
+        }
+
+        Ok(replayed)
+    }
+
+    fn write_snapshot(dir: &Path, state: &PersistedState) -> Result<()> {
+        let tmp_path = dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(serde_json::to_string_pretty(state)?.as_bytes())?;
+            tmp.sync_all()?;
+        }
+        fs::rename(&tmp_path, dir.join(SNAPSHOT_FILE))?;
+        sync_dir(dir)
+    }
+}
+
+// Drain queued writes before the store goes away
+impl Drop for StateStore {
+    fn drop(&mut self) {
+        self.writer.take();
+        if let Some(thread) = self.writer_thread.take() {
+            if thread.join().is_err() {
+                log::error!("State writer panicked");
+            }
+        }
+    }
+}
+
+// The rename is only durable once the directory entry itself reaches disk
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn run_writer(dir: PathBuf, mut journal: File, commands: Receiver<WriterCommand>, journal_entries: Arc<Mutex<usize>>) {
+    // Latest staged tracker line per token, written at flush_due or ahead of the next append
+    let mut staged: HashMap<String, String> = HashMap::new();
+    let mut flush_due: Option<Instant> = None;
+    let mut next: Option<WriterCommand> = None;
+
+    loop {
+        if flush_due.is_some_and(|due| due <= Instant::now()) {
+            write_batch(&mut journal, take_staged(&mut staged, &journal_entries));
+            flush_due = None;
+        }
+
+        let command = match (next.take(), flush_due) {
+            (Some(command), _) => command,
+            (None, None) => match commands.recv() {
+                Ok(command) => command,
+                Err(_) => break,
+            },
+            (None, Some(due)) => match commands.recv_timeout(due.saturating_duration_since(Instant::now())) {
+                Ok(command) => command,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+        };
+
+        match command {
+            WriterCommand::StageTracker(token_address, line) => {
+                staged.insert(token_address, line);
+                flush_due.get_or_insert_with(|| Instant::now() + TRACKER_FLUSH_INTERVAL);
+            },
+            WriterCommand::Append(line) => {
+                // Staged trackers were queued first, so they land first; then batch
+                // whatever else is already queued under one fsync
+                let mut batch = take_staged(&mut staged, &journal_entries);
+                flush_due = None;
+                batch.push_str(&line);
+                loop {
+                    match commands.try_recv() {
+                        Ok(WriterCommand::Append(line)) => batch.push_str(&line),
+                        Ok(other) => {
+                            next = Some(other);
+                            break;
+                        },
+                        Err(_) => break,
+                    }
+                }
+                write_batch(&mut journal, batch);
+            },
+            WriterCommand::Compact(state, done) => {
+                // The snapshot was taken after these were staged, so it already holds them
+                staged.clear();
+                flush_due = None;
+                let result = StateStore::write_snapshot(&dir, &state)
+                    .and_then(|_| journal.set_len(0).and_then(|_| journal.sync_all()).map_err(Into::into));
+                match &result {
+                    Ok(()) => log::debug!(
+                        "State compacted: {} positions, {} ATH trackers",
+                        state.positions.len(), state.trackers.len()
+                    ),
+                    Err(e) => log::error!("Failed to compact state: {}", e),
+                }
+                let _ = done.send(result);
+            },
+        }
+    }
+
+    // The store is closing: nothing staged may be lost
+    write_batch(&mut journal, take_staged(&mut staged, &journal_entries));
+}
+
+// Drain the staged tracker lines, counting them towards the next compaction
+fn take_staged(staged: &mut HashMap<String, String>, journal_entries: &Mutex<usize>) -> String {
+    if let Ok(mut entries) = journal_entries.lock() {
+        *entries += staged.len();
+    }
+    staged.drain().map(|(_, line)| line).collect()
+}
+
+fn write_batch(journal: &mut File, batch: String) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = journal.write_all(batch.as_bytes()).and_then(|_| journal.sync_data()) {
+        log::error!("Failed to persist state change: {}", e);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StrategyType, TimeExitRules};
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fast-meme-trader-state-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn position(token_address: &str, amount_tokens: u64) -> Position {
+        Position {
+            token_address: token_address.to_string(),
+            entry_price: Some(Decimal::ONE),
+            amount_tokens,
+            entry_time: Utc::now(),
+            strategy: StrategyType::ConservativeATH,
+            buy_signature: "sig".to_string(),
+            sol_in: 0.1,
+            realized_sol_out: 0.0,
+            realized_fee_lamports: 0,
+            realized_tip_lamports: 0,
+            initial_amount_tokens: amount_tokens,
+            take_profit_ladder: Vec::new(),
+            completed_tiers: Vec::new(),
+            fired_exits: Vec::new(),
+            time_exits: TimeExitRules::default(),
+            price_feed: None,
+        }
+    }
+
+    fn tracker(ath_price: Decimal) -> ATHTracker {
+        let mut tracker = ATHTracker::new(Some(Decimal::ONE), &StrategyType::ConservativeATH.params());
+        tracker.ath_price = ath_price;
+        tracker
+    }
+
+    fn upsert(position: Position) -> JournalEntry {
+        JournalEntry::UpsertPosition { position: Box::new(position) }
+    }
+
+    #[test]
+    fn journal_replays_in_order_on_reopen() {
+        let dir = temp_dir("replay");
+        {
+            let (store, state) = StateStore::open(&dir).unwrap();
+            assert!(state.positions.is_empty());
+
+            store.record(&upsert(position("AAAA", 100))).unwrap();
+            store.record(&upsert(position("BBBB", 200))).unwrap();
+            store.record(&upsert(position("AAAA", 50))).unwrap();
+            store.record(&JournalEntry::UpsertTracker { token_address: "AAAA".to_string(), tracker: tracker(Decimal::TWO) }).unwrap();
+            store.record(&JournalEntry::RemovePosition { token_address: "BBBB".to_string() }).unwrap();
+        }
+
+        let (_store, state) = StateStore::open(&dir).unwrap();
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions["AAAA"].amount_tokens, 50);
+        assert_eq!(state.trackers["AAAA"].ath_price, Decimal::TWO);
+    }
+
+    fn ticked(last_price: Decimal) -> ATHTracker {
+        let mut tracker = tracker(Decimal::TWO);
+        tracker.last_price = last_price;
+        tracker
+    }
+
+    #[test]
+    fn staged_trackers_keep_the_latest_tick() {
+        let dir = temp_dir("staged");
+        {
+            let (store, _) = StateStore::open(&dir).unwrap();
+            store.record(&JournalEntry::UpsertTracker { token_address: "AAAA".to_string(), tracker: tracker(Decimal::TWO) }).unwrap();
+            for price in ["1.1", "1.2", "1.3"] {
+                store.stage_tracker("AAAA", &ticked(Decimal::from_str_exact(price).unwrap())).unwrap();
+            }
+
+            // Flushed on the writer's cadence without anything else being written
+            std::thread::sleep(TRACKER_FLUSH_INTERVAL * 3);
+            let journal = fs::read_to_string(dir.join(JOURNAL_FILE)).unwrap();
+            assert_eq!(journal.lines().count(), 2);
+        }
+
+        let (_store, state) = StateStore::open(&dir).unwrap();
+        assert_eq!(state.trackers["AAAA"].last_price, Decimal::from_str_exact("1.3").unwrap());
+        assert_eq!(state.trackers["AAAA"].ath_price, Decimal::TWO);
+    }
+
+    #[test]
+    fn staged_trackers_are_written_before_later_entries() {
+        let dir = temp_dir("staged-order");
+        {
+            let (store, _) = StateStore::open(&dir).unwrap();
+            store.stage_tracker("AAAA", &ticked(Decimal::ONE)).unwrap();
+            store.stage_tracker("BBBB", &ticked(Decimal::ONE)).unwrap();
+            store.record(&JournalEntry::RemoveTracker { token_address: "AAAA".to_string() }).unwrap();
+            // Still staged when the store closes
+            store.stage_tracker("BBBB", &ticked(Decimal::TWO)).unwrap();
+        }
+
+        let (_store, state) = StateStore::open(&dir).unwrap();
+        assert!(!state.trackers.contains_key("AAAA"));
+        assert_eq!(state.trackers["BBBB"].last_price, Decimal::TWO);
+    }
+
+    #[test]
+    fn torn_final_entry_stops_replay() {
+        let dir = temp_dir("torn");
+        fs::create_dir_all(&dir).unwrap();
+        let entry = serde_json::to_string(&upsert(position("AAAA", 100))).unwrap();
+        fs::write(dir.join(JOURNAL_FILE), format!("{}\n{{\"op\":\"upsert_posi", entry)).unwrap();
+
+        let (_store, state) = StateStore::open(&dir).unwrap();
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions["AAAA"].amount_tokens, 100);
+    }
+
+    #[test]
+    fn compaction_snapshots_and_truncates_the_journal() {
+        let dir = temp_dir("compact");
+        {
+            let (store, _) = StateStore::open(&dir).unwrap();
+            for amount in 1..=10 {
+                store.record(&upsert(position("AAAA", amount))).unwrap();
+            }
+
+            let mut state = PersistedState::default();
+            state.positions.insert("AAAA".to_string(), position("AAAA", 10));
+            store.compact(state).blocking_recv().unwrap().unwrap();
+            assert_eq!(fs::metadata(dir.join(JOURNAL_FILE)).unwrap().len(), 0);
+            assert!(!store.needs_compaction());
+
+            // Entries after a compaction start the journal afresh, with no gap before them
+            store.record(&upsert(position("BBBB", 5))).unwrap();
+        }
+
+        let journal = fs::read_to_string(dir.join(JOURNAL_FILE)).unwrap();
+        assert!(journal.starts_with('{'));
+        assert_eq!(journal.lines().count(), 1);
+
+        let (_store, state) = StateStore::open(&dir).unwrap();
+        assert_eq!(state.positions["AAAA"].amount_tokens, 10);
+        assert_eq!(state.positions["BBBB"].amount_tokens, 5);
+    }
+
+    #[test]
+    fn compaction_is_due_after_the_threshold() {
+        let dir = temp_dir("threshold");
+        let (store, _) = StateStore::open(&dir).unwrap();
+        for _ in 0..COMPACT_THRESHOLD {
+            assert!(!store.needs_compaction());
+            store.record(&JournalEntry::RemoveTracker { token_address: "AAAA".to_string() }).unwrap();
+        }
+        assert!(store.needs_compaction());
+    }
+
+    #[test]
+    fn a_second_store_on_the_same_dir_is_refused() {
+        let dir = temp_dir("lock");
+        let (store, _) = StateStore::open(&dir).unwrap();
+
+        let error = StateStore::open(&dir).err().unwrap();
+        assert!(error.to_string().contains("in use by another trader process"), "{}", error);
+
+        // Dropping the store releases the directory
+        drop(store);
+        assert!(StateStore::open(&dir).is_ok());
+    }
+}
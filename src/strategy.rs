@@ -0,0 +1,470 @@
+// src/strategy.rs - Pluggable exit strategies
+// Built-in StrategyType presets and user strategies share the ExitStrategy trait
+
+use crate::{short, ATHTracker, Position, StrategyParams};
+use rust_decimal::Decimal;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// A single observed price for a position's token
+#[derive(Debug, Clone, Copy)]
+pub struct PriceTick {
+    pub price: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExitDecision {
+    Hold,
+    SellAll(ExitReason),
+    // Fraction of the current holding to sell, in (0, 1]
+    SellFraction(Decimal, ExitReason),
+}
+
+// The rule that caused a sell
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitRule {
+    TakeProfit,
+    StopLoss,
+    AthPullback,
+    MaxDrawdown,
+    // Zero-based index into Position::take_profit_ladder
+    TakeProfitTier(usize),
+    MaxHold,
+    MinGainWindow,
+    // A rule of a registered ExitStrategy, labelled by the strategy. Partial exits fire once
+    // per rule, so give each SellFraction its own label.
+    Strategy(String),
+    Manual,
+    Emergency,
+    // Backtest only: position still open when the price series ended
+    EndOfData,
+}
+
+// Position metrics at the moment the rule fired
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExitMetrics {
+    pub price: Decimal,
+    // None for an unpriced position
+    pub profit_percent: Option<Decimal>,
+    pub pullback_percent: Decimal,
+    pub ath_price: Decimal,
+    pub minutes_held: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitReason {
+    pub rule: ExitRule,
+    // None for manual and emergency sells, or when no price was available
+    pub metrics: Option<ExitMetrics>,
+}
+
+impl ExitReason {
+    // Reason for a rule that fired on a price tick
+    pub fn triggered(rule: ExitRule, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> Self {
+        Self {
+            rule,
+            metrics: Some(ExitMetrics {
+                price: tick.price,
+                profit_percent: position.profit_percent(tick.price),
+                pullback_percent: pullback_from_ath(tracker.ath_price, tick.price),
+                ath_price: tracker.ath_price,
+                minutes_held: minutes_held(position, tick.timestamp),
+            }),
+        }
+    }
+
+    pub fn without_metrics(rule: ExitRule) -> Self {
+        Self { rule, metrics: None }
+    }
+
+    pub fn manual() -> Self {
+        Self::without_metrics(ExitRule::Manual)
+    }
+
+    pub fn emergency() -> Self {
+        Self::without_metrics(ExitRule::Emergency)
+    }
+}
+
+impl fmt::Display for ExitRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitRule::TakeProfit => write!(f, "take profit"),
+            ExitRule::StopLoss => write!(f, "stop loss"),
+            ExitRule::AthPullback => write!(f, "ATH pullback"),
+            ExitRule::MaxDrawdown => write!(f, "max drawdown stop"),
+            ExitRule::TakeProfitTier(index) => write!(f, "take-profit tier {}", index + 1),
+            ExitRule::MaxHold => write!(f, "max hold"),
+            ExitRule::MinGainWindow => write!(f, "min-gain window"),
+            ExitRule::Strategy(name) => write!(f, "strategy '{}'", name),
+            ExitRule::Manual => write!(f, "manual"),
+            ExitRule::Emergency => write!(f, "emergency"),
+            ExitRule::EndOfData => write!(f, "end of data"),
+        }
+    }
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.metrics {
+            Some(metrics) => {
+                write!(f, "{} (", self.rule)?;
+                match metrics.profit_percent {
+                    Some(profit_percent) => write!(f, "P&L: {:.2}%", profit_percent)?,
+                    None => write!(f, "P&L: n/a")?,
+                }
+                write!(
+                    f,
+                    ", Pullback: {:.2}%, ATH: ${:.8}, Held: {}m)",
+                    metrics.pullback_percent, metrics.ath_price, metrics.minutes_held
+                )
+            },
+            None => write!(f, "{}", self.rule),
+        }
+    }
+}
+
+// Exit rule evaluated on every price tick. The tracker has already been
+// updated with the tick, so ath_price includes it.
+pub trait ExitStrategy: Send + Sync {
+    fn name(&self) -> &str;
+    fn evaluate(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision;
+}
+
+pub fn profit_percent(entry_price: Decimal, current_price: Decimal) -> Decimal {
+    if entry_price == Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (current_price - entry_price) / entry_price * Decimal::from(100)
+}
+
+pub fn pullback_from_ath(ath_price: Decimal, current_price: Decimal) -> Decimal {
+    if ath_price > Decimal::ZERO {
+        (ath_price - current_price) / ath_price * Decimal::from(100)
+    } else {
+        Decimal::ZERO
+    }
+}
+
+// Full exit decision for one tick: time rules, then the strategy with age-tightened
+// pullback. Shared by live monitoring and the backtester; tracker must already
+// include the tick.
+pub fn evaluate_exit(position: &Position, tracker: &ATHTracker, tick: &PriceTick, strategy: &dyn ExitStrategy) -> ExitDecision {
+    // Time-based exits sit in front of the profit and pullback rules
+    if let Some(rule) = check_time_exit(position, tracker, tick.timestamp) {
+        return ExitDecision::SellAll(ExitReason::triggered(rule, position, tracker, tick));
+    }
+
+    // Every other rule is measured against the entry price
+    if position.entry_price.is_none() {
+        return ExitDecision::Hold;
+    }
+
+    // Aging positions get a tighter pullback trigger; the stored tracker keeps the base value
+    let mut tracker = tracker.clone();
+    tracker.pullback_percent = effective_pullback_percent(position, tracker.pullback_percent, tick.timestamp);
+
+    match strategy.evaluate(position, &tracker, tick) {
+        // Like a ladder tier, a partial exit fires once; otherwise it would repeat every tick
+        ExitDecision::SellFraction(_, reason) if position.fired_exits.contains(&reason.rule) => ExitDecision::Hold,
+        decision => decision,
+    }
+}
+
+// Ladder tiers whose profit target is reached and that have not fired yet, lowest target first
+pub fn due_take_profit_tiers(position: &Position, current_price: Decimal) -> Vec<usize> {
+    let Some(profit_percent) = position.profit_percent(current_price) else {
+        return Vec::new();
+    };
+
+    let mut due: Vec<usize> = position.take_profit_ladder.iter()
+        .enumerate()
+        .filter(|(index, tier)| {
+            !position.completed_tiers.contains(index) && profit_percent >= tier.profit_percent
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    due.sort_by_key(|&index| position.take_profit_ladder[index].profit_percent);
+    due
+}
+
+fn minutes_held(position: &Position, now: DateTime<Utc>) -> i64 {
+    (now - position.entry_time).num_minutes()
+}
+
+pub fn max_hold_expired(position: &Position, now: DateTime<Utc>) -> bool {
+    match position.time_exits.max_hold_minutes {
+        Some(max_hold) => minutes_held(position, now) >= max_hold,
+        None => false,
+    }
+}
+
+// Max hold and min-gain-window exits
+pub fn check_time_exit(position: &Position, tracker: &ATHTracker, now: DateTime<Utc>) -> Option<ExitRule> {
+    let rules = &position.time_exits;
+    let held = minutes_held(position, now);
+
+    if max_hold_expired(position, now) {
+        log::info!("Max hold of {}m reached for {}", held, short(&position.token_address));
+        return Some(ExitRule::MaxHold);
+    }
+
+    // "Not up X% within the window": judged on the best price seen, not the current one.
+    // Unpriced positions can't measure a gain, so only max hold applies to them.
+    if let (Some(window), Some(min_gain), Some(best_gain)) =
+        (rules.min_gain_window_minutes, rules.min_gain_percent, position.profit_percent(tracker.ath_price))
+    {
+        if held >= window && best_gain < min_gain {
+            log::info!(
+                "{} never reached +{}% within {}m (best +{:.2}%)",
+                short(&position.token_address), min_gain, window, best_gain
+            );
+            return Some(ExitRule::MinGainWindow);
+        }
+    }
+
+    None
+}
+
+// Pullback trigger after age-based tightening
+pub fn effective_pullback_percent(position: &Position, base_pullback: Decimal, now: DateTime<Utc>) -> Decimal {
+    let decay = match position.time_exits.pullback_decay_per_hour {
+        Some(decay) => decay,
+        None => return base_pullback,
+    };
+    let floor = position.time_exits.min_pullback_percent.unwrap_or(Decimal::ONE).min(base_pullback);
+
+    let hours_held = Decimal::from(minutes_held(position, now).max(0)) / Decimal::from(60);
+    (base_pullback - decay * hours_held).max(floor)
+}
+
+// ATH pullback exit logic. The max-drawdown stop is checked first and ignores min profit.
+pub fn check_ath_pullback_exit(token_address: &str, tracker: &ATHTracker, current_price: Decimal) -> Option<ExitRule> {
+    let profit_percent = profit_percent(tracker.entry_price?, current_price);
+    let pullback_from_ath = pullback_from_ath(tracker.ath_price, current_price);
+    let short_address = short(token_address);
+
+    if let Some(max_drawdown) = tracker.max_drawdown_percent {
+        if profit_percent <= -max_drawdown {
+            log::info!(
+                "Max drawdown stop triggered for {}: P&L: {:.2}% (limit -{}%), ATH: ${:.8}",
+                short_address, profit_percent, max_drawdown, tracker.ath_price
+            );
+            return Some(ExitRule::MaxDrawdown);
+        }
+    }
+
+    if profit_percent >= tracker.min_profit_percent && pullback_from_ath >= tracker.pullback_percent {
+        log::info!(
+            "ATH pullback triggered for {}: Profit: {:.2}%, Pullback: {:.2}%, ATH: ${:.8}",
+            short_address, profit_percent, pullback_from_ath, tracker.ath_price
+        );
+        return Some(ExitRule::AthPullback);
+    }
+
+    None
+}
+
+// Fixed take-profit / stop-loss exits (Conservative, Aggressive)
+pub struct TakeProfitStopLoss {
+    name: String,
+    pub take_profit_percent: Decimal,
+    pub stop_loss_percent: Decimal,
+}
+
+impl TakeProfitStopLoss {
+    pub fn new(name: &str, take_profit_percent: Decimal, stop_loss_percent: Decimal) -> Self {
+        Self {
+            name: name.to_string(),
+            take_profit_percent,
+            stop_loss_percent,
+        }
+    }
+}
+
+impl ExitStrategy for TakeProfitStopLoss {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn evaluate(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision {
+        let Some(profit_percent) = position.profit_percent(tick.price) else {
+            return ExitDecision::Hold;
+        };
+        if profit_percent >= self.take_profit_percent {
+            ExitDecision::SellAll(ExitReason::triggered(ExitRule::TakeProfit, position, tracker, tick))
+        } else if profit_percent <= -self.stop_loss_percent {
+            ExitDecision::SellAll(ExitReason::triggered(ExitRule::StopLoss, position, tracker, tick))
+        } else {
+            ExitDecision::Hold
+        }
+    }
+}
+
+// Sell on a pullback from ATH once in profit (ConservativeATH, AggressiveATH).
+// Thresholds come from the position's ATHTracker.
+pub struct AthPullback {
+    name: String,
+}
+
+impl AthPullback {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string() }
+    }
+}
+
+impl ExitStrategy for AthPullback {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn evaluate(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision {
+        match check_ath_pullback_exit(&position.token_address, tracker, tick.price) {
+            Some(rule) => ExitDecision::SellAll(ExitReason::triggered(rule, position, tracker, tick)),
+            None => ExitDecision::Hold,
+        }
+    }
+}
+
+// Any combination of StrategyParams rules (Custom)
+pub struct ParameterizedExit {
+    pub params: StrategyParams,
+}
+
+impl ExitStrategy for ParameterizedExit {
+    fn name(&self) -> &str {
+        "Custom"
+    }
+
+    fn evaluate(&self, position: &Position, tracker: &ATHTracker, tick: &PriceTick) -> ExitDecision {
+        let Some(profit_percent) = position.profit_percent(tick.price) else {
+            return ExitDecision::Hold;
+        };
+
+        if let Some(take_profit) = self.params.take_profit_percent {
+            if profit_percent >= take_profit {
+                return ExitDecision::SellAll(ExitReason::triggered(ExitRule::TakeProfit, position, tracker, tick));
+            }
+        }
+        if let Some(stop_loss) = self.params.stop_loss_percent {
+            if profit_percent <= -stop_loss {
+                return ExitDecision::SellAll(ExitReason::triggered(ExitRule::StopLoss, position, tracker, tick));
+            }
+        }
+        if self.params.pullback_percent.is_some() {
+            if let Some(rule) = check_ath_pullback_exit(&position.token_address, tracker, tick.price) {
+                return ExitDecision::SellAll(ExitReason::triggered(rule, position, tracker, tick));
+            }
+        }
+
+        ExitDecision::Hold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StrategyType, TimeExitRules};
+    use chrono::Duration;
+
+    fn position(time_exits: TimeExitRules) -> Position {
+        Position {
+            token_address: "So11111111111111111111111111111111111111112".to_string(),
+            entry_price: Some(Decimal::ONE),
+            amount_tokens: 1_000,
+            entry_time: Utc::now(),
+            strategy: StrategyType::ConservativeATH,
+            buy_signature: "test".to_string(),
+            sol_in: 0.1,
+            realized_sol_out: 0.0,
+            realized_fee_lamports: 0,
+            realized_tip_lamports: 0,
+            initial_amount_tokens: 1_000,
+            take_profit_ladder: Vec::new(),
+            completed_tiers: Vec::new(),
+            fired_exits: Vec::new(),
+            time_exits,
+            price_feed: None,
+        }
+    }
+
+    // Tracker whose best price so far is `ath_price`
+    fn tracker(position: &Position, ath_price: Decimal) -> ATHTracker {
+        let mut tracker = ATHTracker::new(position.entry_price, &StrategyType::ConservativeATH.params());
+        tracker.ath_price = ath_price;
+        tracker
+    }
+
+    #[test]
+    fn max_hold_expires_at_the_limit() {
+        let position = position(TimeExitRules { max_hold_minutes: Some(60), ..Default::default() });
+        let tracker = tracker(&position, Decimal::ONE);
+
+        assert_eq!(check_time_exit(&position, &tracker, position.entry_time + Duration::minutes(59)), None);
+        assert_eq!(check_time_exit(&position, &tracker, position.entry_time + Duration::minutes(60)), Some(ExitRule::MaxHold));
+        assert!(max_hold_expired(&position, position.entry_time + Duration::hours(2)));
+    }
+
+    #[test]
+    fn no_time_rules_never_exit() {
+        let position = position(TimeExitRules::default());
+        let tracker = tracker(&position, Decimal::ONE);
+        assert_eq!(check_time_exit(&position, &tracker, position.entry_time + Duration::days(30)), None);
+    }
+
+    #[test]
+    fn min_gain_window_judges_the_best_price_seen() {
+        let position = position(TimeExitRules {
+            min_gain_window_minutes: Some(30),
+            min_gain_percent: Some(Decimal::from(20)),
+            ..Default::default()
+        });
+        let after_window = position.entry_time + Duration::minutes(30);
+
+        // Never got to +20%: out once the window closes, not before
+        let flat = tracker(&position, Decimal::new(11, 1));
+        assert_eq!(check_time_exit(&position, &flat, position.entry_time + Duration::minutes(29)), None);
+        assert_eq!(check_time_exit(&position, &flat, after_window), Some(ExitRule::MinGainWindow));
+
+        // Reached +25% at some point, so it stays even if it has since fallen back
+        let peaked = tracker(&position, Decimal::new(125, 2));
+        assert_eq!(check_time_exit(&position, &peaked, after_window), None);
+    }
+
+    #[test]
+    fn pullback_is_unchanged_without_decay() {
+        let position = position(TimeExitRules::default());
+        let later = position.entry_time + Duration::hours(10);
+        assert_eq!(effective_pullback_percent(&position, Decimal::from(8), later), Decimal::from(8));
+    }
+
+    #[test]
+    fn pullback_tightens_per_hour_down_to_the_floor() {
+        let position = position(TimeExitRules {
+            pullback_decay_per_hour: Some(Decimal::from(2)),
+            min_pullback_percent: Some(Decimal::from(3)),
+            ..Default::default()
+        });
+        let base = Decimal::from(10);
+        let at = |minutes| effective_pullback_percent(&position, base, position.entry_time + Duration::minutes(minutes));
+
+        assert_eq!(at(0), base);
+        assert_eq!(at(90), Decimal::from(7));
+        assert_eq!(at(180), Decimal::from(4));
+        assert_eq!(at(600), Decimal::from(3));
+    }
+
+    #[test]
+    fn pullback_floor_defaults_to_one_percent_and_never_loosens() {
+        let position = position(TimeExitRules { pullback_decay_per_hour: Some(Decimal::from(5)), ..Default::default() });
+        let later = position.entry_time + Duration::hours(24);
+
+        assert_eq!(effective_pullback_percent(&position, Decimal::from(10), later), Decimal::ONE);
+        // A base tighter than the floor is kept as is
+        assert_eq!(effective_pullback_percent(&position, Decimal::new(5, 1), later), Decimal::new(5, 1));
+    }
+}
@@ -0,0 +1,258 @@
+// src/trade_journal.rs - Append-only record of completed round trips
+// One JSON line per closed position, used for realized performance stats
+
+use crate::StrategyType;
+use crate::strategy::ExitReason;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use anyhow::{anyhow, Result};
+
+const JOURNAL_FILE: &str = "trades.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedTrade {
+    pub token_address: String,
+    pub buy_signature: String,
+    pub sell_signature: String,
+    pub sol_in: f64,
+    pub sol_out: f64,
+    // Network fees and Jito tips across the buy and every sell
+    pub fees_sol: f64,
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub hold_time_secs: i64,
+    pub strategy: StrategyType,
+    pub exit_reason: ExitReason,
+}
+
+impl ClosedTrade {
+    // Net SOL gained or lost after fees
+    pub fn pnl_sol(&self) -> f64 {
+        self.sol_out - self.sol_in - self.fees_sol
+    }
+
+    pub fn return_percent(&self) -> f64 {
+        if self.sol_in <= 0.0 {
+            return 0.0;
+        }
+        self.pnl_sol() / self.sol_in * 100.0
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TradeStats {
+    pub closed_trades: usize,
+    pub winning_trades: usize,
+    pub realized_pnl_sol: f64,
+    pub total_fees_sol: f64,
+    pub avg_return_percent: f64,
+    pub best_return_percent: f64,
+    pub worst_return_percent: f64,
+}
+
+impl TradeStats {
+    pub fn from_trades(trades: &[ClosedTrade]) -> Self {
+        let mut stats = TradeStats {
+            closed_trades: trades.len(),
+            ..TradeStats::default()
+        };
+        if trades.is_empty() {
+            return stats;
+        }
+
+        stats.best_return_percent = f64::MIN;
+        stats.worst_return_percent = f64::MAX;
+        let mut total_return = 0.0;
+
+        for trade in trades {
+            let pnl = trade.pnl_sol();
+            let return_percent = trade.return_percent();
+
+            if pnl > 0.0 {
+                stats.winning_trades += 1;
+            }
+            stats.realized_pnl_sol += pnl;
+            stats.total_fees_sol += trade.fees_sol;
+            total_return += return_percent;
+            stats.best_return_percent = stats.best_return_percent.max(return_percent);
+            stats.worst_return_percent = stats.worst_return_percent.min(return_percent);
+        }
+
+        stats.avg_return_percent = total_return / trades.len() as f64;
+        stats
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.closed_trades == 0 {
+            return 0.0;
+        }
+        self.winning_trades as f64 / self.closed_trades as f64 * 100.0
+    }
+}
+
+pub struct TradeJournal {
+    file: Mutex<File>,
+    trades: Mutex<Vec<ClosedTrade>>,
+}
+
+impl TradeJournal {
+    // Open (or create) the journal in dir and load existing records
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(JOURNAL_FILE);
+        let trades = Self::read_all(&path)?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow!("Failed to open trade journal {}: {}", path.display(), e))?;
+
+        log::info!("Trade journal loaded from {}: {} closed trades", path.display(), trades.len());
+
+        Ok(Self {
+            file: Mutex::new(file),
+            trades: Mutex::new(trades),
+        })
+    }
+
+    pub fn record(&self, trade: ClosedTrade) -> Result<()> {
+        let mut line = serde_json::to_string(&trade)?;
+        line.push('\n');
+
+        {
+            let mut file = self.file.lock().map_err(|_| anyhow!("Trade journal lock poisoned"))?;
+            file.write_all(line.as_bytes())?;
+            file.sync_data()?;
+        }
+
+        log::info!(
+            "Recorded closed trade {}: P&L {:.6} SOL ({:.2}%) - {}",
+            crate::short(&trade.token_address), trade.pnl_sol(), trade.return_percent(), trade.exit_reason
+        );
+
+        self.trades.lock().map_err(|_| anyhow!("Trade journal lock poisoned"))?.push(trade);
+        Ok(())
+    }
+
+    pub fn trades(&self) -> Vec<ClosedTrade> {
+        self.trades.lock().map(|trades| trades.clone()).unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> TradeStats {
+        match self.trades.lock() {
+            Ok(trades) => TradeStats::from_trades(&trades),
+            Err(_) => TradeStats::default(),
+        }
+    }
+
+    fn read_all(path: &Path) -> Result<Vec<ClosedTrade>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let mut trades = Vec::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ClosedTrade>(&line) {
+                Ok(trade) => trades.push(trade),
+                Err(e) => log::warn!("Skipping unreadable trade journal line {}: {}", line_number + 1, e),
+            }
+        }
+
+        Ok(trades)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(sol_in: f64, sol_out: f64, fees_sol: f64) -> ClosedTrade {
+        let now = Utc::now();
+        ClosedTrade {
+            token_address: "token".to_string(),
+            buy_signature: "buy".to_string(),
+            sell_signature: "sell".to_string(),
+            sol_in,
+            sol_out,
+            fees_sol,
+            entry_time: now,
+            exit_time: now,
+            hold_time_secs: 0,
+            strategy: StrategyType::default(),
+            exit_reason: ExitReason::manual(),
+        }
+    }
+
+    fn fresh_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("fast-meme-trader-journal-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn stats_count_wins_after_fees() {
+        let stats = TradeStats::from_trades(&[
+            trade(1.0, 1.5, 0.0),
+            // Up before fees, down after them
+            trade(1.0, 1.01, 0.02),
+            trade(1.0, 0.5, 0.0),
+            trade(2.0, 3.0, 0.0),
+        ]);
+
+        assert_eq!(stats.closed_trades, 4);
+        assert_eq!(stats.winning_trades, 2);
+        assert_eq!(stats.win_rate(), 50.0);
+        assert!((stats.realized_pnl_sol - 0.99).abs() < 1e-9);
+        assert!((stats.total_fees_sol - 0.02).abs() < 1e-9);
+        assert!((stats.best_return_percent - 50.0).abs() < 1e-9);
+        assert!((stats.worst_return_percent + 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_trade_is_both_best_and_worst() {
+        let stats = TradeStats::from_trades(&[trade(1.0, 0.8, 0.0)]);
+        assert!((stats.best_return_percent + 20.0).abs() < 1e-9);
+        assert_eq!(stats.best_return_percent, stats.worst_return_percent);
+        assert_eq!(stats.avg_return_percent, stats.best_return_percent);
+        assert_eq!(stats.win_rate(), 0.0);
+    }
+
+    #[test]
+    fn empty_journal_has_zeroed_stats() {
+        let stats = TradeStats::from_trades(&[]);
+        assert_eq!(stats.closed_trades, 0);
+        assert_eq!(stats.win_rate(), 0.0);
+        assert_eq!(stats.best_return_percent, 0.0);
+        assert_eq!(stats.worst_return_percent, 0.0);
+        assert_eq!(trade(0.0, 1.0, 0.0).return_percent(), 0.0);
+    }
+
+    #[test]
+    fn reopening_skips_corrupt_lines() {
+        let dir = fresh_dir("corrupt");
+        let journal = TradeJournal::open(&dir).unwrap();
+        journal.record(trade(1.0, 2.0, 0.0)).unwrap();
+        drop(journal);
+
+        // A torn write and a blank line between two good records
+        let mut file = OpenOptions::new().append(true).open(dir.join(JOURNAL_FILE)).unwrap();
+        file.write_all(b"{\"token_address\": \"half\n\n").unwrap();
+        file.write_all(format!("{}\n", serde_json::to_string(&trade(1.0, 0.5, 0.0)).unwrap()).as_bytes()).unwrap();
+        drop(file);
+
+        let reopened = TradeJournal::open(&dir).unwrap();
+        assert_eq!(reopened.trades().len(), 2);
+        assert_eq!(reopened.stats().winning_trades, 1);
+    }
+}